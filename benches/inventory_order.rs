@@ -0,0 +1,66 @@
+// Benchmarks the order the inventory is rendered in, with a lot of goods.
+// Inventories are BTreeMaps, so display_inventory_grid just walks them in order. Before that, they were HashMaps, and every frame
+// collected the inventory into a Vec and sorted it by Good::sort_key. This times both, over the same goods, to check the BTreeMap is faster.
+// There's no benchmark harness, so this is a plain program that prints its timings. Run it with cargo bench --bench inventory_order.
+
+use dull_idle_game::{GameState, Good, GoodDefinition, GoodGroup, Inventory};
+use num::{BigInt, BigRational};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+type F = BigRational;
+type I = BigInt;
+
+const REGISTERED_GOODS: usize = 2000; // How many goods to register on top of the built-in ones.
+const FRAMES: u32 = 2000; // How many frames' worth of ordering to time.
+
+// Runs the given frame FRAMES times, and returns how long it took on average.
+fn time_frames(mut frame: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        frame();
+    }
+    start.elapsed() / FRAMES
+}
+
+fn main() {
+    // Bars are the last group, and registered goods come after every built-in one, so registering bars keeps the declaration order
+    // grouped by group. That way both ways of ordering come out the same, and only their speed differs.
+    for i in 0..REGISTERED_GOODS {
+        GameState::register_good(GoodDefinition {
+            key: format!("BenchBar{i}"),
+            name: format!("Bench Bar {i}"),
+            group: GoodGroup::Bar,
+            difficulty: 0,
+            capacity: None,
+            base_price: 1,
+            time_limit_ms: None,
+            old_keys: Vec::new(),
+        })
+        .unwrap();
+    }
+    let inventory: Inventory = Good::all()
+        .into_iter()
+        .map(|good| (good, F::from(I::from(1))))
+        .collect();
+    let hashed: HashMap<Good, F> = inventory.clone().into_iter().collect();
+
+    // Each frame writes out the order it would render the goods in, so the work can't be skipped, and the orders can be compared after.
+    let mut ordered = Vec::with_capacity(inventory.len());
+    let btree = time_frames(|| {
+        ordered.clear();
+        ordered.extend(inventory.keys().copied());
+    });
+    let mut sorted = Vec::with_capacity(hashed.len());
+    let sorting = time_frames(|| {
+        let mut goods: Vec<(&Good, &F)> = hashed.iter().collect();
+        goods.sort_by_key(|(good, _amount)| good.sort_key());
+        sorted.clear();
+        sorted.extend(goods.into_iter().map(|(good, _amount)| *good));
+    });
+    assert_eq!(ordered, sorted);
+
+    println!("{} goods, averaged over {FRAMES} frames:", inventory.len());
+    println!("  walking the BTreeMap:       {btree:?} per frame");
+    println!("  sorting a HashMap's goods:  {sorting:?} per frame");
+}
@@ -0,0 +1,248 @@
+/*
+This is the achievements code. Achievements are goals for the player, like mining a thousand ore by hand.
+They aren't saved anywhere themselves. Whether one is unlocked, and how close it is, is worked out from the stats every time.
+That way an achievement added in an update is already unlocked for anyone who'd have earned it, with no migration needed.
+
+Every achievement has a target, and progress counts up towards it. Achievements that are just done or not done have a target of 1.
+Working out the progress is cheap, but the achievements tab is drawn every frame, so it's cached until the stats change.
+Check AchievementCache for more info.
+ */
+
+use crate::idle::stats::Stats;
+use egui::Ui;
+use std::fmt::{Display, Formatter};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+// How fast a solve has to be for the SpeedyFingers achievement, in seconds.
+const SPEEDY_SOLVE_SECONDS: f64 = 1.5;
+
+#[derive(PartialEq, Eq, Clone, Copy, EnumIter, Debug)]
+pub enum Achievement {
+    FirstOre,      // Mine an ore by hand.
+    Miner,         // Mine 100 ore by hand.
+    Prospector,    // Mine 1000 ore by hand.
+    SpeedyFingers, // Solve a minigame really fast.
+    Builder,       // Build a producer.
+    Industrialist, // Build 50 producers.
+}
+
+// The properties of an achievement. Same databasing as goods and producers.
+pub struct AchievementProperties {
+    pub description: &'static str, // What the player has to do, shown under the name.
+    pub target: u64,               // How much progress unlocks the achievement.
+}
+
+impl Achievement {
+    pub fn properties(&self) -> AchievementProperties {
+        match self {
+            Achievement::FirstOre => AchievementProperties {
+                description: "Mine an ore by hand.",
+                target: 1,
+            },
+            Achievement::Miner => AchievementProperties {
+                description: "Mine 100 ore by hand.",
+                target: 100,
+            },
+            Achievement::Prospector => AchievementProperties {
+                description: "Mine 1000 ore by hand.",
+                target: 1000,
+            },
+            Achievement::SpeedyFingers => AchievementProperties {
+                description: "Solve an ore minigame in under 1.5 seconds.",
+                target: 1,
+            },
+            Achievement::Builder => AchievementProperties {
+                description: "Build a producer.",
+                target: 1,
+            },
+            Achievement::Industrialist => AchievementProperties {
+                description: "Build 50 producers.",
+                target: 50,
+            },
+        }
+    }
+
+    // How far the player has got towards the achievement, capped at the target.
+    pub fn progress(&self, stats: &Stats) -> u64 {
+        let progress = match self {
+            Achievement::FirstOre | Achievement::Miner | Achievement::Prospector => {
+                stats.solves.values().sum()
+            }
+            Achievement::SpeedyFingers => u64::from(
+                stats
+                    .best_solve
+                    .values()
+                    .any(|seconds| *seconds < SPEEDY_SOLVE_SECONDS),
+            ),
+            Achievement::Builder | Achievement::Industrialist => stats.producers_built,
+        };
+        progress.min(self.properties().target)
+    }
+}
+
+impl Display for Achievement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Achievement::FirstOre => write!(f, "First Ore"),
+            Achievement::Miner => write!(f, "Miner"),
+            Achievement::Prospector => write!(f, "Prospector"),
+            Achievement::SpeedyFingers => write!(f, "Speedy Fingers"),
+            Achievement::Builder => write!(f, "Builder"),
+            Achievement::Industrialist => write!(f, "Industrialist"),
+        }
+    }
+}
+
+// How far along an achievement is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AchievementProgress {
+    pub achievement: Achievement,
+    pub progress: u64,
+    pub target: u64,
+}
+
+impl AchievementProgress {
+    pub fn is_unlocked(&self) -> bool {
+        self.progress >= self.target
+    }
+}
+
+// Works out the progress of every achievement, in the order they're listed.
+pub fn progress_all(stats: &Stats) -> Vec<AchievementProgress> {
+    Achievement::iter()
+        .map(|achievement| AchievementProgress {
+            achievement,
+            progress: achievement.progress(stats),
+            target: achievement.properties().target,
+        })
+        .collect()
+}
+
+// How many of the achievements are unlocked, from 0 to 1.
+pub fn completion(progress: &[AchievementProgress]) -> f32 {
+    if progress.is_empty() {
+        return 0.0;
+    }
+    let unlocked = progress.iter().filter(|p| p.is_unlocked()).count();
+    unlocked as f32 / progress.len() as f32
+}
+
+// Which achievements the achievements tab shows.
+#[derive(PartialEq, Eq, Clone, Copy, EnumIter, Debug, Default)]
+pub enum AchievementFilter {
+    #[default]
+    All,
+    Locked,
+    Unlocked,
+}
+
+impl AchievementFilter {
+    pub fn matches(&self, progress: &AchievementProgress) -> bool {
+        match self {
+            AchievementFilter::All => true,
+            AchievementFilter::Locked => !progress.is_unlocked(),
+            AchievementFilter::Unlocked => progress.is_unlocked(),
+        }
+    }
+}
+
+impl Display for AchievementFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AchievementFilter::All => write!(f, "All"),
+            AchievementFilter::Locked => write!(f, "Locked"),
+            AchievementFilter::Unlocked => write!(f, "Unlocked"),
+        }
+    }
+}
+
+// The achievements tab's progress, kept until the stats change. The stats count their changes, so this only has to compare a number.
+// Another game state's stats count from 0 too, so this is reset whenever the game state is swapped out. Check IdleGame::replace_game_state.
+#[derive(Default)]
+pub struct AchievementCache {
+    revision: Option<u64>, // The stats revision the progress was worked out for. None until it's first worked out.
+    progress: Vec<AchievementProgress>, // The progress of every achievement, from progress_all.
+}
+
+impl AchievementCache {
+    // The progress of every achievement, worked out again only if the stats changed since last time.
+    pub fn get(&mut self, stats: &Stats) -> &[AchievementProgress] {
+        if self.revision != Some(stats.revision()) {
+            self.progress = progress_all(stats);
+            self.revision = Some(stats.revision());
+        }
+        &self.progress
+    }
+}
+
+// The achievements tab's search box and filter. Not saved.
+#[derive(Default)]
+pub struct AchievementView {
+    pub search: String,
+    pub filter: AchievementFilter,
+    pub cache: AchievementCache,
+}
+
+impl AchievementView {
+    // Renders the achievements tab: the overall completion, the search and filter, every matching achievement, and the stats behind them.
+    pub fn ui(&mut self, ui: &mut Ui, stats: &Stats) {
+        let progress = self.cache.get(stats).to_vec();
+        let unlocked = progress.iter().filter(|p| p.is_unlocked()).count();
+        let fraction = completion(&progress);
+        ui.add(egui::ProgressBar::new(fraction).text(format!(
+            "{unlocked} of {} unlocked ({:.0}%)",
+            progress.len(),
+            fraction * 100.0
+        )));
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.search).hint_text("Search"));
+            for filter in AchievementFilter::iter() {
+                ui.selectable_value(&mut self.filter, filter, filter.to_string());
+            }
+        });
+        let search = self.search.trim().to_lowercase();
+        let shown: Vec<&AchievementProgress> = progress
+            .iter()
+            .filter(|p| self.filter.matches(p))
+            .filter(|p| {
+                search.is_empty()
+                    || p.achievement.to_string().to_lowercase().contains(&search)
+                    || p.achievement
+                        .properties()
+                        .description
+                        .to_lowercase()
+                        .contains(&search)
+            })
+            .collect();
+        if shown.is_empty() {
+            ui.label("No achievements match.");
+        }
+        egui::Grid::new("achievements_grid").show(ui, |ui| {
+            for p in shown {
+                let name = if p.is_unlocked() {
+                    egui::RichText::new(p.achievement.to_string()).strong()
+                } else {
+                    egui::RichText::new(p.achievement.to_string()).weak()
+                };
+                ui.vertical(|ui| {
+                    ui.label(name);
+                    ui.small(p.achievement.properties().description);
+                });
+                let fraction = p.progress as f32 / p.target.max(1) as f32;
+                let text = if p.target == 1 {
+                    if p.is_unlocked() { "Done" } else { "Not yet" }.to_string()
+                } else {
+                    format!("{}/{}", p.progress, p.target)
+                };
+                ui.add(
+                    egui::ProgressBar::new(fraction)
+                        .text(text)
+                        .desired_width(200.0),
+                );
+                ui.end_row();
+            }
+        });
+        ui.collapsing("Stats", |ui| stats.ui(ui));
+    }
+}
@@ -0,0 +1,85 @@
+/*
+This is the alarm code. Alarms watch the amount of a good, and go off when it drops below a low watermark or climbs above a high one.
+When an alarm goes off, the player is told, and the producers that are making things worse can be paused automatically.
+A low alarm pauses the producers that consume the good, and a high alarm pauses the producers that make it.
+Like starvation, an alarm only changes state after the amount has stayed on the other side of the watermark for a little while.
+That way an amount sitting right on the watermark doesn't spam the player every tick.
+ */
+
+use num::{BigInt, BigRational};
+use std::fmt::{Display, Formatter};
+
+type F = BigRational;
+type I = BigInt;
+
+// Which watermark an alarm went off for.
+#[derive(serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum AlarmLevel {
+    Low,  // The good dropped below the low watermark.
+    High, // The good climbed above the high watermark.
+}
+
+impl Display for AlarmLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlarmLevel::Low => write!(f, "low"),
+            AlarmLevel::High => write!(f, "high"),
+        }
+    }
+}
+
+// An alarm on a single good. Goods without watermarks never go off.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct GoodAlarm {
+    pub alarm_low: Option<F>, // The alarm goes off when the good drops below this.
+    pub alarm_high: Option<F>, // The alarm goes off when the good climbs above this.
+    pub pause_producers: bool, // Whether the producers making things worse are paused when the alarm goes off.
+    pub active: Option<AlarmLevel>, // Which watermark the alarm is currently going off for, if any.
+    pub change_timer: F, // How long the amount has been on a different side of the watermarks than active says. Check check for more info.
+}
+
+impl Default for GoodAlarm {
+    fn default() -> Self {
+        Self {
+            alarm_low: None,
+            alarm_high: None,
+            pause_producers: false,
+            active: None,
+            change_timer: F::from(I::from(0)),
+        }
+    }
+}
+
+impl GoodAlarm {
+    // Whether either watermark is set.
+    pub fn is_set(&self) -> bool {
+        self.alarm_low.is_some() || self.alarm_high.is_some()
+    }
+
+    // Which watermark the amount is past right now, ignoring the debounce.
+    pub fn level(&self, amount: &F) -> Option<AlarmLevel> {
+        match (&self.alarm_low, &self.alarm_high) {
+            (Some(low), _) if amount < low => Some(AlarmLevel::Low),
+            (_, Some(high)) if amount > high => Some(AlarmLevel::High),
+            _ => None,
+        }
+    }
+
+    // Checks the amount against the watermarks, once per tick. Returns whether the alarm changed state, in which case active says what to.
+    // The amount has to stay past (or back within) the watermarks for the debounce time before anything changes.
+    pub fn check(&mut self, amount: &F, tick_rate: &F, debounce: &F) -> bool {
+        let level = self.level(amount);
+        if level == self.active {
+            self.change_timer = F::from(I::from(0));
+            return false;
+        }
+        self.change_timer += tick_rate;
+        if &self.change_timer < debounce {
+            return false;
+        }
+        self.change_timer = F::from(I::from(0));
+        self.active = level;
+        true
+    }
+}
@@ -0,0 +1,129 @@
+/*
+This is the build queue code. Producers aren't built instantly. Buying one puts it in the build queue, where it's constructed over time.
+Only the producer at the front of the queue is being worked on. Once it's done, it becomes an element and the next one starts.
+The construction time depends on the cost of the producer, so expensive producers take longer to build.
+Auto-build lives here too. It keeps buying producers until the player owns as many as they asked for. Check AutoBuild for more info.
+ */
+
+use crate::idle::clipboard::ProducerConfig;
+use crate::idle::producers::Producer;
+use num::{BigInt, BigRational};
+use std::fmt::{Display, Formatter};
+
+type F = BigRational;
+type I = BigInt;
+
+// How long it takes to construct a producer, in seconds per dollar of cost.
+fn seconds_per_dollar() -> F {
+    F::new(I::from(1), I::from(2))
+}
+
+// How long it takes to construct a producer, in seconds.
+pub fn construction_time(producer: &Producer) -> F {
+    producer.properties().cost * seconds_per_dollar()
+}
+
+// A producer which has been paid for, but is still being constructed.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct BuildOrder {
+    pub producer: Producer, // The producer being built.
+    pub paid: F, // How much was paid for the producer. This is what's refunded if the order is cancelled.
+    pub remaining: F, // How many seconds of construction are left.
+    #[serde(default)]
+    pub config: Option<ProducerConfig>, // The configuration the producer gets once it's built, if it was pasted. Check clipboard.rs for more info.
+}
+
+impl BuildOrder {
+    pub fn new(producer: Producer, paid: F) -> Self {
+        Self {
+            producer,
+            paid,
+            remaining: construction_time(&producer),
+            config: None,
+        }
+    }
+
+    // How far along the construction is, from 0 (just started) to 1 (done).
+    pub fn progress(&self) -> F {
+        let total = construction_time(&self.producer);
+        if total <= F::from(I::from(0)) {
+            return F::from(I::from(1));
+        }
+        (F::from(I::from(1)) - &self.remaining / total)
+            .clamp(F::from(I::from(0)), F::from(I::from(1)))
+    }
+}
+
+// Returned when the player tries to buy something they can't afford.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InsufficientFunds {
+    pub cost: F,      // How much the thing costs.
+    pub available: F, // How much money the player has.
+}
+
+impl Display for InsufficientFunds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Not enough money: costs {:.0}, but you only have {:.0}",
+            self.cost.ceil(),
+            self.available.floor()
+        )
+    }
+}
+
+// How often auto-build checks its targets, in seconds. At most one of each producer is bought per check, so money isn't drained all at once.
+fn auto_build_interval() -> F {
+    F::from(I::from(2))
+}
+
+// Buys producers automatically whenever the player owns fewer than they want.
+// Producers still being built count as owned, so auto-build doesn't keep buying while the first one is under construction.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AutoBuild {
+    pub targets: Vec<(Producer, usize)>, // How many of each producer the player wants. A list rather than a map, since producers can't be JSON keys.
+    pub paused: bool, // Whether auto-build is paused. The targets are kept, nothing just gets bought.
+    pub timer: F,     // How long it's been since the targets were last checked.
+}
+
+impl Default for AutoBuild {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            paused: false,
+            timer: F::from(I::from(0)),
+        }
+    }
+}
+
+impl AutoBuild {
+    // How many of a producer the player wants. Producers without a target are at zero, so they're never bought.
+    pub fn target(&self, producer: Producer) -> usize {
+        self.targets
+            .iter()
+            .find(|(p, _)| *p == producer)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    // Sets how many of a producer the player wants. Zero removes the target.
+    pub fn set_target(&mut self, producer: Producer, count: usize) {
+        self.targets.retain(|(p, _)| *p != producer);
+        if count > 0 {
+            self.targets.push((producer, count));
+        }
+    }
+
+    // Moves the timer along by a tick. Returns whether it's time to check the targets.
+    pub fn tick(&mut self, tick_rate: &F) -> bool {
+        if self.paused || self.targets.is_empty() {
+            return false;
+        }
+        self.timer += tick_rate;
+        if self.timer < auto_build_interval() {
+            return false;
+        }
+        self.timer -= auto_build_interval();
+        true
+    }
+}
@@ -0,0 +1,116 @@
+/*
+This is the chain planner. Given a good and a rate, it works out which producers it takes to make that much of the good every second,
+along with everything they use, all the way up the chain. "5 Iron Ore a second" comes out as 5 coal drills on iron ore, plus the coal drills on coal
+that keep them fed, plus however many coal drills on coal it takes to keep those fed too.
+
+It walks the recipe graph from the target up:
+- Every good starts off needing nothing, except the target, which needs the rate.
+- Whenever a good needs more than the planned producers make of it, enough of its recipe is added to cover the difference, rounding up.
+- Each producer added makes more of its outputs, and needs more of its inputs, so that goes on until every good is covered.
+A good's recipe is the one of the buildable producer that makes the most of it per dollar, after taking out what it uses of the good itself.
+Debug producers are never used, since a gravity drill would make everything for free.
+Goods that nothing buildable makes can't be planned for. They're listed in the plan, so the player knows which part of the chain is missing.
+The plan stands on its own. It doesn't count on anything that's already built, so the chain keeps up its rate whatever happens to the rest of the factory.
+
+Building a plan goes through the usual purchase code. Check GameState::build_chain for what happens when it's more than the player can afford.
+ */
+
+use crate::idle::goods::Good;
+use crate::idle::producers::{Producer, ProducerCategory};
+use num::{BigInt, BigRational};
+use std::collections::BTreeMap;
+
+type F = BigRational;
+type I = BigInt;
+
+// How many times the planner adds to the chain before giving up. Chains are only as deep as the recipe graph, so this is only hit by cycles
+// where a recipe needs more of its own good than it can make up for. Check the top of this file for more info.
+const MAX_ROUNDS: usize = 64;
+
+// The producers it takes to make a good at a rate. Check the top of this file for how it's worked out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainPlan {
+    pub target: Good,                         // The good the chain makes.
+    pub rate: F, // How much of the target the chain makes per second, at least.
+    pub producers: BTreeMap<Producer, usize>, // How many of each producer the chain needs.
+    pub unmet: Vec<Good>, // The goods the chain needs that no buildable producer makes. Empty if the chain is complete.
+}
+
+// The producer whose recipe the planner uses to make a good, and how much of the good it makes per second after what it uses of it.
+fn producer_for(good: Good) -> Option<(Producer, F)> {
+    let zero = F::from(I::from(0));
+    Producer::buildable()
+        .into_iter()
+        .filter_map(|producer| {
+            let properties = producer.properties();
+            if properties.category == ProducerCategory::Debug {
+                return None;
+            }
+            let net = properties.recipe.net(good);
+            (net > zero).then_some((producer, net))
+        })
+        // The most per dollar. Free producers beat everything. Ties go to whichever comes first in the build menu.
+        .min_by(|(a, a_net), (b, b_net)| {
+            (a.properties().cost / a_net).cmp(&(b.properties().cost / b_net))
+        })
+}
+
+impl ChainPlan {
+    // Plans a chain making the target at the given rate, per second.
+    pub fn new(target: Good, rate: &F) -> Self {
+        let zero = F::from(I::from(0));
+        let mut producers: BTreeMap<Producer, usize> = BTreeMap::new();
+        let mut unmet = Vec::new();
+        // How much of each good is made and needed, per second, by everything planned so far.
+        let mut made: BTreeMap<Good, F> = BTreeMap::new();
+        let mut needed: BTreeMap<Good, F> = BTreeMap::new();
+        needed.insert(target, rate.clone());
+        for _ in 0..MAX_ROUNDS {
+            let short = needed.iter().find_map(|(good, amount)| {
+                let shortfall = amount - made.get(good).unwrap_or(&zero);
+                (shortfall > zero && !unmet.contains(good)).then_some((*good, shortfall))
+            });
+            let Some((good, shortfall)) = short else {
+                break;
+            };
+            let Some((producer, net)) = producer_for(good) else {
+                unmet.push(good);
+                continue;
+            };
+            let count = (shortfall / net).ceil();
+            let recipe = producer.properties().recipe;
+            for (output, amount) in recipe.outputs.iter() {
+                *made.entry(*output).or_default() += amount * &count;
+            }
+            for (input, amount) in recipe.inputs.iter() {
+                *needed.entry(*input).or_default() += amount * &count;
+            }
+            // Counts too big for a usize are as good as endless, so they stop at usize::MAX instead of overflowing.
+            let planned = producers.entry(producer).or_default();
+            *planned = planned.saturating_add(
+                num::ToPrimitive::to_usize(&count.to_integer()).unwrap_or(usize::MAX),
+            );
+        }
+        ChainPlan {
+            target,
+            rate: rate.clone(),
+            producers,
+            unmet,
+        }
+    }
+
+    // How much the whole chain costs to build.
+    pub fn cost(&self) -> F {
+        self.producers
+            .iter()
+            .map(|(producer, count)| producer.properties().cost * F::from(I::from(*count)))
+            .sum()
+    }
+
+    // How many producers there are in the whole chain. Stops at usize::MAX, same as the counts themselves.
+    pub fn count(&self) -> usize {
+        self.producers
+            .values()
+            .fold(0, |total, count| total.saturating_add(*count))
+    }
+}
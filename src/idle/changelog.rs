@@ -0,0 +1,56 @@
+/*
+This is the changelog code. When a save from an older version is loaded, the player gets a "What's new" window listing what changed since.
+The entries are keyed by save version, so the window shows every entry newer than the version the save was written in.
+Once the window has been shown, the save is stamped with the current version, so it doesn't come up again.
+It's just a window, not a modal dialog, so the game keeps running behind it and it can be closed whenever.
+
+Whenever SAVE_VERSION is bumped, add an entry for the new version here too, even if the change is too small to be worth much.
+ */
+
+use crate::idle::save::SAVE_VERSION;
+
+// What changed in each version, oldest first. Each entry is (version, lines). There should be one entry for every version from 1 up.
+pub const CHANGELOG: [(u32, &[&str]); SAVE_VERSION as usize] = [
+    (
+        1,
+        &["Saves now record their version, so old saves keep working after updates."],
+    ),
+    (
+        2,
+        &[
+            "The selected tab is remembered between sessions.",
+            "Producers can be filtered by region, and the filter is remembered too.",
+        ],
+    ),
+];
+
+// The changelog entries newer than the given version, oldest first. Empty if the version is current.
+pub fn entries_since(version: u32) -> Vec<(u32, &'static [&'static str])> {
+    CHANGELOG
+        .iter()
+        .filter(|(entry, _)| *entry > version)
+        .copied()
+        .collect()
+}
+
+// Renders the "What's new" window for the entries since the given version. Returns whether the player dismissed it.
+pub fn display_whats_new(ctx: &egui::Context, version: u32) -> bool {
+    let mut open = true;
+    let mut dismissed = false;
+    egui::Window::new("What's new")
+        .id(egui::Id::new("whats_new"))
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            for (entry, lines) in entries_since(version).iter().rev() {
+                ui.strong(format!("Version {entry}"));
+                for line in lines.iter() {
+                    ui.label(format!("• {line}"));
+                }
+            }
+            if ui.button("Got it").clicked() {
+                dismissed = true;
+            }
+        });
+    dismissed || !open
+}
@@ -0,0 +1,108 @@
+/*
+This is the producer clipboard code. A producer's configuration can be copied to the clipboard as JSON, and pasted back in as a new producer.
+Only the configuration is copied, not the running state, so buffers, warmup and starvation all start fresh in the copy.
+Pasting buys the copy like any other producer, so the clipboard can't be used to get producers for free.
+The JSON is meant to be shared, so anything pasted is checked before it's used. Check ProducerConfig::from_json for more info.
+ */
+
+use crate::idle::element::{ElemVariant, Element, BUFFER_SECONDS};
+use crate::idle::producers::Producer;
+use std::fmt::{Display, Formatter};
+
+// A producer's configuration, without any of its running state.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProducerConfig {
+    pub producer: Producer,                       // What kind of producer it is.
+    pub enabled: bool,                            // Whether the producer is running.
+    pub discard_excess: bool, // Whether the producer keeps running when its outputs are full.
+    pub sell_output: bool,    // Whether the producer sells its output directly.
+    pub priority: i32,        // How important the producer is to production targets.
+    pub region: Option<String>, // The region the producer belongs to, if any.
+    pub count_in_table: bool, // Whether the producer shows up in the production table.
+    pub max_throughput: Option<num::BigRational>, // The most the producer can make per second, if it's capped.
+    pub buffer_seconds: num::BigRational, // How many seconds of each input the producer's input buffer holds.
+}
+
+impl Default for ProducerConfig {
+    fn default() -> Self {
+        Self {
+            producer: Producer::None,
+            enabled: true,
+            discard_excess: false,
+            sell_output: false,
+            priority: 0,
+            region: None,
+            count_in_table: true,
+            max_throughput: None,
+            buffer_seconds: num::BigRational::from(num::BigInt::from(BUFFER_SECONDS)),
+        }
+    }
+}
+
+// The ways pasting a producer can go wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasteError {
+    Malformed(String),      // The text isn't JSON, or isn't a producer's configuration.
+    NotBuildable(Producer), // The producer can't be built, so it can't be pasted either.
+}
+
+impl Display for PasteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PasteError::Malformed(reason) => write!(f, "That isn't a producer: {reason}"),
+            PasteError::NotBuildable(producer) => write!(f, "{producer} can't be built"),
+        }
+    }
+}
+
+impl ProducerConfig {
+    // Reads the configuration of an element. Only producers have one.
+    pub fn of(element: &Element) -> Option<Self> {
+        match element.variant {
+            ElemVariant::Producer(producer) => Some(Self {
+                producer,
+                enabled: element.enabled,
+                discard_excess: element.discard_excess,
+                sell_output: element.sell_output,
+                priority: element.priority,
+                region: element.region.clone(),
+                count_in_table: element.count_in_table,
+                max_throughput: element.max_throughput.clone(),
+                buffer_seconds: element.buffer_seconds.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    // Applies the configuration to a freshly built element. The producer itself isn't changed, since the element was built as one already.
+    // Disabling goes through set_enabled, so the element behaves the same as if the player had unticked it.
+    pub fn apply(&self, element: &mut Element) {
+        element.set_enabled(self.enabled);
+        element.discard_excess = self.discard_excess;
+        element.sell_output = self.sell_output;
+        element.priority = self.priority;
+        element.region = self.region.clone();
+        element.count_in_table = self.count_in_table;
+        element.max_throughput = self.max_throughput.clone();
+        element.buffer_seconds = self.buffer_seconds.clone();
+    }
+
+    // The configuration as JSON, for copying to the clipboard.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self)
+            .expect("a producer's configuration should always be serializable")
+    }
+
+    // Reads a configuration from JSON, as pasted from the clipboard.
+    // Missing fields get their defaults, the producer included. That's Producer::None, which can't be built,
+    // so a paste without a producer is refused the same as any other producer that can't be built.
+    pub fn from_json(json: &str) -> Result<Self, PasteError> {
+        let config: Self =
+            serde_json::from_str(json.trim()).map_err(|e| PasteError::Malformed(e.to_string()))?;
+        if !Producer::buildable().contains(&config.producer) {
+            return Err(PasteError::NotBuildable(config.producer));
+        }
+        Ok(config)
+    }
+}
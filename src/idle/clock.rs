@@ -0,0 +1,21 @@
+/*
+This is the clock code. Anything in the game that needs to know the real time asks a Clock, instead of asking chrono directly.
+That way tests can use a fake clock, and check things like the tick budget without depending on how fast the machine is.
+chrono is used instead of std::time::Instant, since Instant doesn't work on the web.
+ */
+
+use chrono::{DateTime, Utc};
+
+// Something that can tell the time.
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+// The real clock. This is what the game uses.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
@@ -0,0 +1,61 @@
+/*
+This is the crafting code. Crafting is making a producer's recipe by hand, straight out of the inventory, without building the producer.
+There's no separate list of recipes: every producer has a Recipe of what it uses and makes in a second, so one craft is one second of that producer.
+Counting how many fit and moving the goods is up to the Recipe itself. This only decides which recipes can be crafted. Check recipe.rs.
+Producers with no inputs (drills on nothing) are left out, since there'd be nothing to make them from, and crafting would make goods for free.
+Crafting follows the same rules as producers: it's all or nothing, and a full output stops the whole recipe, same as it stalls a producer.
+The good windows list the recipes that make their good. Check IdleGame::display_craft_controls.
+ */
+
+use crate::idle::goods::{Good, Inventory, StorageLevels};
+use crate::idle::producers::Producer;
+use num::{BigInt, BigRational};
+
+type F = BigRational;
+
+// Every recipe that makes a good, as the producers whose recipe it is. They're in the build menu's order.
+pub fn recipes_for(good: Good) -> Vec<Producer> {
+    Producer::buildable()
+        .into_iter()
+        .filter(|producer| {
+            let recipe = producer.properties().recipe;
+            !recipe.inputs.is_empty() && recipe.outputs.contains_key(&good)
+        })
+        .collect()
+}
+
+// How many times a recipe can be made right now. Every input has to be there, and every output has to fit.
+pub fn max_crafts(producer: Producer, inventory: &Inventory, storage: &StorageLevels) -> u64 {
+    let recipe = producer.properties().recipe;
+    if recipe.inputs.is_empty() {
+        return 0;
+    }
+    recipe.max_times(inventory, storage)
+}
+
+// The inputs there isn't enough of to make a recipe even once, in the order the recipe lists them.
+pub fn missing_inputs(producer: Producer, inventory: &Inventory) -> Vec<Good> {
+    producer
+        .properties()
+        .recipe
+        .missing_inputs(inventory, &F::from(BigInt::from(1)))
+}
+
+// Makes a recipe up to count times, taking the inputs out of the inventory and putting the outputs in. Returns how many were made.
+// It stops at max_crafts, so nothing is ever taken without everything it makes fitting.
+pub fn craft(
+    producer: Producer,
+    count: u64,
+    inventory: &mut Inventory,
+    storage: &StorageLevels,
+) -> u64 {
+    let count = count.min(max_crafts(producer, inventory, storage));
+    if count == 0 {
+        return 0;
+    }
+    let recipe = producer.properties().recipe;
+    let times = F::from(BigInt::from(count));
+    recipe.take_inputs(inventory, &times);
+    recipe.store_outputs(inventory, &times, storage);
+    count
+}
@@ -7,10 +7,24 @@ The window_id is used to identify the window, and is_open is used to determine w
 
 // Goods and Producers are currently the two types of elements, and they are stored elsewhere.
 // Their behavior is defined in their respective files.
-use crate::idle::{goods, producers};
+use crate::idle::clipboard::ProducerConfig;
+use crate::idle::goods::{Good, Inventory, StorageLevels};
+use crate::idle::hooks::ProducerHook;
+use crate::idle::lib::{format_si, round_money, round_money_up};
+use crate::idle::settings::{EconomySettings, LogisticsMode, RateUnit, SimSettings};
+use crate::idle::widgets::NumericEdit;
+use crate::idle::{goods, graph, producers};
+use num::{BigInt, BigRational, ToPrimitive};
+use std::collections::BTreeSet;
+
+type F = BigRational;
+type I = BigInt;
+
+// How many seconds worth of inputs a producer's input buffer holds, until the player changes it. Only used with buffered logistics.
+pub(crate) const BUFFER_SECONDS: i32 = 5;
 
 // The ElemVariant enum is used to store and describe the different types of elements.
-#[derive(serde::Serialize, serde::Deserialize, Debug, Default)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
 pub enum ElemVariant {
     #[default]
     Blank, // Blank elements exist for testing purposes, and should (probably) never be used in the actual game.
@@ -24,7 +38,7 @@ pub enum ElemVariant {
 // The Element struct is used to store and describe the elements.
 // This only contains the variant and properties which are common across all elements.
 // Right now, only window_id and is_open are common across all elements. This may change in the future.
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct Element {
     pub variant: ElemVariant, // The variant of the element.
@@ -34,6 +48,32 @@ pub struct Element {
     // The ID is how egui identifies the window, and if you change it, egui will create a new window.
     // If two elements have the same ID, egui will not be able to tell them apart, and will act very strangely.
     pub is_open: bool, // Whether the window is open or not. Allows windows to be closed.
+    #[serde(skip)]
+    pub just_opened: bool, // Whether the window was opened since it was last rendered. It's brought into view and to the front on its next render.
+    pub window_pos: Option<[f32; 2]>, // Where the window was last shown, so it's put back there after a restart. None until it's been shown.
+    pub window_size: Option<[f32; 2]>, // How big the window's contents were last shown. Same as window_pos.
+    pub warmup_remaining: F, // How many seconds are left before a producer reaches full output. Ticks down to zero.
+    pub enabled: bool, // Whether the element is running. Disabled producers don't produce or consume anything.
+    pub input_buffer: Inventory, // The goods a producer has pulled out of the inventory, waiting to be consumed. Only used with buffered logistics.
+    pub output_buffer: Inventory, // The goods a producer has made, waiting to be moved into the inventory. Only used with buffered logistics.
+    // Whatever doesn't fit in the inventory backs up in here, until the buffer is full and the producer stalls.
+    pub buffer_seconds: F, // How many seconds worth of each input the input buffer holds, and of each output the output buffer holds. Check buffer_levels.
+    #[serde(skip)]
+    pub buffer_inflow: Inventory, // How fast each input was moved into the input buffer last tick, per second. Shown next to the buffer's fill bar.
+    pub has_produced: bool, // Whether the producer has ever made anything. Used so the first production hook only fires once. Check hooks.rs.
+    pub was_starved: bool, // Whether the player was last told that this producer is starved. Used so starvation is only announced once.
+    #[serde(skip)]
+    pub missing_inputs: Vec<Good>, // The inputs the producer was short on last tick. Empty if it was fed. Shown in the producer's window.
+    pub starvation_change_timer: F, // How long the producer has been in a different starvation state than was_starved. Check tick for more info.
+    pub discard_excess: bool, // Whether the producer keeps running when its outputs are full, throwing away what doesn't fit. Otherwise it stalls.
+    pub sell_output: bool, // Whether the producer's output is sold for money as it's made, instead of going into the inventory.
+    pub priority: i32, // How important the producer is. Production targets pause the least important producers first. Check targets.rs.
+    pub sale_remainder: F, // Money from selling the output that hasn't been paid yet, because it doesn't add up to a cent. Check pay_sale.
+    pub region: Option<String>, // The region the element belongs to, if any. Regions are purely for organizing, and don't change the simulation.
+    pub count_in_table: bool, // Whether the producer shows up in the production table. Purely for debugging, it still produces either way.
+    pub max_throughput: Option<F>, // The most the producer can make per second, no matter how many inputs it has. None is uncapped. Check throughput_scale.
+    #[serde(skip)]
+    pub region_draft: String, // The name typed into the "new region" box in the element's window. Not saved.
 }
 
 // Since we need to serialize and deserialize the elements, we need to implement the Serialize and Deserialize traits.
@@ -43,18 +83,578 @@ impl Default for Element {
         // Note: This is really, really bad. Never use default element for anything.
         // If you have to, change it immediately, or create only one element with this default.
         // This is because it has a default window_id, which will cause problems if you have multiple elements with the same window_id.
-        // I might be able to fix this by making the window_id an Option<String>, or by making a next_window_id function.
+        // Real elements are made through GameState::add_element, which builds the window_id from a counter that's never reused.
         Element {
             variant: ElemVariant::Blank,
             window_id: String::from(""),
             is_open: true,
+            just_opened: false,
+            window_pos: None,
+            window_size: None,
+            warmup_remaining: F::from(I::from(0)),
+            enabled: true,
+            input_buffer: Inventory::new(),
+            output_buffer: Inventory::new(),
+            buffer_seconds: F::from(I::from(BUFFER_SECONDS)),
+            buffer_inflow: Inventory::new(),
+            has_produced: false,
+            was_starved: false,
+            missing_inputs: Vec::new(),
+            starvation_change_timer: F::from(I::from(0)),
+            discard_excess: false,
+            sell_output: false,
+            priority: 0,
+            sale_remainder: F::from(I::from(0)),
+            region: None,
+            count_in_table: true,
+            max_throughput: None,
+            region_draft: String::new(),
+        }
+    }
+}
+
+impl Element {
+    // Creates a new element. Producers start out warming up, if their properties say they need to.
+    pub fn new(variant: ElemVariant, window_id: String, is_open: bool) -> Self {
+        let warmup_remaining = match &variant {
+            ElemVariant::Producer(producer) => producer.properties().warmup,
+            _ => F::from(I::from(0)),
+        };
+        Element {
+            variant,
+            window_id,
+            is_open,
+            just_opened: is_open,
+            window_pos: None,
+            window_size: None,
+            warmup_remaining,
+            enabled: true,
+            input_buffer: Inventory::new(),
+            output_buffer: Inventory::new(),
+            buffer_seconds: F::from(I::from(BUFFER_SECONDS)),
+            buffer_inflow: Inventory::new(),
+            has_produced: false,
+            was_starved: false,
+            missing_inputs: Vec::new(),
+            starvation_change_timer: F::from(I::from(0)),
+            discard_excess: false,
+            sell_output: false,
+            priority: 0,
+            sale_remainder: F::from(I::from(0)),
+            region: None,
+            count_in_table: true,
+            max_throughput: None,
+            region_draft: String::new(),
+        }
+    }
+
+    // Opens or closes the element's window. Opening it marks it as just opened, so it shows up where the player can see it.
+    pub fn toggle_window(&mut self) {
+        self.is_open = !self.is_open;
+        self.just_opened = self.is_open;
+    }
+
+    // Enables or disables the element. Returns whether anything actually changed.
+    // Re-enabling a producer makes it warm up again from scratch, just like when it was built.
+    pub fn set_enabled(&mut self, enabled: bool) -> bool {
+        if self.enabled == enabled {
+            return false;
+        }
+        self.enabled = enabled;
+        if enabled {
+            if let ElemVariant::Producer(producer) = &self.variant {
+                self.warmup_remaining = producer.properties().warmup;
+            }
+        }
+        true
+    }
+
+    // The title shown at the top of the element's window. Unlike window_id, this is rebuilt every frame, so it can show the element's state.
+    // egui is told to use window_id as the window's ID instead of the title, so the title can change freely without egui losing track of the window.
+    // Goods show how much of them there is, and producers show whether they're off or starved.
+    // Once producers can stack or level up, the count and level go here too.
+    pub fn title(&self, inventory: &Inventory) -> String {
+        match &self.variant {
+            ElemVariant::Good(good) => {
+                let amount = inventory.get(good).cloned().unwrap_or_default();
+                format!("{} ({})", self.window_id, format_si(&amount))
+            }
+            ElemVariant::Producer(_) if !self.enabled => format!("{} (off)", self.window_id),
+            ElemVariant::Producer(_) if self.was_starved => format!("{} (starved)", self.window_id),
+            _ => self.window_id.clone(),
+        }
+    }
+
+    // Ticks the element. Only producers do anything when ticked.
+    // While a producer is warming up, its tick is scaled down by how far along the warmup is.
+    // Scaling the tick scales both the inputs and the outputs, so a warming producer doesn't waste anything.
+    // With buffered logistics, the producer works on its own buffers, and the buffers are topped up and emptied around the tick.
+    // Returns the hooks the tick fired, for the game state to handle. Check hooks.rs for more info.
+    // The throttle slows the producer down, from 0 to 1, when one of its inputs is scarce. Check GameState::throttles for more info.
+    pub fn tick(
+        &mut self,
+        inventory: &mut Inventory,
+        tick_rate: &F,
+        throttle: &F,
+        sim_settings: &SimSettings,
+        storage: &StorageLevels,
+    ) -> Vec<ProducerHook> {
+        let mut hooks = Vec::new();
+        if !self.enabled {
+            return hooks;
+        }
+        let ElemVariant::Producer(producer) = self.variant else {
+            return hooks;
+        };
+        let progress = self.warmup_progress();
+        let scaled_rate = tick_rate * progress * self.throughput_scale() * throttle;
+        // Which inputs are short is kept around, so the producer's window and the starvation notification can say what's missing.
+        // Whether there's room is checked before ticking, since the tick changes the inventory. It's only used for the first production hook.
+        let sell = self.sell(sim_settings);
+        let money_before = sell.map(|_| inventory.get(&Good::Money).cloned().unwrap_or_default());
+        let (checked, room) = match sim_settings.logistics {
+            LogisticsMode::Shared => {
+                let checked = producer.check_inputs(inventory, &scaled_rate);
+                let room = self.discard_excess
+                    || producer.room_ratio(inventory, &scaled_rate, storage, sell)
+                        > F::from(I::from(0));
+                producer.tick(inventory, &scaled_rate, self.discard_excess, storage, sell);
+                (checked, room)
+            }
+            LogisticsMode::Buffered => {
+                self.fill_input_buffer(&producer, inventory, tick_rate, sim_settings);
+                let checked = producer.check_inputs(&self.input_buffer, &scaled_rate);
+                // Outputs that don't fit in the inventory can back up in the output buffer, so there's room if they fit in either.
+                let room = self.discard_excess
+                    || self.has_output_room(&producer, inventory, &scaled_rate, storage, sell);
+                if room {
+                    producer.tick_buffered(
+                        &mut self.input_buffer,
+                        &mut self.output_buffer,
+                        &scaled_rate,
+                        sell,
+                    );
+                }
+                self.empty_output_buffer(&producer, inventory, storage);
+                (checked, room)
+            }
+        };
+        if let Some(before) = money_before {
+            self.pay_sale(inventory, before);
+        }
+        self.missing_inputs = checked.err().unwrap_or_default();
+        let starved = !self.missing_inputs.is_empty();
+        if !self.has_produced && !starved && room && scaled_rate > F::from(I::from(0)) {
+            self.has_produced = true;
+            hooks.push(ProducerHook::FirstProduction);
+        }
+        self.warmup_remaining -= tick_rate;
+        if self.warmup_remaining < F::from(I::from(0)) {
+            self.warmup_remaining = F::from(I::from(0));
+        }
+        hooks.extend(self.update_starvation(starved, tick_rate, sim_settings));
+        hooks
+    }
+
+    // Pays out a tick of selling the output directly in whole cents, same as every other sale. Check lib::round_money.
+    // A single tick usually sells for a fraction of a cent, so whatever isn't paid is kept, and paid once it adds up to a cent.
+    // The sale is whatever the tick added to the money, which only selling does, since nothing uses money as an input.
+    fn pay_sale(&mut self, inventory: &mut Inventory, before: F) {
+        let money = inventory
+            .entry(Good::Money)
+            .or_insert_with(|| F::from(I::from(0)));
+        let earned = &*money - &before + &self.sale_remainder;
+        let paid = round_money(&earned);
+        self.sale_remainder = &earned - &paid;
+        *money = before + paid;
+    }
+
+    // Keeps track of whether the producer is starved, and returns a hook when that changes.
+    // To avoid spamming the player when a producer is flapping between starved and fed every other tick,
+    // the new state has to hold for the whole debounce interval before it's announced.
+    fn update_starvation(
+        &mut self,
+        starved: bool,
+        tick_rate: &F,
+        sim_settings: &SimSettings,
+    ) -> Option<ProducerHook> {
+        if starved == self.was_starved {
+            self.starvation_change_timer = F::from(I::from(0));
+            return None;
+        }
+        self.starvation_change_timer += tick_rate;
+        if self.starvation_change_timer < sim_settings.starvation_debounce {
+            return None;
+        }
+        self.was_starved = starved;
+        self.starvation_change_timer = F::from(I::from(0));
+        if starved {
+            Some(ProducerHook::Starved(self.missing_inputs.clone()))
+        } else {
+            Some(ProducerHook::Recovered)
+        }
+    }
+
+    // Tops up the input buffer from the inventory, up to buffer_seconds worth of each input.
+    // If the inventory doesn't have enough, the buffer takes whatever is there.
+    // With a buffer fill rate set, only so much can be moved each tick, so an empty buffer takes a while to fill. Check SimSettings.
+    // How much was moved is kept as a rate per second, for the producer's window.
+    fn fill_input_buffer(
+        &mut self,
+        producer: &producers::Producer,
+        inventory: &mut Inventory,
+        tick_rate: &F,
+        sim_settings: &SimSettings,
+    ) {
+        let zero = F::from(I::from(0));
+        for (good, rate) in producer.properties().recipe.inputs.iter() {
+            let capacity = rate * &self.buffer_seconds;
+            let buffered = self
+                .input_buffer
+                .entry(*good)
+                .or_insert_with(|| zero.clone());
+            let available = inventory.entry(*good).or_insert_with(|| zero.clone());
+            let mut amount = (&capacity - &*buffered).min(available.clone());
+            if let Some(fill_rate) = &sim_settings.buffer_fill_rate {
+                amount = amount.min(rate * fill_rate * tick_rate);
+            }
+            let amount = amount.max(zero.clone());
+            *buffered += &amount;
+            *available -= &amount;
+            if tick_rate > &zero {
+                self.buffer_inflow.insert(*good, amount / tick_rate);
+            }
+        }
+    }
+
+    // How full the input buffer is, for each input, as (good, amount, capacity). Empty unless the producer has buffered anything.
+    pub fn buffer_levels(&self) -> Vec<(Good, F, F)> {
+        let ElemVariant::Producer(producer) = self.variant else {
+            return Vec::new();
+        };
+        let inputs = producer.properties().recipe.inputs;
+        self.input_buffer
+            .iter()
+            .map(|(good, amount)| {
+                let capacity = inputs
+                    .get(good)
+                    .map_or_else(|| F::from(I::from(0)), |rate| rate * &self.buffer_seconds);
+                (*good, amount.clone(), capacity)
+            })
+            .collect()
+    }
+
+    // How much of a good the output buffer holds, which is buffer_seconds worth of it. Goods the producer doesn't make have no room.
+    // Money from selling the output isn't counted, but it never needs the room, since there's no limit on money.
+    fn output_capacity(&self, producer: &producers::Producer, good: &Good) -> F {
+        producer
+            .properties()
+            .recipe
+            .outputs
+            .get(good)
+            .map_or_else(|| F::from(I::from(0)), |rate| rate * &self.buffer_seconds)
+    }
+
+    // Checks whether a whole tick of outputs fits, either in the inventory or in what's left of the output buffer.
+    fn has_output_room(
+        &self,
+        producer: &producers::Producer,
+        inventory: &Inventory,
+        tick_rate: &F,
+        storage: &StorageLevels,
+        sell: Option<&EconomySettings>,
+    ) -> bool {
+        let zero = F::from(I::from(0));
+        producer
+            .deposited_outputs(sell)
+            .iter()
+            .all(|(good, amount)| {
+                let Some(room) = good.room(inventory, storage) else {
+                    return true;
+                };
+                let buffered = self.output_buffer.get(good).unwrap_or(&zero);
+                let spare = (self.output_capacity(producer, good) - buffered).max(zero.clone());
+                amount * tick_rate <= room + spare
+            })
+    }
+
+    // How full the output buffer is, for each output that's backed up, as (good, amount, capacity).
+    // Outputs only back up when the inventory is full, so this is usually empty.
+    pub fn output_levels(&self) -> Vec<(Good, F, F)> {
+        let ElemVariant::Producer(producer) = self.variant else {
+            return Vec::new();
+        };
+        self.output_buffer
+            .iter()
+            .map(|(good, amount)| (*good, amount.clone(), self.output_capacity(&producer, good)))
+            .collect()
+    }
+
+    // Moves as much of the output buffer into the inventory as fits. The rest stays in the buffer, up to the buffer's capacity.
+    // Anything past that is thrown away, which only happens when the producer is discarding its excess, or the buffer was just shrunk.
+    fn empty_output_buffer(
+        &mut self,
+        producer: &producers::Producer,
+        inventory: &mut Inventory,
+        storage: &StorageLevels,
+    ) {
+        let zero = F::from(I::from(0));
+        for (good, amount) in std::mem::take(&mut self.output_buffer) {
+            let moved = match good.room(inventory, storage) {
+                Some(room) => amount.clone().min(room),
+                None => amount.clone(),
+            };
+            good.store(inventory, moved.clone(), storage);
+            let left = (amount - moved).min(self.output_capacity(producer, &good));
+            if left > zero {
+                self.output_buffer.insert(good, left);
+            }
+        }
+    }
+
+    // Moves everything in both buffers back into the inventory. Used when switching back to shared logistics, so nothing gets stranded.
+    // Unlike the output buffer every tick, nothing is capped here, since it would be a shame to lose goods just for switching modes.
+    pub fn flush_buffers(&mut self, inventory: &mut Inventory) {
+        for (good, amount) in std::mem::take(&mut self.output_buffer)
+            .into_iter()
+            .chain(std::mem::take(&mut self.input_buffer))
+        {
+            *inventory.entry(good).or_insert_with(|| F::from(I::from(0))) += amount;
+        }
+    }
+
+    // Calculates how close the element is to its theoretical output, from 0 to 1.
+    // Disabled elements and non-producers are at 0. Producers are scaled down while warming up.
+    // Producers stalled because their outputs are full are at 0 too, unless they're discarding the excess. Nearly full ones are somewhere in between.
+    // With buffered logistics, the inputs come from the input buffer instead of the inventory.
+    pub fn efficiency(
+        &self,
+        inventory: &Inventory,
+        tick_rate: &F,
+        sim_settings: &SimSettings,
+        storage: &StorageLevels,
+    ) -> F {
+        let ElemVariant::Producer(producer) = &self.variant else {
+            return F::from(I::from(0));
+        };
+        if !self.enabled {
+            return F::from(I::from(0));
+        }
+        let one = F::from(I::from(1));
+        let sell = self.sell(sim_settings);
+        // Nearly full outputs only leave room for part of a tick, so the producer only runs that much of it. Check Producer::tick.
+        let room = match sim_settings.logistics {
+            _ if self.discard_excess => one,
+            LogisticsMode::Shared => producer.room_ratio(inventory, tick_rate, storage, sell),
+            LogisticsMode::Buffered => {
+                if self.has_output_room(producer, inventory, tick_rate, storage, sell) {
+                    one
+                } else {
+                    F::from(I::from(0))
+                }
+            }
+        };
+        let source = match sim_settings.logistics {
+            LogisticsMode::Shared => inventory,
+            LogisticsMode::Buffered => &self.input_buffer,
+        };
+        producer.efficiency(source, tick_rate) * self.warmup_progress() * room
+    }
+
+    // The economy the producer's output is sold at, if it's selling its output directly. Check Producer::deposited_outputs.
+    pub fn sell<'a>(&self, sim_settings: &'a SimSettings) -> Option<&'a EconomySettings> {
+        self.sell_output.then_some(&sim_settings.economy)
+    }
+
+    // How much of its full rate the producer is allowed to run at, because of max_throughput. From 0 to 1, and 1 if it's uncapped.
+    // The cap applies to whichever output the producer makes the most of, and everything else (inputs too) slows down by the same amount.
+    // That way a capped producer is just a slower version of itself, and never makes more than the cap of anything.
+    pub fn throughput_scale(&self) -> F {
+        let one = F::from(I::from(1));
+        let (ElemVariant::Producer(producer), Some(cap)) = (&self.variant, &self.max_throughput)
+        else {
+            return one;
+        };
+        let Some(fastest) = producer.properties().recipe.outputs.into_values().max() else {
+            return one;
+        };
+        if fastest <= F::from(I::from(0)) {
+            return one;
+        }
+        (cap.clone().max(F::from(I::from(0))) / fastest).min(one)
+    }
+
+    // How far along the warmup is, from 0 (just built) to 1 (full output).
+    // Elements which don't warm up are always at 1.
+    pub fn warmup_progress(&self) -> F {
+        let warmup = match &self.variant {
+            ElemVariant::Producer(producer) => producer.properties().warmup,
+            _ => F::from(I::from(0)),
+        };
+        if warmup <= F::from(I::from(0)) {
+            return F::from(I::from(1));
+        }
+        let progress = F::from(I::from(1)) - &self.warmup_remaining / warmup;
+        progress.clamp(F::from(I::from(0)), F::from(I::from(1)))
+    }
+
+    // This function renders UI elements within a ui. While this expects a window, any ui will work.
+    // The efficiency is calculated by the caller, since it depends on the inventory. Check efficiency for more info.
+    // The regions are every region in the game, so the element can be moved into any of them.
+    // Returns whether anything the production table goes off was changed, so the caller knows to work it out again.
+    pub(crate) fn window_render(
+        &mut self,
+        ui: &mut egui::Ui,
+        efficiency: &F,
+        regions: &BTreeSet<String>,
+        rate_unit: RateUnit,
+    ) -> bool {
+        let mut table_changed = false;
+        self.variant.window_render(ui, rate_unit);
+        if let ElemVariant::Producer(_) = self.variant {
+            ui.label(format!(
+                "Efficiency: {:.0}%",
+                efficiency.to_f64().unwrap_or(0.0) * 100.0
+            ));
+            // Says exactly what the producer is short on, rather than just that it stalled.
+            if self.enabled && !self.missing_inputs.is_empty() {
+                ui.label(format!("Needs more {}", goods::list(&self.missing_inputs)));
+            }
+            let mut enabled = self.enabled;
+            if ui.checkbox(&mut enabled, "Enabled").changed() {
+                self.set_enabled(enabled);
+                table_changed = true;
+            }
+            // Moves the producer between regions. A region exists as long as something is in it, so new ones are made by typing a name.
+            ui.horizontal(|ui| {
+                ui.label("Region");
+                egui::ComboBox::from_id_source(format!("{}_region", self.window_id))
+                    .selected_text(self.region.as_deref().unwrap_or("None"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.region, None, "None");
+                        for region in regions.iter() {
+                            ui.selectable_value(&mut self.region, Some(region.clone()), region);
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.region_draft);
+                let name = self.region_draft.trim().to_string();
+                if ui
+                    .add_enabled(!name.is_empty(), egui::Button::new("Move to new region"))
+                    .clicked()
+                {
+                    self.region = Some(name);
+                    self.region_draft.clear();
+                }
+            });
+            ui.checkbox(&mut self.discard_excess, "Discard excess output")
+                .on_hover_text("When the outputs are full, keep running and throw away what doesn't fit, instead of stalling.");
+            ui.add(egui::DragValue::new(&mut self.priority).prefix("Priority "))
+                .on_hover_text("Production targets pause the lowest priority producers first.");
+            table_changed |= ui.checkbox(&mut self.sell_output, "Sell output directly")
+                .on_hover_text("Sells the output for money as it's made, at the current sell price, instead of storing it.")
+                .changed();
+            // Copies the producer's configuration, so it can be pasted into the build section (or sent to someone else).
+            if let Some(config) = ProducerConfig::of(self) {
+                if ui
+                    .button("Copy")
+                    .on_hover_text(
+                        "Copies this producer's settings, to paste in the build section.",
+                    )
+                    .clicked()
+                {
+                    ui.output().copied_text = config.to_json();
+                }
+            }
+            // Caps how fast the producer runs, even when it has plenty of inputs. Check throughput_scale for more info.
+            ui.horizontal(|ui| {
+                let mut capped = self.max_throughput.is_some();
+                if ui
+                    .checkbox(&mut capped, "Max output per second")
+                    .on_hover_text("Limits how much this producer makes per second, even with plenty of inputs.")
+                    .changed()
+                {
+                    self.max_throughput = capped.then(|| F::from(I::from(1)));
+                    table_changed = true;
+                }
+                if let Some(cap) = &mut self.max_throughput {
+                    table_changed |= ui.add(
+                        NumericEdit::new(
+                            &format!("{}_throughput", self.window_id),
+                            cap,
+                            F::new(I::from(1), I::from(4)),
+                        )
+                        .min(F::from(I::from(0))),
+                    ).changed();
+                }
+            });
+            table_changed |= ui.checkbox(&mut self.count_in_table, "Count in production table")
+                .on_hover_text("Leave this producer out of the inventory's production columns, to see what the rest of the factory does without it. It keeps producing.")
+                .changed();
+        }
+        // Producers with buffered logistics show how full their input buffer is, and how fast it's being filled.
+        let levels = self.buffer_levels();
+        if !levels.is_empty() {
+            for (good, amount, capacity) in levels {
+                let fill = if capacity > F::from(I::from(0)) {
+                    (&amount / &capacity).to_f32().unwrap_or(0.0)
+                } else {
+                    0.0
+                };
+                let inflow = self.buffer_inflow.get(&good).cloned().unwrap_or_default();
+                ui.add(egui::ProgressBar::new(fill).text(format!(
+                    "{good}: {:.1}/{:.1} (+{})",
+                    amount.to_f64().unwrap_or(0.0),
+                    capacity.to_f64().unwrap_or(0.0),
+                    rate_unit.format(&inflow)
+                )));
+            }
+            // Outputs only show up here when they've backed up, because the inventory is full.
+            for (good, amount, capacity) in self.output_levels() {
+                let fill = if capacity > F::from(I::from(0)) {
+                    (&amount / &capacity).to_f32().unwrap_or(0.0)
+                } else {
+                    0.0
+                };
+                ui.add(egui::ProgressBar::new(fill).text(format!(
+                    "{good} backed up: {:.1}/{:.1}",
+                    amount.to_f64().unwrap_or(0.0),
+                    capacity.to_f64().unwrap_or(0.0)
+                )));
+            }
+            ui.horizontal(|ui| {
+                ui.label("Buffer size").on_hover_text(
+                    "How many seconds of each input the buffer holds, and of each output when the inventory is full.",
+                );
+                ui.add(
+                    NumericEdit::new(
+                        &format!("{}_buffer", self.window_id),
+                        &mut self.buffer_seconds,
+                        F::from(I::from(1)),
+                    )
+                    .min(F::from(I::from(0))),
+                );
+                ui.label("seconds");
+            });
+        }
+        // Producers which are still warming up show how far along they are.
+        if self.warmup_remaining > F::from(I::from(0)) {
+            let progress = self.warmup_progress().to_f32().unwrap_or(1.0);
+            ui.add(
+                egui::ProgressBar::new(progress)
+                    .show_percentage()
+                    .text(format!("Warming up: {:.0}%", progress * 100.0)),
+            );
         }
+        table_changed
     }
 }
 
 impl ElemVariant {
     // This function renders UI elements within a ui. While this expects a window, any ui will work.
-    pub fn window_render(&self, ui: &mut egui::Ui) {
+    // Rates are shown in the player's chosen unit. Check RateUnit for more info.
+    pub(crate) fn window_render(&self, ui: &mut egui::Ui, rate_unit: RateUnit) {
         match self {
             ElemVariant::Blank => {
                 // The crab shows up as a box. I should probably enable unicode/emoji features, or change the font.
@@ -63,9 +663,25 @@ impl ElemVariant {
             }
             ElemVariant::Good(good) => {
                 ui.label(good.properties().name);
+                // The recipes that make the good are listed further down, by the caller, since crafting needs the inventory.
+                // Check IdleGame::display_craft_controls.
             }
             ElemVariant::Producer(producer) => {
-                ui.label(producer.properties().name);
+                let properties = producer.properties();
+                ui.label(properties.name);
+                // Rounded up to the cent, like every other cost. Check round_money_up.
+                ui.label(format!(
+                    "Cost: ${}",
+                    format_si(&round_money_up(&properties.cost))
+                ));
+                ui.label(format!(
+                    "Uses: {}",
+                    graph::format_flows(&properties.recipe.inputs, rate_unit)
+                ));
+                ui.label(format!(
+                    "Makes: {}",
+                    graph::format_flows(&properties.recipe.outputs, rate_unit)
+                ));
             }
         }
     }
@@ -0,0 +1,39 @@
+/*
+This is the game event code. Events are things that happen inside the simulation which the player might want to hear about.
+The game state can't show anything itself, since it doesn't know about egui, so it queues events up instead.
+The UI drains the queue every frame and turns the events into notifications.
+ */
+
+use crate::idle::alarms::AlarmLevel;
+use crate::idle::goods::{self, Good};
+
+// Something that happened in the simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    ProducerStarved(String, Vec<Good>), // A producer ran out of inputs. Holds the producer's name, and which inputs it's short on.
+    ProducerRecovered(String), // A starved producer has its inputs again. Holds the producer's name.
+    BuildFinished(String), // A producer in the build queue has finished construction. Holds the producer's name.
+    AlarmRaised(String, AlarmLevel, usize), // A good's alarm went off. Holds the good's name, which watermark it crossed, and how many producers were paused.
+    AlarmCleared(String), // A good's alarm stopped going off. Holds the good's name.
+}
+
+impl GameEvent {
+    // The message shown to the player for this event.
+    pub fn message(&self) -> String {
+        match self {
+            GameEvent::ProducerStarved(name, missing) if missing.is_empty() => {
+                format!("{name} has run out of inputs")
+            }
+            GameEvent::ProducerStarved(name, missing) => {
+                format!("{name} needs more {}", goods::list(missing))
+            }
+            GameEvent::ProducerRecovered(name) => format!("{name} is running again"),
+            GameEvent::BuildFinished(name) => format!("{name} has been built"),
+            GameEvent::AlarmRaised(name, level, 0) => format!("{name} crossed its {level} alarm"),
+            GameEvent::AlarmRaised(name, level, paused) => {
+                format!("{name} crossed its {level} alarm, paused {paused} producers")
+            }
+            GameEvent::AlarmCleared(name) => format!("{name} is back within its alarms"),
+        }
+    }
+}
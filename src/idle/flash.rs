@@ -0,0 +1,64 @@
+/*
+This is the inventory flash code. When the amount of a good changes, its row in the inventory briefly lights up and fades away.
+Green means the amount went up, and red means it went down (or whatever the palette says, for colorblind players).
+Only the amount that's actually displayed is compared, which is the floored amount. Otherwise every row would flash every tick.
+Flashes aren't saved, since they're only relevant right when they happen.
+ */
+
+use crate::idle::goods::Good;
+use crate::idle::settings::Palette;
+use num::BigInt;
+use std::collections::HashMap;
+
+type I = BigInt;
+
+// How long a flash takes to fade away, in seconds.
+const FLASH_SECONDS: f64 = 0.5;
+
+// The last displayed amount of a good, and the last time it changed.
+struct Flash {
+    previous: I,     // The amount that was displayed last frame.
+    changed_at: f64, // When the amount last changed, in egui's time (seconds since the app started).
+    increased: bool, // Whether the last change was an increase or a decrease.
+}
+
+// The flashes for every good in the inventory.
+#[derive(Default)]
+pub struct InventoryFlashes {
+    flashes: HashMap<Good, Flash>,
+}
+
+impl InventoryFlashes {
+    // Compares the displayed amount of a good with last frame's, and returns the color of the row's highlight, if it has one.
+    // The color fades out over FLASH_SECONDS after the last change.
+    pub fn update(
+        &mut self,
+        good: Good,
+        displayed: &I,
+        now: f64,
+        palette: &Palette,
+    ) -> Option<egui::Color32> {
+        // The first time a good is seen there's nothing to compare with, so it doesn't flash.
+        let flash = self.flashes.entry(good).or_insert_with(|| Flash {
+            previous: displayed.clone(),
+            changed_at: f64::NEG_INFINITY,
+            increased: true,
+        });
+        if *displayed != flash.previous {
+            flash.increased = *displayed > flash.previous;
+            flash.previous = displayed.clone();
+            flash.changed_at = now;
+        }
+        let fade = 1.0 - (now - flash.changed_at) / FLASH_SECONDS;
+        if fade <= 0.0 {
+            return None;
+        }
+        let color = if flash.increased {
+            palette.good
+        } else {
+            palette.bad
+        };
+        // The highlight is drawn behind the text, so it's kept faint even at its brightest.
+        Some(color.linear_multiply(0.4 * fade as f32))
+    }
+}
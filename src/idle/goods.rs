@@ -1,22 +1,47 @@
+use crate::idle::build::InsufficientFunds;
+use crate::idle::registry::{self, GoodId};
+use num::{BigInt, BigRational};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
+type F = BigRational;
+type I = BigInt;
+
+// How many times each group's storage has been upgraded. Groups that aren't in here haven't been upgraded at all.
+// Every upgrade doubles the capacity of every good in the group. Check GameState::upgrade_storage for the costs.
+pub type StorageLevels = HashMap<GoodGroup, u32>;
+
+// The ways a storage upgrade can go wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageError {
+    NoCapacity(GoodGroup), // Nothing in the group has a capacity, so there's nothing to upgrade.
+    InsufficientFunds(InsufficientFunds), // The upgrade costs more than the player has.
+}
+
+impl Display for StorageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::NoCapacity(group) => write!(f, "{group} has no storage to upgrade"),
+            StorageError::InsufficientFunds(e) => e.fmt(f),
+        }
+    }
+}
+
+// How much of each good there is, in the player's inventory or in a producer's buffers.
+// It's a BTreeMap so it's always iterated in the same order. Goods are declared grouped by GoodGroup, in the groups' order,
+// so that order is also the order the UI shows them in, and nothing has to be sorted to display them. validate_data checks this.
+pub type Inventory = BTreeMap<Good, F>;
+
 // An enum that describes the different types of goods.
-#[derive(
-    serde::Serialize,
-    serde::Deserialize,
-    PartialEq,
-    Eq,
-    Clone,
-    Copy,
-    EnumIter,
-    Hash,
-    PartialOrd,
-    Ord,
-    Debug,
-    Default,
-)]
+// Goods have to be declared grouped by GoodGroup, in the groups' order. Check Inventory for why.
+// Every variant has an explicit key, which is what it's saved under. Check Good::key. The display name comes from GoodProperties instead.
+// This means a variant can be renamed in the code (or rebranded in the UI) without breaking saves, so long as the key stays the same.
+// NEVER CHANGE A KEY. If a good's saved key ever has to change, put the old key in Good::old_keys so old saves still load.
+// Goods registered at runtime are Registered, and aren't in Good::iter. Check registry.rs for more info, and Good::all to get every good.
+// Serialization is written out by hand, so registered goods can be saved under their keys too. It comes out the same as serde's derive would.
+#[derive(PartialEq, Eq, Clone, Copy, EnumIter, Hash, PartialOrd, Ord, Debug, Default)]
 pub enum Good {
     #[default]
     Money,
@@ -24,8 +49,25 @@ pub enum Good {
     GoldOre,
     SilverOre,
     Coal,
+    IronBar,
+    GoldBar,
+    SilverBar,
+    #[strum(disabled)]
+    Registered(GoodId), // A good added at runtime. Check registry.rs for more info.
 }
 
+// The keys of the built-in goods, in the order they're declared. Serde wants a list of them, for its error messages.
+const BUILTIN_KEYS: &[&str] = &[
+    "Money",
+    "IronOre",
+    "GoldOre",
+    "SilverOre",
+    "Coal",
+    "IronBar",
+    "GoldBar",
+    "SilverBar",
+];
+
 // An enum for the different groups of goods. Used for iteration and defaults.
 #[derive(
     serde::Serialize,
@@ -43,14 +85,64 @@ pub enum Good {
 pub enum GoodGroup {
     Money,
     Ore,
+    Bar, // Metal bars, smelted from ores. Check Producer::Smelter.
+}
+
+// Databasing for good groups. The UI uses this to sort and tint goods the same way everywhere.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+pub struct GroupProperties {
+    pub name: &'static str,  // The name of the group
+    pub order: u32,          // Where the group goes when goods are sorted. Lower comes first.
+    pub color: (u8, u8, u8), // The color goods in this group are tinted with, as RGB. Kept as a tuple so this file doesn't need egui.
+}
+
+impl GoodGroup {
+    pub fn properties(&self) -> GroupProperties {
+        match self {
+            GoodGroup::Money => GroupProperties {
+                name: "Money",
+                order: 0,
+                color: (222, 196, 92),
+            },
+            GoodGroup::Ore => GroupProperties {
+                name: "Ores",
+                order: 1,
+                color: (176, 142, 120),
+            },
+            GoodGroup::Bar => GroupProperties {
+                name: "Bars",
+                order: 2,
+                color: (168, 178, 196),
+            },
+        }
+    }
+}
+
+impl Display for GoodGroup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.properties().name)
+    }
 }
 
 // Databasing for goods.
 #[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Debug)]
 pub struct GoodProperties {
-    pub name: &'static str, // The name of the good
-    pub group: GoodGroup,   // The group of the good
+    pub name: &'static str,         // The name of the good
+    pub group: GoodGroup,           // The group of the good
     pub difficulty: u32, // The difficulty of the good. Used for determining the minigame difficulty.
+    pub capacity: Option<u32>, // How much of the good the player can hold. None means there's no limit.
+    pub base_price: u32, // How much money one of the good sells for, before the economy settings are applied. Zero means it can't be sold.
+    pub time_limit_ms: Option<u32>, // How long the good's minigame gives to finish, in milliseconds, with timed minigames on. None is never timed.
+}
+
+// Lists goods in a sentence, like "Coal", "Coal and Iron Ore", or "Coal, Iron Ore and Gold Ore".
+pub fn list(goods: &[Good]) -> String {
+    let names: Vec<String> = goods.iter().map(|good| good.to_string()).collect();
+    match names.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {last}", rest.join(", ")),
+    }
 }
 
 impl Good {
@@ -60,41 +152,186 @@ impl Good {
                 name: "Money",
                 group: GoodGroup::Money,
                 difficulty: 0,
+                capacity: None,
+                base_price: 0,
+                time_limit_ms: None,
             },
+            // Ores give about a second per button to finish their minigame, when it's timed. Harder ores give a bit less per button.
             Good::IronOre => GoodProperties {
                 name: "Iron Ore",
                 group: GoodGroup::Ore,
                 difficulty: 3,
+                capacity: Some(10000),
+                base_price: 2,
+                time_limit_ms: Some(3000),
             },
             Good::GoldOre => GoodProperties {
                 name: "Gold Ore",
                 group: GoodGroup::Ore,
                 difficulty: 5,
+                capacity: Some(10000),
+                base_price: 10,
+                time_limit_ms: Some(4000),
             },
             Good::SilverOre => GoodProperties {
                 name: "Silver Ore",
                 group: GoodGroup::Ore,
                 difficulty: 4,
+                capacity: Some(10000),
+                base_price: 6,
+                time_limit_ms: Some(3600),
             },
             Good::Coal => GoodProperties {
                 name: "Coal",
                 group: GoodGroup::Ore,
                 difficulty: 3,
+                capacity: Some(10000),
+                base_price: 1,
+                time_limit_ms: Some(3000),
+            },
+            // Bars aren't mined, so they have no minigame, and their difficulty doesn't matter.
+            // They sell for more than the ore and coal that went into them, so smelting is worth it.
+            Good::IronBar => GoodProperties {
+                name: "Iron Bar",
+                group: GoodGroup::Bar,
+                difficulty: 0,
+                capacity: Some(5000),
+                base_price: 8,
+                time_limit_ms: None,
             },
+            Good::GoldBar => GoodProperties {
+                name: "Gold Bar",
+                group: GoodGroup::Bar,
+                difficulty: 0,
+                capacity: Some(5000),
+                base_price: 30,
+                time_limit_ms: None,
+            },
+            Good::SilverBar => GoodProperties {
+                name: "Silver Bar",
+                group: GoodGroup::Bar,
+                difficulty: 0,
+                capacity: Some(5000),
+                base_price: 18,
+                time_limit_ms: None,
+            },
+            Good::Registered(id) => registry::good_properties(*id),
+        }
+    }
+
+    // The key the good is saved under. Check the comment on Good for why this must never change.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Good::Money => "Money",
+            Good::IronOre => "IronOre",
+            Good::GoldOre => "GoldOre",
+            Good::SilverOre => "SilverOre",
+            Good::Coal => "Coal",
+            Good::IronBar => "IronBar",
+            Good::GoldBar => "GoldBar",
+            Good::SilverBar => "SilverBar",
+            Good::Registered(id) => registry::good_key(*id),
+        }
+    }
+
+    // The keys a built-in good used to be saved under, which still load as it. Saves are always written with the current key.
+    // No built-in good has been renamed yet. Registered goods list theirs in their definition. Check registry.rs.
+    pub fn old_keys(&self) -> &'static [&'static str] {
+        match self {
+            Good::Money
+            | Good::IronOre
+            | Good::GoldOre
+            | Good::SilverOre
+            | Good::Coal
+            | Good::IronBar
+            | Good::GoldBar
+            | Good::SilverBar
+            | Good::Registered(_) => &[],
+        }
+    }
+
+    // The bar an ore is smelted into, if it can be smelted. Coal is burned, not smelted. Check Producer::Smelter.
+    pub fn bar(&self) -> Option<Good> {
+        match self {
+            Good::IronOre => Some(Good::IronBar),
+            Good::GoldOre => Some(Good::GoldBar),
+            Good::SilverOre => Some(Good::SilverBar),
+            _ => None,
+        }
+    }
+
+    // Every good, built-in and registered. The built-in ones come first, in the order they're declared, then the registered ones.
+    pub fn all() -> Vec<Good> {
+        Good::iter().chain(registry::goods()).collect()
+    }
+
+    // The capacity of the good as a rational, so it can be compared with inventory amounts directly.
+    // This is the base capacity, doubled once for every storage upgrade the good's group has had.
+    pub fn capacity(&self, storage: &StorageLevels) -> Option<F> {
+        let properties = self.properties();
+        let level = storage.get(&properties.group).copied().unwrap_or(0);
+        properties
+            .capacity
+            .map(|capacity| F::from(I::from(capacity) * I::from(2).pow(level)))
+    }
+
+    // How much more of the good fits in the inventory. None means there's no limit.
+    pub fn room(&self, inventory: &Inventory, storage: &StorageLevels) -> Option<F> {
+        let zero = F::from(I::from(0));
+        let capacity = self.capacity(storage)?;
+        let amount = inventory.get(self).unwrap_or(&zero);
+        Some((capacity - amount).max(zero))
+    }
+
+    // Adds some of the good to the inventory, throwing away anything past its capacity.
+    // Anything that produces goods should go through here, so the capacity is respected everywhere.
+    pub fn store(&self, inventory: &mut Inventory, amount: F, storage: &StorageLevels) {
+        let stored = inventory
+            .entry(*self)
+            .or_insert_with(|| F::from(I::from(0)));
+        *stored += amount;
+        if let Some(capacity) = self.capacity(storage) {
+            if *stored > capacity {
+                *stored = capacity;
+            }
         }
     }
 
+    // The key goods are sorted by in the UI. Goods are grouped by their group's order, then sorted by the enum order within the group.
+    pub fn sort_key(&self) -> (u32, Good) {
+        (self.properties().group.properties().order, *self)
+    }
+
+    // Returns every good, sorted the way the UI shows them. Check sort_key for more info.
+    pub fn sorted() -> Vec<Good> {
+        let mut goods = Good::all();
+        goods.sort_by_key(|good| good.sort_key());
+        goods
+    }
+
     // Returns the default value of a good group. Currently not used.
     pub fn _default_for_group(group: GoodGroup) -> Good {
         match group {
             GoodGroup::Money => Good::Money,
             GoodGroup::Ore => Good::IronOre,
+            GoodGroup::Bar => Good::IronBar,
         }
     }
 
     // Returns an iterator of all goods in a group. Useful for UI elements.
+    // Registered goods are included, so registered ores get minigames and drills like the built-in ones.
     pub fn group_iter(group: GoodGroup) -> impl Iterator<Item = Good> {
-        Good::iter().filter(move |good| good.properties().group == group)
+        Good::all()
+            .into_iter()
+            .filter(move |good| good.properties().group == group)
+    }
+
+    // Where the good is in the list of every good. This is what serde's derive would use as the variant index.
+    fn index(&self) -> u32 {
+        Good::all()
+            .iter()
+            .position(|good| good == self)
+            .unwrap_or(0) as u32
     }
 }
 
@@ -103,3 +340,81 @@ impl Display for Good {
         write!(f, "{}", self.properties().name)
     }
 }
+
+// Goods are saved as unit variants named after their key, which is how serde's derive saved them before there were registered goods.
+// So every format writes them the same as it always has: a string in JSON and MessagePack, and a bare identifier in RON.
+impl serde::Serialize for Good {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit_variant("Good", self.index(), self.key())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Good {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_enum("Good", BUILTIN_KEYS, GoodVisitor)
+    }
+}
+
+// Reads a good from an enum, which is how every format hands over a unit variant.
+struct GoodVisitor;
+
+impl<'de> serde::de::Visitor<'de> for GoodVisitor {
+    type Value = Good;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "a good")
+    }
+
+    fn visit_enum<A: serde::de::EnumAccess<'de>>(self, data: A) -> Result<Good, A::Error> {
+        let (GoodKey(good), variant) = data.variant()?;
+        serde::de::VariantAccess::unit_variant(variant)?;
+        Ok(good)
+    }
+
+    fn visit_str<E: serde::de::Error>(self, key: &str) -> Result<Good, E> {
+        GoodKey::from_key(key)
+    }
+}
+
+// The name of a good's variant, which is its key. Some formats hand over the index instead, so that works too.
+struct GoodKey(Good);
+
+impl GoodKey {
+    fn from_key<E: serde::de::Error>(key: &str) -> Result<Good, E> {
+        registry::good_by_key(key).ok_or_else(|| E::unknown_variant(key, BUILTIN_KEYS))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for GoodKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_identifier(GoodKeyVisitor)
+            .map(GoodKey)
+    }
+}
+
+struct GoodKeyVisitor;
+
+impl<'de> serde::de::Visitor<'de> for GoodKeyVisitor {
+    type Value = Good;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the key of a good")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, key: &str) -> Result<Good, E> {
+        GoodKey::from_key(key)
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Good, E> {
+        let key = std::str::from_utf8(bytes).map_err(E::custom)?;
+        GoodKey::from_key(key)
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, index: u64) -> Result<Good, E> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| Good::all().get(index).copied())
+            .ok_or_else(|| E::custom(format!("there's no good number {index}")))
+    }
+}
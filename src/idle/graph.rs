@@ -0,0 +1,109 @@
+/*
+This is the production graph view. It shows how goods flow through the producers in the game.
+Goods and producers are the nodes, and the inputs and outputs of each producer are the edges.
+There's no fancy node layout here, just lists with arrows. It's enough to see the whole production web at a glance.
+ */
+
+use crate::idle::goods::{Good, Inventory};
+use crate::idle::lib::format_si;
+use crate::idle::producers::Producer;
+use crate::idle::settings::RateUnit;
+use crate::idle::widgets::good_text;
+use crate::idle::GameState;
+use egui::{RichText, Ui};
+use num::{BigInt, BigRational};
+use std::collections::BTreeMap;
+
+type F = BigRational;
+type I = BigInt;
+
+// Formats a list of goods and rates as "1/4/s Coal + 1/s Iron Ore", in the given unit. Used for the edges of the graph, and in producer windows.
+pub(crate) fn format_flows(flows: &Inventory, unit: RateUnit) -> String {
+    if flows.is_empty() {
+        return String::from("(nothing)");
+    }
+    flows
+        .iter()
+        .map(|(good, rate)| format!("{} {good}", unit.format(rate)))
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+// Renders the production graph for the given game state.
+pub fn display_production_graph(ui: &mut Ui, game_state: &GameState, unit: RateUnit) {
+    let breakdown = game_state.producer_breakdown();
+    if breakdown.is_empty() {
+        ui.label("There are no producers yet, so there's nothing to graph.");
+        return;
+    }
+
+    // The producer nodes. Each row is "inputs -> producer -> outputs", with the rates multiplied by how many of that producer there are.
+    ui.label(RichText::new("Producers").strong());
+    egui::Grid::new("graph_producers")
+        .striped(true)
+        .show(ui, |grid_ui| {
+            for (producer, count) in breakdown.iter() {
+                let properties = producer.properties();
+                let scale = F::from(I::from(*count));
+                // BTreeMaps so the goods are always listed in the same order.
+                let inputs = properties
+                    .recipe
+                    .inputs
+                    .iter()
+                    .map(|(good, rate)| (*good, rate * &scale))
+                    .collect();
+                let outputs = properties
+                    .recipe
+                    .outputs
+                    .iter()
+                    .map(|(good, rate)| (*good, rate * &scale))
+                    .collect();
+                grid_ui.label(format_flows(&inputs, unit));
+                grid_ui.label("→");
+                grid_ui.label(RichText::new(format!("{producer} ×{}", format_si(&scale))).strong());
+                grid_ui.label("→");
+                grid_ui.label(format_flows(&outputs, unit));
+                grid_ui.end_row();
+            }
+        });
+
+    ui.add(egui::Separator::default().horizontal().spacing(4.0));
+
+    // The good nodes. Each row lists which producers feed a good, and which producers it feeds.
+    ui.label(RichText::new("Goods").strong());
+    let mut fed_by: BTreeMap<Good, Vec<Producer>> = BTreeMap::new();
+    let mut feeds: BTreeMap<Good, Vec<Producer>> = BTreeMap::new();
+    for producer in breakdown.keys() {
+        let properties = producer.properties();
+        for good in properties.recipe.outputs.keys() {
+            fed_by.entry(*good).or_default().push(*producer);
+        }
+        for good in properties.recipe.inputs.keys() {
+            feeds.entry(*good).or_default().push(*producer);
+        }
+    }
+    let format_producers = |producers: Option<&Vec<Producer>>| match producers {
+        Some(producers) => producers
+            .iter()
+            .map(|producer| producer.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        None => String::from("(nothing)"),
+    };
+    egui::Grid::new("graph_goods")
+        .striped(true)
+        .show(ui, |grid_ui| {
+            for good in Good::sorted() {
+                // Goods which aren't touched by any producer aren't part of the graph.
+                if !fed_by.contains_key(&good) && !feeds.contains_key(&good) {
+                    continue;
+                }
+                grid_ui.label(format_producers(fed_by.get(&good)));
+                grid_ui.label("→");
+                grid_ui.label(good_text(good).strong());
+                grid_ui.label("→");
+                grid_ui.label(format_producers(feeds.get(&good)));
+                grid_ui.end_row();
+            }
+        });
+}
@@ -0,0 +1,87 @@
+/*
+This is the production heatmap. It's a compact overview of the whole economy, for when the inventory grid gets too long to read at a glance.
+Every good is a cell, colored by its net rate: surplus is the palette's good color, deficit is its bad color, and goods that aren't moving are grey.
+The stronger the color, the bigger the rate, compared to the fastest moving good. Hovering a cell shows the actual rate.
+It's drawn from the same production table as the inventory, so the two always agree.
+ */
+
+use crate::idle::goods::Good;
+use crate::idle::lib::format_si;
+use crate::idle::settings::{Palette, RateUnit};
+use egui::{Color32, Sense, Ui};
+use num::{BigInt, BigRational, Signed, ToPrimitive};
+use std::collections::HashMap;
+
+type F = BigRational;
+type I = BigInt;
+
+// How big each cell is, in points.
+const CELL_SIZE: egui::Vec2 = egui::vec2(96.0, 28.0);
+
+// Mixes two colors. t goes from 0 (all a) to 1 (all b).
+fn mix(a: Color32, b: Color32, t: f32) -> Color32 {
+    let channel = |x: u8, y: u8| (f32::from(x) + (f32::from(y) - f32::from(x)) * t).round() as u8;
+    Color32::from_rgb(
+        channel(a.r(), b.r()),
+        channel(a.g(), b.g()),
+        channel(a.b(), b.b()),
+    )
+}
+
+// Renders the heatmap. The production table is the one from GameState::production_table_theoretical, as (produced, consumed).
+pub(crate) fn display_heatmap(
+    ui: &mut Ui,
+    production_table: &HashMap<Good, (F, F)>,
+    palette: &Palette,
+    unit: RateUnit,
+) {
+    let zero = F::from(I::from(0));
+    let net_rate = |good: &Good| {
+        production_table
+            .get(good)
+            .map_or(zero.clone(), |(made, used)| made - used)
+    };
+    // Every cell is shaded compared to the fastest moving good, so the biggest surplus or deficit is always full strength.
+    let fastest = Good::all()
+        .into_iter()
+        .map(|good| net_rate(&good).abs())
+        .max()
+        .unwrap_or_default();
+    let neutral = ui.visuals().widgets.inactive.bg_fill;
+    ui.horizontal_wrapped(|ui| {
+        for good in Good::all() {
+            let net = net_rate(&good);
+            let strength = if fastest > zero {
+                (net.abs() / &fastest).to_f32().unwrap_or(0.0)
+            } else {
+                0.0
+            };
+            let target = if net > zero {
+                palette.good
+            } else {
+                palette.bad
+            };
+            // Even tiny rates get a little color, so they don't look the same as goods that aren't moving at all.
+            let fill = if net == zero {
+                neutral
+            } else {
+                mix(neutral, target, 0.25 + 0.75 * strength)
+            };
+            let (rect, response) = ui.allocate_exact_size(CELL_SIZE, Sense::hover());
+            ui.painter().rect_filled(rect, 3.0, fill);
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                good.to_string(),
+                egui::FontId::proportional(12.0),
+                ui.visuals().strong_text_color(),
+            );
+            let sign = if net > zero { "+" } else { "" };
+            response.on_hover_text(format!(
+                "{good}: {sign}{}{}",
+                format_si(&unit.scale(&net)),
+                unit.suffix()
+            ));
+        }
+    });
+}
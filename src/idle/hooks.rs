@@ -0,0 +1,25 @@
+/*
+This is the producer hook code. Hooks are the points in a producer's life where something else in the game might want to react.
+Rather than sprinkling notifications (and later stats, achievements and sounds) all over tick and the UI,
+everything that happens to a producer is reported as a hook, and GameState::producer_hook decides what each one leads to.
+
+The contract is:
+- Built fires once, when a producer finishes construction. Producers added directly with add_element (like the debug ones) don't count.
+- FirstProduction fires once per producer, on the first tick it actually makes something.
+- Starved and Recovered fire when the producer's starvation changes, after the debounce. Check Element::update_starvation.
+- Deleted fires when a producer is removed, after its buffers have been emptied back into the inventory.
+Hooks are handled right after the tick (or action) that caused them, in the order they happened.
+Handlers only get the producer's id and name, since the producer may be gone by the time they run.
+ */
+
+use crate::idle::goods::Good;
+
+// Something that happened to a producer. Check the top of this file for when each one fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProducerHook {
+    Built,              // The producer finished construction.
+    FirstProduction,    // The producer made something for the first time.
+    Starved(Vec<Good>), // The producer ran out of inputs. Holds which inputs it's short on.
+    Recovered,          // The producer has its inputs again.
+    Deleted,            // The producer was removed from the game.
+}
@@ -0,0 +1,150 @@
+/*
+This is the keybind code. A few common actions have keyboard shortcuts, and the player can rebind them from the settings tab.
+Rebinding works by clicking an action's button and then pressing the new key. The next key pressed is captured instead of doing anything.
+Binds are plain keys without modifiers, so they're ignored while a text box has focus. Otherwise typing a region name would undo things.
+Two actions can end up on the same key. That's allowed, but the settings tab warns about it, and only the first of them runs.
+ */
+
+use egui::{Context, Key, Ui};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+// Something the player can do with a key.
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    EnumIter,
+    Debug,
+)]
+pub enum Action {
+    Save,     // Saves the game to storage right away, instead of waiting for the autosave.
+    Undo,     // Undoes the last undoable action.
+    NextTab,  // Switches to the next tab, wrapping around at the end.
+    Mine,     // Switches to the metallurgy tab, where the ore minigames are.
+    QuickBuy, // Buys the cheapest producer there's money for. Check GameState::cheapest_affordable.
+}
+
+impl Action {
+    // The key the action is bound to until the player changes it.
+    pub fn default_key(&self) -> Key {
+        match self {
+            Action::Save => Key::S,
+            Action::Undo => Key::Z,
+            Action::NextTab => Key::T,
+            Action::Mine => Key::M,
+            Action::QuickBuy => Key::B,
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Save => write!(f, "Save"),
+            Action::Undo => write!(f, "Undo"),
+            Action::NextTab => write!(f, "Next tab"),
+            Action::Mine => write!(f, "Go mining"),
+            Action::QuickBuy => write!(f, "Buy cheapest producer"),
+        }
+    }
+}
+
+// Which key each action is bound to. Actions missing from the map use their default key, so new actions get bound in old saves too.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Keybinds {
+    pub binds: BTreeMap<Action, Key>, // The keys the player has rebound. Check key for the ones they haven't.
+    #[serde(skip)]
+    pub rebinding: Option<Action>, // The action waiting for its new key to be pressed, if any. Not saved.
+}
+
+impl Keybinds {
+    // The key an action is bound to.
+    pub fn key(&self, action: Action) -> Key {
+        self.binds
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_key())
+    }
+
+    // The actions sharing a key with an earlier action. These never run, since the earlier action takes the key.
+    pub fn conflicts(&self) -> Vec<(Action, Action)> {
+        let mut taken: BTreeMap<Key, Action> = BTreeMap::new();
+        let mut conflicts = Vec::new();
+        for action in Action::iter() {
+            let key = self.key(action);
+            match taken.get(&key) {
+                Some(first) => conflicts.push((*first, action)),
+                None => {
+                    taken.insert(key, action);
+                }
+            }
+        }
+        conflicts
+    }
+
+    // The actions whose keys were pressed this frame. Nothing is pressed while rebinding, or while a text box has focus.
+    pub fn pressed(&self, ctx: &Context) -> Vec<Action> {
+        if self.rebinding.is_some() || ctx.wants_keyboard_input() {
+            return Vec::new();
+        }
+        let conflicting: Vec<Action> = self.conflicts().into_iter().map(|(_, a)| a).collect();
+        let input = ctx.input();
+        Action::iter()
+            .filter(|action| !conflicting.contains(action))
+            .filter(|action| input.key_pressed(self.key(*action)))
+            .collect()
+    }
+
+    // Renders the rebinding controls. Clicking an action's button waits for the next key, and Escape cancels.
+    // Conflicts are warned about in the given color.
+    pub fn ui(&mut self, ui: &mut Ui, warning: egui::Color32) {
+        if let Some(action) = self.rebinding {
+            let pressed = ui.input().events.iter().find_map(|event| match event {
+                egui::Event::Key {
+                    key, pressed: true, ..
+                } => Some(*key),
+                _ => None,
+            });
+            match pressed {
+                Some(Key::Escape) => self.rebinding = None,
+                Some(key) => {
+                    self.binds.insert(action, key);
+                    self.rebinding = None;
+                }
+                None => {}
+            }
+        }
+        egui::Grid::new("keybinds_grid").show(ui, |ui| {
+            for action in Action::iter() {
+                ui.label(action.to_string());
+                let text = if self.rebinding == Some(action) {
+                    "Press a key...".to_string()
+                } else {
+                    format!("{:?}", self.key(action))
+                };
+                if ui.button(text).clicked() {
+                    self.rebinding = Some(action);
+                }
+                if ui.button("Default").clicked() {
+                    self.binds.remove(&action);
+                }
+                ui.end_row();
+            }
+        });
+        for (first, second) in self.conflicts() {
+            ui.colored_label(
+                warning,
+                format!("{second} is on the same key as {first}, so only {first} works."),
+            );
+        }
+    }
+}
@@ -1 +1,156 @@
-// Currently blank, so have a ferris: 🦀
+// Little helpers that are shared across the game, and don't belong to any one part of it.
+// This used to be blank, so the ferris stays: 🦀
+
+use num::{BigInt, BigRational, Integer, Signed, ToPrimitive};
+
+type F = BigRational;
+type I = BigInt;
+
+// How many cents there are in a unit of money. Check round_money.
+const CENTS: i64 = 100;
+
+// The suffixes for each power of a thousand, for format_si.
+const SI_SUFFIXES: [&str; 5] = ["", "K", "M", "B", "T"];
+
+// Formats a number short enough to fit in a title, like "950", "1.2K", "34.5M" or "678B".
+// The number is kept to about three significant digits, and trailing zeros are dropped, so 1000 is "1K" rather than "1.00K".
+// Numbers past the last suffix fall back to scientific notation. Since it's only for display, it goes through f64.
+pub fn format_si(value: &F) -> String {
+    let value = value.to_f64().unwrap_or(0.0);
+    let mut scaled = value.abs();
+    let mut suffix = 0;
+    while scaled >= 1000.0 && suffix + 1 < SI_SUFFIXES.len() {
+        scaled /= 1000.0;
+        suffix += 1;
+    }
+    if scaled >= 1000.0 {
+        return format!("{value:.2e}");
+    }
+    let digits = if scaled < 10.0 {
+        2
+    } else if scaled < 100.0 {
+        1
+    } else {
+        0
+    };
+    let mut text = format!("{scaled:.digits$}");
+    if text.contains('.') {
+        text = text.trim_end_matches('0').trim_end_matches('.').to_string();
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+    format!("{sign}{text}{}", SI_SUFFIXES[suffix])
+}
+
+// Formats an amount of a good for the inventory, like "950", "1.23K", "34.5M" or "678B". It's format_si, but exact.
+// Amounts can get far too big for an f64 to hold exactly, so this works on the digits of the whole part instead, and never goes through f64.
+// Digits past the third significant one are cut off rather than rounded, so 999,999 is "999K", never "1000K", and amounts never look bigger than they are.
+// Amounts past the last suffix are written in scientific notation, like "5.00e15". Amounts below 1 keep two decimal places, so a quarter is "0.25" rather than "0".
+// Negative amounts get a minus sign, as long as there's anything left after cutting them off.
+pub fn format_good_amount(value: &F) -> String {
+    let magnitude = value.abs();
+    let digits = magnitude.trunc().to_integer().to_string();
+    let text = if digits.len() <= 3 {
+        if digits == "0" {
+            let text = format_exact(&magnitude, 2);
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            digits
+        }
+    } else {
+        let group = (digits.len() - 1) / 3;
+        let (whole, rest, suffix) = match SI_SUFFIXES.get(group) {
+            Some(suffix) => {
+                let split = digits.len() - group * 3;
+                (&digits[..split], &digits[split..3], suffix.to_string())
+            }
+            None => (
+                &digits[..1],
+                &digits[1..3],
+                format!("e{}", digits.len() - 1),
+            ),
+        };
+        // Scientific notation keeps its zeros, like format_si does.
+        let rest = if group < SI_SUFFIXES.len() {
+            rest.trim_end_matches('0')
+        } else {
+            rest
+        };
+        if rest.is_empty() {
+            format!("{whole}{suffix}")
+        } else {
+            format!("{whole}.{rest}{suffix}")
+        }
+    };
+    if value.is_negative() && text != "0" {
+        format!("-{text}")
+    } else {
+        text
+    }
+}
+
+// Formats a number of seconds as days, hours, minutes and seconds, leaving out the parts that are zero. "1h 5m 3s", "2d 4h", and so on.
+pub fn format_seconds(seconds: &F) -> String {
+    let total = seconds.floor().to_integer().to_u64().unwrap_or(0);
+    let (days, hours) = (total / 86400, total / 3600 % 24);
+    let (minutes, seconds) = (total / 60 % 60, total % 60);
+    let mut parts = Vec::new();
+    if days > 0 {
+        parts.push(format!("{days}d"));
+    }
+    if hours > 0 {
+        parts.push(format!("{hours}h"));
+    }
+    if minutes > 0 {
+        parts.push(format!("{minutes}m"));
+    }
+    if seconds > 0 || parts.is_empty() {
+        parts.push(format!("{seconds}s"));
+    }
+    parts.join(" ")
+}
+
+// Writes out a number as a decimal, exactly, to the given number of decimal places. Anything past that is cut off, not rounded.
+// This is long division on the numerator and denominator, so it works no matter how big they get. Nothing goes through f64.
+// Trailing zeros are kept, so every number with the same places lines up. 1/3 to 5 places is "0.33333", and 5 is "5.00000".
+pub fn format_exact(value: &F, places: usize) -> String {
+    let denom = value.denom().abs();
+    let (whole, mut remainder) = value.numer().abs().div_rem(&denom);
+    let mut text = String::new();
+    if value.is_negative() {
+        text.push('-');
+    }
+    text.push_str(&whole.to_string());
+    if places > 0 {
+        text.push('.');
+    }
+    let ten = I::from(10);
+    for _ in 0..places {
+        let (digit, rest) = (remainder * &ten).div_rem(&denom);
+        text.push_str(&digit.to_string());
+        remainder = rest;
+    }
+    text
+}
+
+// Rounds a number to the nearest multiple of 1 / 10^places, so its denominator is at most 10^places. Halves round away from zero.
+// The result is never more than half of 1 / 10^places away from the number.
+pub fn round_to_places(value: &F, places: u32) -> F {
+    let scale = F::from(num::pow(I::from(10), places as usize));
+    (value * &scale).round() / scale
+}
+
+// Rounds an amount of money down to a whole number of cents. This is the rounding rule for all money made from selling,
+// by hand, by auto-selling and by producers selling their output directly, so money never ends up with a long fraction.
+// It always rounds down (towards negative infinity), so selling never pays more than the exact price. Even halves of a cent round down.
+// So 0.999 is 0.99, 0.125 is 0.12, and anything under a cent is nothing at all. Amounts that are already whole cents stay as they are.
+pub fn round_money(value: &F) -> F {
+    let cents = F::from(I::from(CENTS));
+    (value * &cents).floor() / cents
+}
+
+// Rounds an amount of money up to a whole number of cents. This is the rounding rule for money spent buying goods,
+// the opposite of round_money, so buying never costs less than the exact price either.
+pub fn round_money_up(value: &F) -> F {
+    let cents = F::from(I::from(CENTS));
+    (value * &cents).ceil() / cents
+}
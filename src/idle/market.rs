@@ -0,0 +1,50 @@
+/*
+This is the market code. Right now, the market is just auto-selling: goods can be set to sell themselves every tick.
+When a good is both auto-sold and used by producers, something has to decide who gets it first. That's the sell priority.
+By default producers get fed first, and only the excess is sold, so auto-selling never starves a producer.
+ */
+
+use num::{BigInt, BigRational};
+use std::fmt::{Display, Formatter};
+use strum_macros::EnumIter;
+
+type F = BigRational;
+type I = BigInt;
+
+// Who gets a good first when it's both auto-sold and consumed by producers.
+#[derive(
+    serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Copy, EnumIter, Debug, Default,
+)]
+pub enum SellPriority {
+    #[default]
+    ProduceFirst, // Producers are fed first. Enough for the next tick of consumption is held back, and only the rest is sold.
+    SellFirst, // The good is sold before producers run. Producers only get what's left, which might be nothing.
+}
+
+impl Display for SellPriority {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SellPriority::ProduceFirst => write!(f, "Produce first"),
+            SellPriority::SellFirst => write!(f, "Sell first"),
+        }
+    }
+}
+
+// An auto-sell rule for a single good.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct AutoSellRule {
+    pub enabled: bool,          // Whether the good is being auto-sold at all.
+    pub keep: F,                // How much of the good is never sold, no matter what.
+    pub priority: SellPriority, // Who gets the good first. Check SellPriority for more info.
+}
+
+impl Default for AutoSellRule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keep: F::from(I::from(0)),
+            priority: SellPriority::default(),
+        }
+    }
+}
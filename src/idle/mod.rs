@@ -1,215 +1,3320 @@
-use crate::idle::element::{ElemVariant, Element};
-use crate::idle::goods::{Good, GoodGroup};
-use crate::idle::producers::Producer;
+use crate::idle::achievements::{AchievementCache, AchievementView};
+use crate::idle::build::AutoBuild;
+use crate::idle::flash::InventoryFlashes;
+use crate::idle::hooks::ProducerHook;
+use crate::idle::keybinds::Action;
+use crate::idle::lib::format_seconds;
+use crate::idle::notifications::Notifications;
+use crate::idle::offline::{OfflineChoice, OfflinePreview};
+use crate::idle::rates::{ProductionTicker, RateSmoother};
+use crate::idle::settings::{Palette, Settings};
+use crate::idle::solver::Flow;
+use crate::idle::sound::{Cue, Sounds};
+use crate::idle::widgets::{good_text, NumericEdit};
 use egui::widget_text::RichText;
 use egui::{Align, Ui};
-use num::{BigInt, BigRational, ToPrimitive};
-use std::collections::HashMap;
+use num::{BigInt, BigRational, Signed, ToPrimitive};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
+mod achievements;
+mod alarms;
+mod build;
+mod chain;
+mod changelog;
+mod clipboard;
+mod clock;
+mod crafting;
 mod element;
+mod events;
+mod flash;
 mod goods;
+mod graph;
+mod heatmap;
+mod hooks;
+mod keybinds;
 mod lib;
+mod market;
+mod notifications;
+mod offline;
 mod ores;
+mod plan;
 mod producers;
+mod rates;
+mod recipe;
+mod registry;
+mod save;
+mod settings;
+mod solver;
+mod sound;
+mod stats;
+mod targets;
+mod validate;
+mod widgets;
+
+// The simulation's public surface. Everything here works without egui, so it can be driven by tests or another frontend.
+// The UI side (IdleGame, notifications, the graph, settings panels) stays private to the crate.
+pub use achievements::{progress_all, Achievement, AchievementProgress};
+pub use alarms::{AlarmLevel, GoodAlarm};
+pub use build::{construction_time, BuildOrder, InsufficientFunds};
+pub use chain::ChainPlan;
+pub use clipboard::{PasteError, ProducerConfig};
+pub use clock::{Clock, SystemClock};
+pub use crafting::recipes_for;
+pub use element::{ElemVariant, Element};
+pub use events::GameEvent;
+pub use goods::{
+    Good, GoodGroup, GoodProperties, GroupProperties, Inventory, StorageError, StorageLevels,
+};
+pub use lib::{format_exact, format_good_amount, format_si, round_money};
+pub use market::{AutoSellRule, SellPriority};
+pub use ores::{inversions, min_inversions, shuffled_order, OreMinigame};
+pub use plan::{FactoryPlan, PlanError, MAX_PLAN_PRODUCERS};
+pub use producers::{Producer, ProducerCategory, ProducerProperties};
+pub use recipe::Recipe;
+pub use registry::{GoodDefinition, GoodId, ProducerDefinition, ProducerId, RegistryError};
+pub use save::{decode_compact, encode_compact, MigrationReport, SaveChange, SaveError};
+pub use settings::{
+    EconomySettings, FailBehavior, LogisticsMode, MinigameSettings, SimSettings, TaxKind,
+};
+pub use solver::Solution;
+pub use stats::{expected_solve_seconds, ores_per_minute, Stats};
+pub use targets::ProductionTarget;
+pub use validate::validate_data;
+pub use widgets::NumericValue;
 
 // Type aliases because screw typing all that out
 type F = BigRational;
 type I = BigInt;
 
-// The game state. Contains all the data that needs to be saved and is directly related to the game.
-#[derive(serde::Serialize, serde::Deserialize)]
-#[serde(default)]
-struct GameState {
-    inventory: HashMap<Good, F>, // The personal inventory of the player
-    ore_minigames: HashMap<Good, ores::OreMinigame>, // The current state of the ore minigames
-    // Check ores.rs for more info on the ore minigames
-    elements: HashMap<usize, Element>, // The elements currently in the game
-                                       // Check element.rs for more info on elements
-}
+/// How long a backlog of game time has to be, in seconds, before it's caught up with [`GameState::advance_coarse`] instead of tick by tick.
+pub fn coarse_backlog_threshold() -> F {
+    F::from(I::from(300))
+}
+
+// The most steps advance_coarse splits a backlog into. Check advance_coarse for how this bounds the error.
+const COARSE_STEPS: u64 = 1200;
+
+/// The game state. Contains all the data that needs to be saved and is directly related to the game.
+///
+/// This is the whole simulation, with no egui in sight. Create one with [`GameState::new`], add producers with
+/// [`GameState::add_element`] or [`GameState::try_buy_producer`], and run it with [`GameState::advance`].
+// It's Clone so that snapshots of it can be kept for undoing.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(default)]
+pub struct GameState {
+    inventory: Inventory, // The personal inventory of the player
+    ore_minigames: BTreeMap<Good, ores::OreMinigame>, // The current state of the ore minigames. A BTreeMap so saves list them in the same order every time
+    // Check ores.rs for more info on the ore minigames
+    elements: BTreeMap<usize, Element>, // The elements currently in the game, by id. Ordered, so they're always listed in the order they were added.
+    // Check element.rs for more info on elements
+    seed: Option<u64>, // The seed for the random number generator. If None, the rng is seeded from entropy.
+    // Setting a seed makes the minigame button orders reproducible, which is handy for testing and replays.
+    #[serde(skip)]
+    rng: StdRng, // The random number generator used by the ore minigames. Rebuilt from the seed when loading.
+    sim_settings: SimSettings, // Settings which change how the simulation works. Check settings.rs for more info.
+    next_id: usize, // The id the next element will get. Only ever goes up, so ids are never reused, even after elements are deleted.
+    build_queue: Vec<BuildOrder>, // Producers that have been paid for but are still being built. Check build.rs for more info.
+    storage_level: StorageLevels, // How many times each group's storage has been upgraded. Check upgrade_storage for more info.
+    auto_sell: BTreeMap<Good, AutoSellRule>, // The auto-sell rules for each good. Goods without a rule aren't auto-sold. Check market.rs for more info.
+    auto_build: AutoBuild, // How many of each producer to keep buying up to. Check build.rs for more info.
+    stats: Stats, // Records of how the player has been doing. Check stats.rs for more info.
+    production_targets: BTreeMap<Good, ProductionTarget>, // The target stockpile for each good. Goods without one aren't managed. Check targets.rs for more info.
+    alarms: BTreeMap<Good, GoodAlarm>, // The low and high watermarks for each good. Goods without an alarm are never watched. Check alarms.rs for more info.
+    pinned: BTreeSet<Good>, // The goods pinned to the top of the inventory. Ordered, so pinned goods stay in the same order as the rest.
+    #[serde(skip)]
+    events: Vec<GameEvent>, // Things that happened in the simulation, waiting for the UI to pick them up. Check events.rs for more info.
+    #[serde(skip)]
+    production_cache: RefCell<Option<HashMap<Good, (F, F)>>>, // The theoretical production table, or None if it needs working out again. Check production_table_theoretical.
+    #[serde(skip)]
+    running_cache: RefCell<Option<HashMap<Good, (F, F)>>>, // The same, but with the producers hidden from the table too. Check production_table_running.
+    #[serde(skip)]
+    sustainable_cache: RefCell<Option<Solution>>, // The sustainable production table, worked out by the solver. Check production_table_sustainable.
+}
+
+// Creates the random number generator for a given seed, or from entropy if there is no seed.
+fn rng_from_seed(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+// Default implementation for GameState. Used for deserialization, and for resetting the game.
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl GameState {
+    /// Registers a good at runtime, without adding it to the [`Good`] enum. Returns the good to use it as.
+    /// The registry is shared by every game state, so this should be done once, at startup, before any game state is made or loaded.
+    /// Check registry.rs for the rules.
+    pub fn register_good(definition: GoodDefinition) -> Result<Good, RegistryError> {
+        registry::register_good(definition)
+    }
+
+    /// Registers a producer at runtime, without adding it to the [`Producer`] enum. Returns the producer to use it as.
+    /// It can use and make registered goods, and shows up in the build menu like any other producer. Same rules as register_good.
+    pub fn register_producer(definition: ProducerDefinition) -> Result<Producer, RegistryError> {
+        registry::register_producer(definition)
+    }
+
+    /// Creates a fresh game state. If a seed is given, the minigames will always generate the same button orders.
+    pub fn new(seed: Option<u64>) -> Self {
+        let mut rng = rng_from_seed(seed);
+        Self {
+            inventory: {
+                // Creates an empty inventory. Note that it starts with all goods as 0, instead of having a blank map.
+                // This is so that the player can see all the goods in the game, even if they don't have any.
+                // It's also more useful to fill the inventory's keys now, rather than at the render step.
+                // Why? I dunno, superstition I guess.
+                let mut map = Inventory::new();
+                for good in Good::all() {
+                    map.insert(good, F::new(I::from(0), I::from(1)));
+                }
+                map
+            },
+            ore_minigames: {
+                // Fills the map with all the ore minigames, depending on the ore type's difficulty.
+                let mut map = BTreeMap::new();
+                for good in Good::group_iter(GoodGroup::Ore) {
+                    map.insert(
+                        good,
+                        // The player's settings aren't known here, so the first orders are made with the default ones.
+                        // The settings apply from the first reset onwards.
+                        ores::OreMinigame::new(
+                            good.properties().difficulty,
+                            &settings::MinigameSettings::default(),
+                            &mut rng,
+                        ),
+                    );
+                }
+                map
+            },
+            // There are no default elements, so it's just an empty HashMap.
+            // We could fill the hashmap with "blanks" here, but it's not necessary.
+            elements: BTreeMap::new(),
+            seed,
+            rng,
+            sim_settings: SimSettings::default(),
+            next_id: 0,
+            build_queue: Vec::new(),
+            storage_level: HashMap::new(),
+            auto_sell: BTreeMap::new(),
+            auto_build: AutoBuild::default(),
+            stats: Stats::default(),
+            production_targets: BTreeMap::new(),
+            alarms: BTreeMap::new(),
+            pinned: BTreeSet::new(),
+            events: Vec::new(),
+            production_cache: RefCell::new(None),
+            running_cache: RefCell::new(None),
+            sustainable_cache: RefCell::new(None),
+        }
+    }
+
+    // The rng isn't saved, so after loading it needs to be rebuilt from the seed.
+    // This means a seeded game restarts its button order sequence from the beginning after a reload.
+    fn reseed(&mut self) {
+        self.rng = rng_from_seed(self.seed);
+    }
+
+    // Makes sure every good has an entry in the inventory. Saves from older versions may be missing goods that were added since.
+    // Removed goods don't need handling here, since a save with a good that no longer exists fails to deserialize in the first place.
+    fn ensure_goods_present(&mut self) {
+        for good in Good::all() {
+            self.inventory
+                .entry(good)
+                .or_insert_with(|| F::from(I::from(0)));
+        }
+    }
+
+    /// Adds an element to the game, and returns its id. Every element must be added through here, so that ids stay unique.
+    /// The window id is built from the element's id, so it's unique too.
+    pub fn add_element(&mut self, variant: ElemVariant, name: &str, is_open: bool) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.elements
+            .insert(id, Element::new(variant, format!("{id}: {name}"), is_open));
+        self.invalidate_production_table();
+        id
+    }
+
+    /// Removes an element from the game, and returns it. Anything in its buffers goes back into the inventory first,
+    /// rather than vanishing with the element.
+    pub fn remove_element(&mut self, id: usize) -> Option<Element> {
+        let mut element = self.elements.remove(&id)?;
+        self.invalidate_production_table();
+        element.flush_buffers(&mut self.inventory);
+        if let ElemVariant::Producer(_) = element.variant {
+            self.producer_hook(id, element.window_id.clone(), ProducerHook::Deleted);
+        }
+        Some(element)
+    }
+
+    /// The player's inventory. Every good has an entry, even if it's zero.
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+
+    /// The player's inventory, for changing directly. Handy for tests and debug tools.
+    pub fn inventory_mut(&mut self) -> &mut Inventory {
+        &mut self.inventory
+    }
+
+    /// The elements currently in the game, by id.
+    pub fn elements(&self) -> &BTreeMap<usize, Element> {
+        &self.elements
+    }
+
+    /// The producers that have been paid for but are still being built. The first one is the one being worked on.
+    pub fn build_queue(&self) -> &[BuildOrder] {
+        &self.build_queue
+    }
+
+    /// The settings which change how the simulation works.
+    pub fn sim_settings(&self) -> &SimSettings {
+        &self.sim_settings
+    }
+
+    /// Replaces the economy settings, which decide what goods sell for.
+    pub fn set_economy(&mut self, economy: EconomySettings) {
+        self.sim_settings.economy = economy;
+        // Producers selling their output directly make money at the new prices.
+        self.invalidate_production_table();
+    }
+
+    // Throws away the cached production table, so it's worked out again the next time it's needed.
+    // Anything that changes what producers make or use has to call this. Check production_table_theoretical for more info.
+    pub(crate) fn invalidate_production_table(&mut self) {
+        *self.production_cache.get_mut() = None;
+        *self.running_cache.get_mut() = None;
+        *self.sustainable_cache.get_mut() = None;
+    }
+
+    /// Takes every event that happened since the last call. The UI turns these into notifications.
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    // Makes sure next_id is past every element id in use. Saves from before next_id existed start it at 0, which would collide.
+    fn fix_next_id(&mut self) {
+        if let Some(max_id) = self.elements.keys().max() {
+            self.next_id = self.next_id.max(max_id + 1);
+        }
+    }
+
+    /// Gives every element with a duplicate window id a new, unique one, and returns how many were fixed.
+    ///
+    /// Window ids used to be built from how many elements there were, so saves from back then can have several elements
+    /// with the same window id after some were deleted. egui mixes up windows with the same id, so they end up drawn wrong.
+    /// The first element with each window id keeps it. The rest are moved to a fresh id from next_id, and get a window id built from it.
+    pub fn repair_window_ids(&mut self) -> usize {
+        self.fix_next_id();
+        let mut taken = HashSet::new();
+        let mut duplicates = Vec::new();
+        for (id, element) in self.elements.iter() {
+            if !taken.insert(element.window_id.clone()) {
+                duplicates.push(*id);
+            }
+        }
+        for old_id in duplicates.iter() {
+            let Some(mut element) = self.elements.remove(old_id) else {
+                continue;
+            };
+            // Window ids look like "3: Coal Drill (Iron Ore)". Only the name is kept, since the number is what collided.
+            let name = element
+                .window_id
+                .split_once(": ")
+                .map_or(element.window_id.clone(), |(_, name)| name.to_string());
+            // Old ids can be anything, so the fresh one is skipped past any window id that's somehow already taken.
+            loop {
+                let id = self.next_id;
+                self.next_id += 1;
+                element.window_id = format!("{id}: {name}");
+                if taken.insert(element.window_id.clone()) {
+                    self.elements.insert(id, element);
+                    break;
+                }
+            }
+        }
+        duplicates.len()
+    }
+
+    /// How much money the player has.
+    pub fn money(&self) -> F {
+        self.inventory
+            .get(&Good::Money)
+            .cloned()
+            .unwrap_or_else(|| F::from(I::from(0)))
+    }
+
+    /// Whether the player has enough money to buy a producer. Free producers are always affordable, even with no money at all.
+    pub fn can_afford(&self, producer: Producer) -> bool {
+        producer.properties().cost <= self.money()
+    }
+
+    /// Buys a producer, and puts it in the build queue. Fails if the player doesn't have enough money.
+    /// Producers that take no time to build (like free ones) skip the queue and are added right away.
+    // The error holds two big rationals, which clippy thinks is too big. It's only made when a button is clicked, so it doesn't matter.
+    #[allow(clippy::result_large_err)]
+    pub fn try_buy_producer(&mut self, producer: Producer) -> Result<(), InsufficientFunds> {
+        self.try_buy(producer, None)
+    }
+
+    /// Buys up to count of a producer, one after another, stopping as soon as the money runs out. Returns how many were bought.
+    /// Fails only if not even one could be bought, with the same error as try_buy_producer.
+    #[allow(clippy::result_large_err)] // Same as try_buy_producer.
+    pub fn try_buy_many(
+        &mut self,
+        producer: Producer,
+        count: usize,
+    ) -> Result<usize, InsufficientFunds> {
+        for bought in 0..count {
+            if let Err(e) = self.try_buy_producer(producer) {
+                return if bought == 0 { Err(e) } else { Ok(bought) };
+            }
+        }
+        Ok(count)
+    }
+
+    /// Removes a producer along with others like it, up to count in all. Returns how many were actually removed.
+    /// Others like it are the same producer in the same region. The newest of them go first, since they're likely the ones just built by mistake.
+    /// Elements that aren't producers are only ever removed on their own.
+    pub fn remove_similar(&mut self, id: usize, count: usize) -> usize {
+        let Some(element) = self.elements.get(&id) else {
+            return 0;
+        };
+        let mut ids = vec![id];
+        if let ElemVariant::Producer(producer) = element.variant {
+            let region = element.region.clone();
+            ids.extend(
+                self.elements
+                    .iter()
+                    .rev()
+                    .filter(|(other, element)| {
+                        **other != id
+                            && matches!(element.variant, ElemVariant::Producer(p) if p == producer)
+                            && element.region == region
+                    })
+                    .map(|(other, _element)| *other),
+            );
+        }
+        ids.truncate(count);
+        for id in ids.iter() {
+            self.remove_element(*id);
+        }
+        ids.len()
+    }
+
+    /// The cheapest producer the player can afford right now, if there is one. Ties go to whichever comes first in the build menu.
+    /// Debug producers are left out, since they're only there for testing.
+    pub fn cheapest_affordable(&self) -> Option<Producer> {
+        let money = self.money();
+        Producer::buildable()
+            .into_iter()
+            .filter(|producer| producer.properties().category != ProducerCategory::Debug)
+            .filter(|producer| producer.properties().cost <= money)
+            .min_by(|a, b| a.properties().cost.cmp(&b.properties().cost))
+    }
+
+    /// Buys a copy of a producer with the given configuration, like pasting it. Check clipboard.rs for more info.
+    /// The copy costs the same and goes through the build queue like any other producer, and is configured once it's built.
+    #[allow(clippy::result_large_err)] // Same as try_buy_producer.
+    pub fn try_buy_configured(&mut self, config: ProducerConfig) -> Result<(), InsufficientFunds> {
+        self.try_buy(config.producer, Some(config))
+    }
+
+    #[allow(clippy::result_large_err)] // Same as try_buy_producer.
+    fn try_buy(
+        &mut self,
+        producer: Producer,
+        config: Option<ProducerConfig>,
+    ) -> Result<(), InsufficientFunds> {
+        let cost = producer.properties().cost;
+        if !self.remove_good(Good::Money, &cost) {
+            let available = self.money();
+            return Err(InsufficientFunds { cost, available });
+        }
+        let mut order = BuildOrder::new(producer, cost);
+        order.config = config;
+        if order.remaining <= F::from(I::from(0)) {
+            self.finish_build(order);
+        } else {
+            self.build_queue.push(order);
+        }
+        Ok(())
+    }
+
+    /// Removes every producer, and cancels everything in the build queue with a refund. Returns how many producers were removed.
+    /// Goods in the producers' buffers go back into the inventory, same as deleting them one by one.
+    /// Goods' windows, the inventory and everything else are left alone, so the factory can be rebuilt from where it was.
+    pub fn clear_producers(&mut self) -> usize {
+        let ids: Vec<usize> = self
+            .elements
+            .iter()
+            .filter(|(_id, element)| matches!(element.variant, ElemVariant::Producer(_)))
+            .map(|(id, _element)| *id)
+            .collect();
+        for id in ids.iter() {
+            self.remove_element(*id);
+        }
+        while !self.build_queue.is_empty() {
+            self.cancel_build(0);
+        }
+        ids.len()
+    }
+
+    /// Empties the inventory, money included, along with every producer's buffers.
+    /// The producers themselves stay, so they start back up as soon as they have their inputs again.
+    pub fn clear_inventory(&mut self) {
+        for amount in self.inventory.values_mut() {
+            *amount = F::from(I::from(0));
+        }
+        for element in self.elements.values_mut() {
+            element.input_buffer.clear();
+            element.output_buffer.clear();
+        }
+    }
+
+    /// Rounds big amounts so their denominators don't grow forever, and returns how many amounts were rounded.
+    ///
+    /// Exact rational math means a fraction like a third, added up over and over, can end up with a denominator thousands of digits long,
+    /// which slows every tick down. This only touches amounts at least `normalize_above` in size (check [`SimSettings`]),
+    /// where the fraction hardly matters, and rounds them to the nearest multiple of 1 / 10^`normalize_places`.
+    /// So the precision guarantee is: amounts below the threshold are never changed, and amounts above it change by at most
+    /// half of 1 / 10^`normalize_places`, and are left with a denominator of at most 10^`normalize_places`.
+    /// Amounts that already fit are left alone. The inventory and the producers' buffers are both normalized.
+    ///
+    /// It's not free, so it isn't done every tick. The game calls it every so often instead.
+    pub fn normalize(&mut self) -> usize {
+        let places = self.sim_settings.normalize_places;
+        let above = self.sim_settings.normalize_above.clone();
+        let limit = num::pow(I::from(10), places as usize);
+        let mut rounded = 0;
+        let buffers = self
+            .elements
+            .values_mut()
+            .flat_map(|element| [&mut element.input_buffer, &mut element.output_buffer]);
+        for inventory in std::iter::once(&mut self.inventory).chain(buffers) {
+            for amount in inventory.values_mut() {
+                if amount.abs() >= above && amount.denom() > &limit {
+                    *amount = lib::round_to_places(amount, places);
+                    rounded += 1;
+                }
+            }
+        }
+        rounded
+    }
+
+    /// The player's records so far. Check [`Stats`] for what's in them.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Rewards a solved ore minigame with the given amount of the ore, and records how long the solve took, in seconds.
+    /// The amount comes from the minigame, since it depends on the streak. Check [`ores::OreMinigame::reward`].
+    pub fn record_solve(&mut self, ore: Good, seconds: f64, reward: &F) {
+        self.add_good(ore, reward);
+        self.stats.record_solve(ore, seconds);
+    }
+
+    /// Starts every ore minigame over with a new button order, using the given settings.
+    pub fn reset_minigames(&mut self, settings: &settings::MinigameSettings) {
+        for good in Good::group_iter(GoodGroup::Ore) {
+            self.ore_minigames.insert(
+                good,
+                ores::OreMinigame::new(good.properties().difficulty, settings, &mut self.rng),
+            );
+        }
+    }
+
+    /// Cancels an order in the build queue, and refunds what was paid for it.
+    pub fn cancel_build(&mut self, index: usize) {
+        if index < self.build_queue.len() {
+            let order = self.build_queue.remove(index);
+            self.add_good(Good::Money, &order.paid);
+        }
+    }
+
+    // Turns a finished build order into an element.
+    fn finish_build(&mut self, order: BuildOrder) {
+        let name = order.producer.to_string();
+        let id = self.add_element(ElemVariant::Producer(order.producer), &name, false);
+        if let (Some(config), Some(element)) = (&order.config, self.elements.get_mut(&id)) {
+            config.apply(element);
+        }
+        self.producer_hook(id, name, ProducerHook::Built);
+    }
+
+    // Works on the order at the front of the build queue. Leftover time from a finished order carries over to the next one.
+    fn tick_build_queue(&mut self, tick_rate: &F) {
+        let zero = F::from(I::from(0));
+        let mut time = tick_rate.clone();
+        while time > zero && !self.build_queue.is_empty() {
+            let order = &mut self.build_queue[0];
+            let spent = time.clone().min(order.remaining.clone());
+            order.remaining -= &spent;
+            time -= spent;
+            if order.remaining <= zero {
+                let order = self.build_queue.remove(0);
+                self.finish_build(order);
+            }
+        }
+    }
+
+    /// How many of a producer auto-build is keeping the player at. Zero means it isn't auto-built.
+    pub fn auto_build_target(&self, producer: Producer) -> usize {
+        self.auto_build.target(producer)
+    }
+
+    /// Sets how many of a producer auto-build keeps the player at. Zero turns auto-build off for it.
+    pub fn set_auto_build_target(&mut self, producer: Producer, count: usize) {
+        self.auto_build.set_target(producer, count);
+    }
+
+    /// Whether auto-build is paused for every producer.
+    pub fn auto_build_paused(&self) -> bool {
+        self.auto_build.paused
+    }
+
+    /// Pauses or unpauses auto-build for every producer. The targets are kept either way.
+    pub fn set_auto_build_paused(&mut self, paused: bool) {
+        self.auto_build.paused = paused;
+    }
+
+    // Buys one of every producer that's below its auto-build target, every so often. Check AutoBuild for more info.
+    // Producers that can't be afforded yet are skipped, and get tried again next time.
+    fn tick_auto_build(&mut self, tick_rate: &F) {
+        if !self.auto_build.tick(tick_rate) {
+            return;
+        }
+        let mut owned = self.producer_breakdown();
+        for order in self.build_queue.iter() {
+            *owned.entry(order.producer).or_insert(0) += 1;
+        }
+        for (producer, target) in self.auto_build.targets.clone() {
+            if owned.get(&producer).copied().unwrap_or(0) < target {
+                // Not being able to afford it isn't a problem, it just waits for the money.
+                let _ = self.try_buy_producer(producer);
+            }
+        }
+    }
+
+    // Updates the game state by a single tick. Elements in skip aren't ticked, since advance handles them separately.
+    fn tick(&mut self, tick_rate: &F, skip: &HashSet<usize>) {
+        // This for loop iterates over all the elements in the game, and updates the ones which are producers.
+        // This could probably be done in a more functional way, or abstracted into a function, but I'm lazy.
+        // However, this appears more than once, so I should probably abstract it at some point.
+        self.tick_build_queue(tick_rate);
+        self.tick_auto_build(tick_rate);
+        // Goods that sell first are sold before the producers get to them, and the rest are sold after. Check market.rs for more info.
+        self.auto_sell(SellPriority::SellFirst, tick_rate);
+        let throttles = self.throttles(tick_rate);
+        let one = F::from(I::from(1));
+        let mut hooks = Vec::new();
+        for (id, element) in self.elements.iter_mut() {
+            if skip.contains(id) {
+                continue;
+            }
+            // A producer is slowed down by its scarcest input, if any of them are scarce.
+            let throttle = match element.variant {
+                ElemVariant::Producer(producer) => producer
+                    .properties()
+                    .recipe
+                    .inputs
+                    .keys()
+                    .filter_map(|good| throttles.get(good))
+                    .min()
+                    .unwrap_or(&one)
+                    .clone(),
+                _ => one.clone(),
+            };
+            // Each producer's production is calculated by multiplying the production rate by the tick rate.
+            // This allows the production rate to be in units of "per second" for easier reading and balancing.
+            // Non-producers don't do anything when ticked. Check element.rs for the warmup logic.
+            for hook in element.tick(
+                &mut self.inventory,
+                tick_rate,
+                &throttle,
+                &self.sim_settings,
+                &self.storage_level,
+            ) {
+                hooks.push((*id, element.window_id.clone(), hook));
+            }
+        }
+        // The elements are borrowed by the loop, so the hooks wait until it's done.
+        for (id, name, hook) in hooks {
+            self.producer_hook(id, name, hook);
+        }
+        self.auto_sell(SellPriority::ProduceFirst, tick_rate);
+        self.tick_production_targets(tick_rate);
+        self.check_alarms(tick_rate);
+    }
+
+    /// The target stockpile for a good, if it has one. Check targets.rs for more info.
+    pub fn production_target(&self, good: Good) -> Option<&ProductionTarget> {
+        self.production_targets.get(&good)
+    }
+
+    /// Sets the target stockpile for a good, or removes it with None. Changing the amount keeps track of what the target paused.
+    /// Removing a target turns back on every producer it paused, so nothing is left off by a target that isn't there anymore.
+    pub fn set_production_target(&mut self, good: Good, target: Option<F>) {
+        match target {
+            Some(target) => {
+                self.production_targets.entry(good).or_default().target = target;
+            }
+            None => {
+                if let Some(removed) = self.production_targets.remove(&good) {
+                    // The table is only invalidated once, and only if a producer was actually turned back on.
+                    let mut reenabled = false;
+                    for id in removed.paused {
+                        if let Some(element) = self.elements.get_mut(&id) {
+                            element.set_enabled(true);
+                            reenabled = true;
+                        }
+                    }
+                    if reenabled {
+                        self.invalidate_production_table();
+                    }
+                }
+            }
+        }
+    }
+
+    // Checks every production target, pausing or unpausing one producer of each good. Check targets.rs for how they're picked.
+    fn tick_production_targets(&mut self, tick_rate: &F) {
+        let zero = F::from(I::from(0));
+        // The targets are borrowed while they're checked, so the production table is only invalidated once they're done.
+        let mut table_changed = false;
+        for (good, target) in self.production_targets.iter_mut() {
+            if !target.tick(tick_rate) {
+                continue;
+            }
+            // Producers that have been deleted since they were paused are forgotten.
+            target.paused.retain(|id| self.elements.contains_key(id));
+            let amount = self.inventory.get(good).unwrap_or(&zero);
+            // The producers of the good, least important first. Ties go to the newest, so it's paused first.
+            let mut producers: Vec<(usize, i32)> = self
+                .elements
+                .iter()
+                .filter(|(_id, element)| match element.variant {
+                    ElemVariant::Producer(producer) => {
+                        producer.properties().recipe.outputs.contains_key(good)
+                    }
+                    _ => false,
+                })
+                .map(|(id, element)| (*id, element.priority))
+                .collect();
+            producers.sort_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)));
+            if amount >= &target.target {
+                let running = producers
+                    .iter()
+                    .find(|(id, _)| self.elements[id].enabled && !target.paused.contains(id));
+                if let Some((id, _)) = running {
+                    if let Some(element) = self.elements.get_mut(id) {
+                        element.set_enabled(false);
+                        target.paused.insert(*id);
+                        table_changed = true;
+                    }
+                }
+            } else {
+                let paused = producers
+                    .iter()
+                    .rev()
+                    .find(|(id, _)| target.paused.contains(id));
+                if let Some((id, _)) = paused {
+                    if let Some(element) = self.elements.get_mut(id) {
+                        element.set_enabled(true);
+                        target.paused.remove(id);
+                        table_changed = true;
+                    }
+                }
+            }
+        }
+        if table_changed {
+            self.invalidate_production_table();
+        }
+    }
+
+    // Works out how much every producer using a scarce good should be slowed down, with auto-throttle on. Goods that aren't scarce are left out.
+    // A good is scarce when there isn't enough of it for a whole tick of everything consuming it, and it's made slower than it's consumed.
+    // Without throttling, the consumers would run on the ticks there's enough and stall on the rest, over and over.
+    // With it, every consumer runs at made / consumed of its rate, which is just what's made, so they all run every tick instead.
+    // This goes off the production table, so it assumes whatever makes the good is running. If that's starved too, the consumers still stall sometimes.
+    // Goods that nothing makes aren't throttled at all, since there's no sustainable rate to slow down to. Their consumers just starve.
+    fn throttles(&self, tick_rate: &F) -> HashMap<Good, F> {
+        if !self.sim_settings.auto_throttle {
+            return HashMap::new();
+        }
+        let zero = F::from(I::from(0));
+        self.production_table_running()
+            .into_iter()
+            .filter(|(good, (made, used))| {
+                let owned = self.inventory.get(good).unwrap_or(&zero);
+                made > &zero && made < used && owned < &(used * tick_rate)
+            })
+            .map(|(good, (made, used))| (good, made / used))
+            .collect()
+    }
+
+    /// Sets how fast input buffers are refilled, in seconds of input per second. None refills them right away, every tick.
+    pub fn set_buffer_fill_rate(&mut self, buffer_fill_rate: Option<F>) {
+        self.sim_settings.buffer_fill_rate = buffer_fill_rate;
+    }
+
+    /// Turns auto-throttling on or off. Check [`SimSettings`] for what it does.
+    pub fn set_auto_throttle(&mut self, auto_throttle: bool) {
+        self.sim_settings.auto_throttle = auto_throttle;
+    }
+
+    // Handles something that happened to a producer. Every side effect of a producer's lifecycle goes through here.
+    // Check hooks.rs for when each hook fires. The id isn't used yet, but stats and achievements will want it.
+    fn producer_hook(&mut self, _id: usize, name: String, hook: ProducerHook) {
+        match hook {
+            ProducerHook::Built => {
+                self.stats.record_built();
+                self.events.push(GameEvent::BuildFinished(name));
+            }
+            ProducerHook::Starved(missing) => {
+                self.events.push(GameEvent::ProducerStarved(name, missing))
+            }
+            ProducerHook::Recovered if self.sim_settings.notify_recovered => {
+                self.events.push(GameEvent::ProducerRecovered(name))
+            }
+            // Nothing listens for these yet. They're here so more stats and sounds have somewhere to hook in.
+            ProducerHook::Recovered | ProducerHook::FirstProduction | ProducerHook::Deleted => {}
+        }
+    }
+
+    /// Whether a good is pinned to the top of the inventory.
+    pub fn is_pinned(&self, good: Good) -> bool {
+        self.pinned.contains(&good)
+    }
+
+    /// Pins a good to the top of the inventory, or unpins it if it's already pinned.
+    pub fn toggle_pin(&mut self, good: Good) {
+        if !self.pinned.remove(&good) {
+            self.pinned.insert(good);
+        }
+    }
+
+    /// The goods in the order the inventory shows them: pinned goods first, then the rest, each in the order the goods are declared.
+    pub fn inventory_order(&self) -> Vec<Good> {
+        let (mut pinned, unpinned): (Vec<Good>, Vec<Good>) = self
+            .inventory
+            .keys()
+            .partition(|good| self.pinned.contains(good));
+        pinned.extend(unpinned);
+        pinned
+    }
+
+    /// The alarm on a good. Goods without an alarm get the default one, which never goes off.
+    pub fn alarm(&self, good: Good) -> GoodAlarm {
+        self.alarms.get(&good).cloned().unwrap_or_default()
+    }
+
+    /// Sets the alarm on a good. Setting an alarm with no watermarks removes it.
+    pub fn set_alarm(&mut self, good: Good, alarm: GoodAlarm) {
+        if alarm.is_set() {
+            self.alarms.insert(good, alarm);
+        } else {
+            self.alarms.remove(&good);
+        }
+    }
+
+    // Checks every alarm against the inventory, telling the player about the ones that went off or cleared.
+    // Alarms that go off can also pause producers. A low alarm pauses what consumes the good, and a high alarm pauses what makes it.
+    // Paused producers stay paused when the alarm clears, since the player might want to look into what happened first.
+    fn check_alarms(&mut self, tick_rate: &F) {
+        let zero = F::from(I::from(0));
+        let mut raised = Vec::new();
+        for (good, alarm) in self.alarms.iter_mut() {
+            let amount = self.inventory.get(good).unwrap_or(&zero);
+            if !alarm.check(amount, tick_rate, &self.sim_settings.starvation_debounce) {
+                continue;
+            }
+            match alarm.active {
+                Some(level) => raised.push((*good, level, alarm.pause_producers)),
+                None => self.events.push(GameEvent::AlarmCleared(good.to_string())),
+            }
+        }
+        for (good, level, pause) in raised {
+            let paused = if pause {
+                self.set_producers_enabled(false, |producer| {
+                    let properties = producer.properties();
+                    match level {
+                        AlarmLevel::Low => properties.recipe.inputs.contains_key(&good),
+                        AlarmLevel::High => properties.recipe.outputs.contains_key(&good),
+                    }
+                })
+            } else {
+                0
+            };
+            self.events
+                .push(GameEvent::AlarmRaised(good.to_string(), level, paused));
+        }
+    }
+
+    /// The auto-sell rule for a good. Goods without a rule get the default one, which doesn't sell anything.
+    pub fn auto_sell_rule(&self, good: Good) -> AutoSellRule {
+        self.auto_sell.get(&good).cloned().unwrap_or_default()
+    }
+
+    /// Sets the auto-sell rule for a good.
+    /// Auto-sold goods count as money in the production table, so it's thrown away if the rule changed.
+    pub fn set_auto_sell_rule(&mut self, good: Good, rule: AutoSellRule) {
+        if self.auto_sell.insert(good, rule.clone()) != Some(rule) {
+            self.invalidate_production_table();
+        }
+    }
+
+    // Sells every good with an enabled auto-sell rule of the given priority, down to what the rule keeps.
+    // Produce-first goods also hold back a tick's worth of what producers consume, so the next tick can't be starved by selling.
+    fn auto_sell(&mut self, priority: SellPriority, tick_rate: &F) {
+        let rules: Vec<(Good, F)> = self
+            .auto_sell
+            .iter()
+            .filter(|(_good, rule)| rule.enabled && rule.priority == priority)
+            .map(|(good, rule)| (*good, rule.keep.clone()))
+            .collect();
+        if rules.is_empty() {
+            return;
+        }
+        let production_table = match priority {
+            // Producers hidden from the table still eat their inputs, so every producer is counted here.
+            SellPriority::ProduceFirst => self.production_table_running(),
+            SellPriority::SellFirst => HashMap::new(),
+        };
+        for (good, keep) in rules {
+            let mut reserved = keep;
+            if let Some((_output, input)) = production_table.get(&good) {
+                reserved += input * tick_rate;
+            }
+            let owned = self
+                .inventory
+                .get(&good)
+                .cloned()
+                .unwrap_or_else(|| F::from(I::from(0)));
+            if owned > reserved {
+                self.sell(good, &(owned - reserved));
+            }
+        }
+    }
+
+    /// Switches the logistics mode. When switching away from buffered logistics, every buffer is emptied back into the inventory.
+    pub fn set_logistics_mode(&mut self, logistics: LogisticsMode) {
+        if logistics == self.sim_settings.logistics {
+            return;
+        }
+        if logistics == LogisticsMode::Shared {
+            for element in self.elements.values_mut() {
+                element.flush_buffers(&mut self.inventory);
+            }
+        }
+        self.sim_settings.logistics = logistics;
+    }
+
+    /// Advances the game by the given amount of time, in whole ticks. Returns the leftover time that wasn't enough for a whole tick.
+    /// This doesn't depend on egui or the clock at all, so it can be used for offline progress or for testing.
+    pub fn advance(&mut self, seconds: &F, tick_rate: &F) -> F {
+        let ticks = (seconds / tick_rate).floor();
+        let leftover = seconds - &ticks * tick_rate;
+        let ticks = ticks.to_integer().to_u64().unwrap_or(0);
+        self.run_ticks(ticks, tick_rate, || true);
+        leftover
+    }
+
+    /// Advances the game by a long stretch of time, in a bounded number of coarse steps instead of tick by tick.
+    /// This is for huge backlogs, like offline progress or a long stall, where ticking one by one would take forever.
+    /// Returns the leftover time that wasn't enough for a whole tick, same as advance.
+    ///
+    /// Steady producers are still done in one exact step, so they come out exactly the same as with advance.
+    /// Everything else runs in at most COARSE_STEPS steps, each a whole number of ticks long. That's where the error comes in:
+    /// producers are all or nothing per step, so a producer running out of inputs (or room) stops up to one step early.
+    /// So each producer can be short by at most one step's worth of its output, every time it stalls. It never makes more than it should.
+    /// Debounces and warmups are also only checked once per step, so notifications can come up to a step late.
+    pub fn advance_coarse(&mut self, seconds: &F, tick_rate: &F) -> F {
+        let ticks = (seconds / tick_rate).floor();
+        let leftover = seconds - &ticks * tick_rate;
+        let ticks = ticks.to_integer().to_u64().unwrap_or(0);
+        let steps = ticks.min(COARSE_STEPS);
+        let steady = self.steady_producers();
+        // The ticks are spread over the steps as evenly as possible. The first few steps take one extra tick each for the remainder.
+        for step in 0..steps {
+            let length = ticks / steps + u64::from(step < ticks % steps);
+            self.tick(&(tick_rate * F::from(I::from(length))), &steady);
+        }
+        self.tick_steady(&steady, &(tick_rate * F::from(I::from(ticks))));
+        leftover
+    }
+
+    /// Advances the game tick by tick, for as long as there's a whole tick of time left and the budget hasn't run out.
+    /// The budget is real time, measured with the clock, so a slow machine does fewer ticks per frame instead of freezing up.
+    /// Returns the time that wasn't simulated, which is the leftover partial tick plus anything the budget cut off.
+    pub fn advance_budgeted(
+        &mut self,
+        seconds: &F,
+        tick_rate: &F,
+        budget: chrono::Duration,
+        clock: &impl Clock,
+    ) -> F {
+        let start = clock.now();
+        let ticks = (seconds / tick_rate)
+            .floor()
+            .to_integer()
+            .to_u64()
+            .unwrap_or(0);
+        let done = self.run_ticks(ticks, tick_rate, || clock.now() - start < budget);
+        seconds - tick_rate * F::from(I::from(done))
+    }
+
+    // The tick loop behind advance and advance_budgeted. Does up to the given number of ticks, checking keep_going before each one,
+    // and stops as soon as it says no. Returns how many ticks were done.
+    fn run_ticks(
+        &mut self,
+        ticks: u64,
+        tick_rate: &F,
+        mut keep_going: impl FnMut() -> bool,
+    ) -> u64 {
+        // Some producers can be done in one big step instead of tick by tick. Check steady_producers for when that's allowed.
+        let steady = self.steady_producers();
+        let mut done = 0;
+        while done < ticks && keep_going() {
+            self.tick(tick_rate, &steady);
+            done += 1;
+        }
+        self.tick_steady(&steady, &(tick_rate * F::from(I::from(done))));
+        done
+    }
+
+    // Runs the steady producers for the given amount of time, in one step. Check steady_producers for more info.
+    fn tick_steady(&mut self, steady: &HashSet<usize>, elapsed: &F) {
+        // Running a steady producer for N ticks produces exactly N times a single tick, so a single tick of N times the length does the same thing.
+        // Since it's all exact rational math, the result is identical to doing it tick by tick.
+        for id in steady.iter() {
+            if let Some(element) = self.elements.get_mut(id) {
+                let name = element.window_id.clone();
+                let hooks = element.tick(
+                    &mut self.inventory,
+                    elapsed,
+                    // Steady producers have no inputs, so there's nothing to throttle them for.
+                    &F::from(I::from(1)),
+                    &self.sim_settings,
+                    &self.storage_level,
+                );
+                for hook in hooks {
+                    self.producer_hook(*id, name.clone(), hook);
+                }
+            }
+        }
+    }
+
+    // Finds the producers which can be advanced analytically, in one step, rather than tick by tick.
+    // A producer is steady if nothing can ever stop it, and if nothing else can notice when its outputs show up:
+    // 1. It has no inputs, so it can never be starved.
+    // 2. It isn't warming up, so its output doesn't change from tick to tick.
+    // 3. None of its outputs are consumed by any producer, so producing everything at the end doesn't starve or feed anyone early.
+    // 4. It can't stall on full outputs. Capping one big step gives the same result as capping every tick, but stalling doesn't.
+    // 5. None of its outputs are auto-sold, since selling every tick keeps the good away from its capacity, and one big step wouldn't.
+    // 6. None of its outputs have an alarm, since alarms are checked every tick, and would miss output that shows up all at the end.
+    //    The same goes for production targets.
+    fn steady_producers(&self) -> HashSet<usize> {
+        let consumed: HashSet<Good> = self
+            .elements
+            .values()
+            .filter_map(|element| match element.variant {
+                ElemVariant::Producer(producer) => Some(producer.properties().recipe.inputs),
+                _ => None,
+            })
+            .flat_map(|inputs| inputs.into_keys())
+            .collect();
+        self.elements
+            .iter()
+            .filter(|(_id, element)| {
+                let ElemVariant::Producer(producer) = element.variant else {
+                    return false;
+                };
+                let properties = producer.properties();
+                // Producers selling their output deposit money instead, so that's what counts here.
+                let outputs = producer.deposited_outputs(element.sell(&self.sim_settings));
+                properties.recipe.inputs.is_empty()
+                    && element.warmup_remaining == F::from(I::from(0))
+                    && (element.discard_excess
+                        || outputs
+                            .keys()
+                            .all(|good| good.capacity(&self.storage_level).is_none()))
+                    && !outputs
+                        .keys()
+                        .any(|good| self.auto_sell_rule(*good).enabled)
+                    && !outputs.keys().any(|good| self.alarms.contains_key(good))
+                    && !outputs
+                        .keys()
+                        .any(|good| self.production_targets.contains_key(good))
+                    && !properties
+                        .recipe
+                        .outputs
+                        .keys()
+                        .any(|good| consumed.contains(good))
+            })
+            .map(|(id, _element)| *id)
+            .collect()
+    }
+
+    /// Calculates the net worth of the player. This is the money they have, plus the money they've sunk into producers.
+    /// That includes producers still in the build queue, since they can be cancelled for a refund.
+    /// Goods are counted at what they'd sell for right now, after tax.
+    pub fn net_worth(&self) -> F {
+        let mut worth = self.money();
+        for (good, amount) in self.inventory.iter() {
+            if *good != Good::Money {
+                worth += amount * self.sell_price(*good);
+            }
+        }
+        for (_id, element) in self.elements.iter() {
+            if let ElemVariant::Producer(producer) = element.variant {
+                worth += producer.properties().cost;
+            }
+        }
+        for order in self.build_queue.iter() {
+            worth += &order.paid;
+        }
+        // Storage upgrades count at what they cost, like producers.
+        for level in self.storage_level.values() {
+            for paid in 0..*level {
+                worth += Self::storage_cost_at(paid);
+            }
+        }
+        worth
+    }
+
+    /// Enables or disables every producer that matches the filter. Returns how many producers actually changed.
+    pub fn set_producers_enabled(
+        &mut self,
+        enabled: bool,
+        filter: impl Fn(&Producer) -> bool,
+    ) -> usize {
+        let mut changed = 0;
+        for (_id, element) in self.elements.iter_mut() {
+            if let ElemVariant::Producer(producer) = element.variant {
+                if filter(&producer) && element.set_enabled(enabled) {
+                    changed += 1;
+                }
+            }
+        }
+        if changed > 0 {
+            self.invalidate_production_table();
+        }
+        changed
+    }
+
+    /// How much money one of a good sells for right now, after the economy settings. Zero means it can't be sold.
+    pub fn sell_price(&self, good: Good) -> F {
+        self.sim_settings
+            .economy
+            .sell_price(good.properties().base_price)
+    }
+
+    /// Sells some of a good for money, and returns how much money it made.
+    /// Only whole goods are sold, and never more than the player has. Goods that can't be sold are left alone.
+    pub fn sell(&mut self, good: Good, amount: &F) -> F {
+        let zero = F::from(I::from(0));
+        let price = self.sell_price(good);
+        if good == Good::Money || price <= zero {
+            return zero;
+        }
+        let owned = self
+            .inventory
+            .get(&good)
+            .cloned()
+            .unwrap_or_else(|| zero.clone());
+        let sold = amount.clone().min(owned).floor().max(zero);
+        // The money is rounded down to whole cents, same as every other sale. Check round_money for more info.
+        let earned = lib::round_money(&(&sold * price));
+        self.transfer((good, &sold), (Good::Money, &earned));
+        earned
+    }
+
+    /// How much money one of a good costs to buy right now, after the economy settings. Zero means it can't be bought.
+    /// This is always at least the sell price. Check EconomySettings::buy_price for more info.
+    pub fn buy_price(&self, good: Good) -> F {
+        if good == Good::Money {
+            return F::from(I::from(0));
+        }
+        self.sim_settings
+            .economy
+            .buy_price(good.properties().base_price)
+    }
+
+    /// How many times a producer's recipe can be made by hand right now. Check crafting.rs for how crafting works.
+    pub fn max_craftable(&self, producer: Producer) -> u64 {
+        crafting::max_crafts(producer, &self.inventory, &self.storage_level)
+    }
+
+    /// Makes a producer's recipe by hand, up to count times, straight out of the inventory. Returns how many were made.
+    /// Each one takes a second's worth of the producer's inputs and makes a second's worth of its outputs. Check crafting.rs for more info.
+    pub fn craft(&mut self, producer: Producer, count: u64) -> u64 {
+        crafting::craft(producer, count, &mut self.inventory, &self.storage_level)
+    }
+
+    /// Buys some of a good with money, and returns how much money it cost.
+    /// Only whole goods are bought, and never more than there's room for, so nothing bought is thrown away.
+    /// If the player can't afford all of it, nothing is bought and the error says how much it would have cost. It never buys less than asked for to fit the money.
+    /// Money itself, and goods that have no price, can't be bought, and cost nothing.
+    #[allow(clippy::result_large_err)] // Same as try_buy_producer.
+    pub fn buy(&mut self, good: Good, amount: &F) -> Result<F, InsufficientFunds> {
+        let zero = F::from(I::from(0));
+        let price = self.buy_price(good);
+        if price <= zero {
+            return Ok(zero);
+        }
+        let mut bought = amount.floor().max(zero.clone());
+        if let Some(capacity) = self.capacity(good) {
+            let owned = self.inventory.get(&good).unwrap_or(&zero);
+            bought = bought.min((capacity - owned).floor().max(zero.clone()));
+        }
+        // The cost is rounded up to whole cents. Check round_money_up for more info.
+        let cost = lib::round_money_up(&(&bought * price));
+        if !self.transfer((Good::Money, &cost), (good, &bought)) {
+            return Err(InsufficientFunds {
+                cost,
+                available: self.money(),
+            });
+        }
+        Ok(cost)
+    }
+
+    /// How much of a good the player can hold, including storage upgrades. None means there's no limit.
+    pub fn capacity(&self, good: Good) -> Option<F> {
+        good.capacity(&self.storage_level)
+    }
+
+    /// Adds some of a good to the inventory, throwing away anything past its capacity.
+    /// Everything that gives the player goods outside of producers should go through here (or transfer), so capacity is handled the same everywhere.
+    pub fn add_good(&mut self, good: Good, amount: &F) {
+        good.store(&mut self.inventory, amount.clone(), &self.storage_level);
+    }
+
+    /// Takes some of a good out of the inventory. If there isn't enough, nothing is taken, and this returns false.
+    /// Negative amounts are refused the same way, since taking a negative amount would add goods.
+    pub fn remove_good(&mut self, good: Good, amount: &F) -> bool {
+        let zero = F::from(I::from(0));
+        if *amount < zero {
+            return false;
+        }
+        let owned = self.inventory.entry(good).or_insert(zero);
+        if *owned < *amount {
+            return false;
+        }
+        *owned -= amount;
+        true
+    }
+
+    /// Trades some of one good for some of another, like selling goods for money or paying money for goods.
+    /// The first good is removed, and the second is added (capped at its capacity). If there isn't enough of the first, nothing happens, and this returns false.
+    pub fn transfer(&mut self, from: (Good, &F), to: (Good, &F)) -> bool {
+        if !self.remove_good(from.0, from.1) {
+            return false;
+        }
+        self.add_good(to.0, to.1);
+        true
+    }
+
+    /// How many times a group's storage has been upgraded.
+    pub fn storage_level(&self, group: GoodGroup) -> u32 {
+        self.storage_level.get(&group).copied().unwrap_or(0)
+    }
+
+    /// How much the next storage upgrade for a group costs. None means the group has no capacity to upgrade.
+    /// The first upgrade costs 100, and every one after costs three times the last.
+    pub fn storage_upgrade_cost(&self, group: GoodGroup) -> Option<F> {
+        if Good::group_iter(group).all(|good| good.properties().capacity.is_none()) {
+            return None;
+        }
+        Some(Self::storage_cost_at(self.storage_level(group)))
+    }
+
+    // How much the upgrade from the given level to the next one costs.
+    fn storage_cost_at(level: u32) -> F {
+        F::from(I::from(100) * I::from(3).pow(level))
+    }
+
+    /// Buys a storage upgrade for a group, doubling the capacity of every good in it.
+    /// Fails without taking anything if the group has no capacity to upgrade, or the upgrade can't be afforded.
+    // Same as try_buy_producer, the error is big, but it's only made when a button is clicked.
+    #[allow(clippy::result_large_err)]
+    pub fn upgrade_storage(&mut self, group: GoodGroup) -> Result<(), StorageError> {
+        let Some(cost) = self.storage_upgrade_cost(group) else {
+            return Err(StorageError::NoCapacity(group));
+        };
+        if !self.remove_good(Good::Money, &cost) {
+            let available = self.money();
+            return Err(StorageError::InsufficientFunds(InsufficientFunds {
+                cost,
+                available,
+            }));
+        }
+        *self.storage_level.entry(group).or_insert(0) += 1;
+        Ok(())
+    }
+
+    /// Lists every region that has at least one element in it, in alphabetical order.
+    pub fn regions(&self) -> BTreeSet<String> {
+        self.elements
+            .values()
+            .filter_map(|element| element.region.clone())
+            .collect()
+    }
+
+    /// Moves an element into a region, or out of every region if the region is None. Returns whether the element exists.
+    pub fn move_to_region(&mut self, id: usize, region: Option<String>) -> bool {
+        match self.elements.get_mut(&id) {
+            Some(element) => {
+                element.region = region;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The configurations of every producer in a region, in the order they were added. Check clone_region for what they're for.
+    pub fn region_configs(&self, region: &str) -> Vec<ProducerConfig> {
+        self.elements
+            .values()
+            .filter(|element| element.region.as_deref() == Some(region))
+            .filter_map(ProducerConfig::of)
+            .collect()
+    }
+
+    /// Works out what cloning a region would do, without doing it. Returns (how many would be built, how many are in the region, what the ones built cost).
+    /// Producers are bought in order until the money runs out, so only the first ones are built if there isn't enough for all of them.
+    pub fn clone_region_preview(&self, region: &str) -> (usize, usize, F) {
+        let configs = self.region_configs(region);
+        let mut money = self.money();
+        let (mut built, mut cost) = (0, F::from(I::from(0)));
+        for config in configs.iter() {
+            let price = config.producer.properties().cost;
+            if price > money {
+                break;
+            }
+            money -= &price;
+            cost += price;
+            built += 1;
+        }
+        (built, configs.len(), cost)
+    }
+
+    /// Buys a copy of every producer in one region, configured the same, and puts the copies in another region. Returns how many were bought.
+    /// The copies are bought like any other producer, so they go through the build queue and get fresh ids when they're done.
+    /// If there isn't enough money for all of them, the first ones are bought and the rest aren't. Check clone_region_preview.
+    pub fn clone_region(&mut self, from: &str, to: &str) -> usize {
+        let mut bought = 0;
+        for mut config in self.region_configs(from) {
+            config.region = Some(to.to_string());
+            if self.try_buy_configured(config).is_err() {
+                break;
+            }
+            bought += 1;
+        }
+        bought
+    }
+
+    /// The factory's layout as a plan, for sharing. Only the producers and how they're set up are in it. Check plan.rs for more info.
+    pub fn export_plan(&self) -> String {
+        FactoryPlan {
+            producers: self
+                .elements
+                .values()
+                .filter_map(ProducerConfig::of)
+                .collect(),
+        }
+        .to_json()
+    }
+
+    /// Adds the producers from a plan made by export_plan, and returns how many were added. Nothing is added if the plan is bad.
+    /// Normally they're bought in order until the money runs out, so they go through the build queue like anything else.
+    /// If free is set, they're all added right away without paying, for sandboxing.
+    pub fn import_plan(&mut self, plan: &str, free: bool) -> Result<usize, PlanError> {
+        let plan = FactoryPlan::from_json(plan)?;
+        let mut added = 0;
+        for config in plan.producers {
+            if free {
+                let producer = config.producer;
+                let id = self.add_element(
+                    ElemVariant::Producer(producer),
+                    &producer.to_string(),
+                    false,
+                );
+                if let Some(element) = self.elements.get_mut(&id) {
+                    config.apply(element);
+                }
+            } else if self.try_buy_configured(config).is_err() {
+                break;
+            }
+            added += 1;
+        }
+        Ok(added)
+    }
+
+    /// Buys the producers in a chain plan, and returns how many were bought. They go through the build queue like anything else.
+    /// If the chain costs more than the player has, as much of it is bought as possible without throwing off its ratios.
+    /// Each purchase is whichever producer is furthest behind its share of the chain, and buying stops once that one can't be afforded,
+    /// so half the money gets about half of every part of the chain, rather than all of the drills and none of what feeds them.
+    /// Only up to MAX_PLAN_PRODUCERS are bought at once, same as importing a plan. Check chain.rs for how plans are made.
+    pub fn build_chain(&mut self, plan: &ChainPlan) -> usize {
+        let mut bought: BTreeMap<Producer, usize> = BTreeMap::new();
+        let mut total = 0;
+        while total < MAX_PLAN_PRODUCERS {
+            let next = plan
+                .producers
+                .iter()
+                .map(|(producer, count)| {
+                    (
+                        *producer,
+                        bought.get(producer).copied().unwrap_or(0),
+                        *count,
+                    )
+                })
+                .filter(|(_producer, done, count)| done < count)
+                .min_by_key(|(_producer, done, count)| F::new(I::from(*done), I::from(*count)));
+            let Some((producer, _done, _count)) = next else {
+                break;
+            };
+            if self.try_buy_producer(producer).is_err() {
+                break;
+            }
+            *bought.entry(producer).or_insert(0) += 1;
+            total += 1;
+        }
+        total
+    }
+
+    /// Counts how many of each producer there are in the game. Used by the production graph.
+    /// This is a BTreeMap so the producers always come out in the same order.
+    pub fn producer_breakdown(&self) -> BTreeMap<Producer, usize> {
+        let mut breakdown = BTreeMap::new();
+        for (_id, element) in self.elements.iter() {
+            if let ElemVariant::Producer(producer) = element.variant {
+                *breakdown.entry(producer).or_insert(0) += 1;
+            }
+        }
+        breakdown
+    }
+
+    /// Calculates how much of each good is produced and consumed per second, as (produced, consumed), if every producer runs at full output.
+    /// This is what the inventory shows.
+    /// Producers that the player has taken out of the table (see Element::count_in_table) are left out.
+    // Working it out means adding up every producer, which is slow with BigRationals, and it's needed every frame.
+    // It only changes when the producers do, so it's cached, and the cache is thrown away whenever they change. Check invalidate_production_table.
+    // The cache isn't saved, so it starts off empty after loading, and is worked out the first time it's needed.
+    pub fn production_table_theoretical(&self) -> HashMap<Good, (F, F)> {
+        if let Some(table) = self.production_cache.borrow().as_ref() {
+            return table.clone();
+        }
+        let table = self.production_table(true, None);
+        *self.production_cache.borrow_mut() = Some(table.clone());
+        table
+    }
+
+    // The theoretical production table with every producer in it, hidden from the table or not, since they all still eat their inputs.
+    // The simulation goes off this every tick (throttling and auto-selling), so it's cached the same way as the theoretical table.
+    fn production_table_running(&self) -> HashMap<Good, (F, F)> {
+        if let Some(table) = self.running_cache.borrow().as_ref() {
+            return table.clone();
+        }
+        let table = self.production_table(false, None);
+        *self.running_cache.borrow_mut() = Some(table.clone());
+        table
+    }
+
+    /// Calculates how much of each good is actually produced and consumed per second right now, as (produced, consumed).
+    /// Unlike the theoretical table, producers only count as much as they're running: starved or backed up producers count for nothing,
+    /// and warming up ones count for their warmup. When this is below the theoretical table, something is stalled.
+    /// Producers taken out of the table are left out, just like in the theoretical table.
+    pub fn production_table_effective(&self, tick_rate: &F) -> HashMap<Good, (F, F)> {
+        self.production_table(true, Some(tick_rate))
+    }
+
+    /// Estimates how many seconds it'll take for a good to reach a target amount, at the net rate from the production table.
+    /// None means it never will, because the good isn't going up. A good that's already there takes no time at all.
+    /// This is only an estimate. Producers starving, stalling on full storage or being auto-sold from all throw it off.
+    pub fn time_to_reach(&self, good: Good, target: &F) -> Option<F> {
+        let amount = self.inventory.get(&good).cloned().unwrap_or_default();
+        if &amount >= target {
+            return Some(F::from(I::from(0)));
+        }
+        let (made, used) = self
+            .production_table_theoretical()
+            .remove(&good)
+            .unwrap_or_default();
+        let net = made - used;
+        if net <= F::from(I::from(0)) {
+            return None;
+        }
+        Some((target - amount) / net)
+    }
+
+    /// Calculates how much of each good is produced and consumed per second, as (produced, consumed), once every producer settles
+    /// into the rate its inputs can keep up with. Stockpiles aren't counted, so this is what the factory makes after they run out.
+    /// The solution also says whether the solver settled. It doesn't always, with cycles. Check solver.rs for more info.
+    /// Producers taken out of the table are left out, just like in the theoretical table.
+    /// It's cached, like the theoretical table, since the solver goes over every producer many times.
+    pub fn production_table_sustainable(&self) -> Solution {
+        if let Some(solution) = self.sustainable_cache.borrow().as_ref() {
+            return solution.clone();
+        }
+        let solution = solver::solve(&self.production_flows(true, None));
+        *self.sustainable_cache.borrow_mut() = Some(solution.clone());
+        solution
+    }
+
+    // The tick rate is only given for the effective table. Check production_flows for more info.
+    fn production_table(
+        &self,
+        respect_table_toggle: bool,
+        tick_rate: Option<&F>,
+    ) -> HashMap<Good, (F, F)> {
+        // This function calculates the theoretical production of all the goods in the game.
+        // This is done by gathering all the inputs and outputs of all the producers in the game, and adding them together.
+        // Eventually, producers will interact with stockpiles rather than the inventory directly, so this will eventually be scrapped.
+        // It's good for now though.
+        // Money is treated like any other good here. Producers selling their output directly count as making money, so that shows up in money's row.
+        // Auto-selling does too. Check the end of this function.
+        let mut hashmap = HashMap::new();
+        for flow in self.production_flows(respect_table_toggle, tick_rate) {
+            // Iterate over the inputs and outputs, and add them to the hashmap.
+            for (good, amount) in flow.outputs {
+                hashmap
+                    .entry(good)
+                    .or_insert((F::from(I::from(0)), F::from(I::from(0))))
+                    .0 += amount;
+            }
+            for (good, amount) in flow.inputs {
+                hashmap
+                    .entry(good)
+                    .or_insert((F::from(I::from(0)), F::from(I::from(0))))
+                    .1 += amount;
+            }
+        }
+        // Auto-selling runs every tick, so an auto-sold good's surplus is sold as fast as it's made, and turns into money at the same rate.
+        // What the producers use of it is held back, so only what's left over after them counts. The good's own row is left as it is.
+        let zero = F::from(I::from(0));
+        let mut income = zero.clone();
+        for (good, rule) in self.auto_sell.iter() {
+            if !rule.enabled {
+                continue;
+            }
+            if let Some((made, used)) = hashmap.get(good) {
+                if made > used {
+                    income += (made - used) * self.sell_price(*good);
+                }
+            }
+        }
+        if income > zero {
+            hashmap.entry(Good::Money).or_insert((zero.clone(), zero)).0 += income;
+        }
+        hashmap
+        // This recalculates the entire hashmap, so the theoretical table is cached. Check production_table_theoretical.
+        // We don't have to recalculate the entire hashmap, just the contributions of the changed producer.
+        // I don't know if this is even worth fixing. Again, I plan on scrapping this function once it becomes obsolete.
+    }
+
+    // What every running producer makes and uses per second at its full rate. Every production table is built from these.
+    // Given a tick rate, each producer is scaled by how efficient it is this tick instead, which is what the effective table uses.
+    fn production_flows(&self, respect_table_toggle: bool, tick_rate: Option<&F>) -> Vec<Flow> {
+        let mut flows = Vec::new();
+        for (_id, element) in self.elements.iter() {
+            // Disabled producers don't produce anything, so they don't count.
+            if !element.enabled {
+                continue;
+            }
+            // Producers can be hidden from the table for debugging. They still run, they're just not shown.
+            if respect_table_toggle && !element.count_in_table {
+                continue;
+            }
+            if let ElemVariant::Producer(producer) = element.variant {
+                // Get the properties of the producer, which contains the inputs and outputs.
+                let properties = producer.properties();
+                // Capped producers run slower than their properties say. Check Element::throughput_scale for more info.
+                let mut scale = element.throughput_scale();
+                if let Some(tick_rate) = tick_rate {
+                    scale *= element.efficiency(
+                        &self.inventory,
+                        tick_rate,
+                        &self.sim_settings,
+                        &self.storage_level,
+                    );
+                }
+                // Sold outputs are counted as the money they make.
+                let scaled = |inventory: Inventory| {
+                    inventory
+                        .into_iter()
+                        .map(|(good, amount)| (good, amount * &scale))
+                        .collect()
+                };
+                flows.push(Flow {
+                    outputs: scaled(producer.deposited_outputs(element.sell(&self.sim_settings))),
+                    inputs: scaled(properties.recipe.inputs),
+                });
+            }
+        }
+        flows
+    }
+}
+
+// Enum for the radio buttons that determine which section of the game the player is viewing.
+#[derive(serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Copy, EnumIter, Default)]
+enum Selection {
+    #[default]
+    Summary,
+    Metallurgy,
+    Graph,
+    Achievements,
+    Settings,
+}
+
+// The parts of the game that can be reset on their own, from the settings tab. Check display_reset_section for more info.
+#[derive(PartialEq, Eq, Clone, Copy, EnumIter)]
+enum PartialReset {
+    Producers,
+    Inventory,
+    Minigames,
+}
+
+impl PartialReset {
+    // What the reset does, for the confirmation.
+    fn description(&self) -> &'static str {
+        match self {
+            PartialReset::Producers => "Removes every producer and refunds the build queue.",
+            PartialReset::Inventory => "Sets every good to zero, money included.",
+            PartialReset::Minigames => "Gives every ore minigame a new button order.",
+        }
+    }
+}
+
+impl Display for PartialReset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PartialReset::Producers => write!(f, "Clear all producers"),
+            PartialReset::Inventory => write!(f, "Clear inventory"),
+            PartialReset::Minigames => write!(f, "Reset minigames"),
+        }
+    }
+}
+
+// The side panels, for when they're shown as tabs in the narrow layout.
+#[derive(serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Copy, EnumIter)]
+enum SidePanelTab {
+    Inventory,
+    Producers,
+}
+
+impl Display for SidePanelTab {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SidePanelTab::Inventory => write!(f, "Inventory"),
+            SidePanelTab::Producers => write!(f, "Producers"),
+        }
+    }
+}
+
+// Which producers are shown in the producers panel, by region.
+#[derive(serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Default)]
+enum RegionFilter {
+    #[default]
+    All, // Every producer, no matter the region.
+    Unassigned,     // Only producers which aren't in any region.
+    Region(String), // Only producers in the given region.
+}
+
+impl RegionFilter {
+    // Whether an element with the given region passes the filter.
+    fn matches(&self, region: &Option<String>) -> bool {
+        match self {
+            RegionFilter::All => true,
+            RegionFilter::Unassigned => region.is_none(),
+            RegionFilter::Region(name) => region.as_ref() == Some(name),
+        }
+    }
+}
+
+// The UI state that's saved along with the game, so the game opens up the way the player left it.
+// Only things players expect to stick go in here: which tab is selected, which region the producers panel is filtered to,
+// and which side panel is open in the narrow layout.
+// Anything that only matters for a moment stays in IdleGame as serde(skip) instead, like text being typed, fades and flashes.
+// The same goes for anything egui already remembers on its own, like scroll positions and which windows are where.
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+#[serde(default)]
+struct UiState {
+    selection: Selection,             // Which tab the player is viewing.
+    region_filter: RegionFilter,      // Which region's producers are shown in the producers panel.
+    side_panel: Option<SidePanelTab>, // In the narrow layout, which side panel is shown instead of the selected tab, if any.
+}
+
+impl Display for RegionFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegionFilter::All => write!(f, "All regions"),
+            RegionFilter::Unassigned => write!(f, "No region"),
+            RegionFilter::Region(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl Display for Selection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Summary => write!(f, "Summary"),
+            Self::Metallurgy => write!(f, "Metallurgy"),
+            Self::Graph => write!(f, "Production Graph"),
+            Self::Achievements => write!(f, "Achievements"),
+            Self::Settings => write!(f, "Settings"),
+        }
+    }
+}
+
+// The main game struct. Contains all the data that needs to be saved. Also contains the game state.
+// The additional parameters are for the egui integration, and for calculating the time between frames.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct IdleGame {
+    prev_time: chrono::DateTime<chrono::Utc>, // The timestamp of the previous frame. Used for calculating the time between frames.
+    #[serde(skip)]
+    normalize_timer: F, // How many seconds it's been since the game state was last normalized. Check GameState::normalize.
+    game_timer: F, // Holds the amount of "ticks" that have passed. Used for keeping the tick rate consistent, even if the framerate is inconsistent.
+    // This is done by ticking the game until game_timer is less than 1, and then rendering the game.
+    // Of course there is a limit in order to avoid a lag spiral.
+    speed_multiplier: F, // How many seconds of game time pass per second of real time. It's exact, so 5x for an hour is exactly 1x for five hours.
+    #[serde(skip)]
+    paused: bool, // Whether time is stopped. Nothing ticks while paused, and the time that passes isn't made up for afterwards. Not saved, so the game always starts running.
+    game_state: GameState, // Stores the state of the game.
+    producer_index_marked_for_deletion: Option<(usize, usize)>, // Hacky way of deleting producers. Holds the id, and how many like it to delete. Check GameState::remove_similar.
+    ui_state: UiState, // The parts of the UI that stick between sessions, like the selected tab. Check UiState for more info.
+    debug_amt_slider: I, // The amount of the selected good that is added to the inventory when the debug button is pressed.
+    debug_seed: u64,     // The seed used by the "Reset with seed" debug button.
+    settings: Settings,  // The player's preferences. Check settings.rs for more info.
+    #[serde(skip)]
+    sounds: Sounds, // Plays the game's sounds. Check sound.rs for more info.
+    #[serde(skip)]
+    notifications: Notifications, // The notifications currently on screen. Check notifications.rs for more info.
+    #[serde(skip)]
+    previous_selection: Selection, // The selection as of last frame. Used to notice when the tab changes, for the fade between tabs.
+    #[serde(skip)]
+    tab_changed_at: f64, // When the tab last changed, in egui's time. The fade between tabs is timed from this.
+    #[serde(skip)]
+    rate_smoother: RateSmoother, // The measured rates of every good, smoothed for display. Check rates.rs for more info.
+    #[serde(skip)]
+    effective_table: HashMap<Good, (F, F)>, // The effective production table, worked out alongside the theoretical one. Check GameState::production_table_effective.
+    #[serde(skip)]
+    ticker: ProductionTicker, // The smoothed rates in the top bar's production ticker. Check rates.rs for more info.
+    #[serde(skip)]
+    inventory_flashes: InventoryFlashes, // The highlights on inventory rows that just changed. Check flash.rs for more info.
+    #[serde(skip)]
+    offline_preview: Option<OfflinePreview>, // The offline progress waiting to be accepted or discarded. Check offline.rs for more info.
+    #[serde(skip)]
+    undo_stack: Vec<GameState>, // Snapshots of the game state from before each undoable action. The last one is the most recent.
+    #[serde(skip)]
+    pending_reset: Option<PartialReset>, // The partial reset waiting to be confirmed, if any. Check display_reset_section for more info.
+    #[serde(skip)]
+    eta_targets: BTreeMap<Good, F>, // The targets typed into goods' windows, for the "time to reach" estimate.
+    #[serde(skip)]
+    build_category: Option<ProducerCategory>, // Which category the build menu is filtered to. None shows every category.
+    #[serde(skip)]
+    build_search: String, // The text in the build menu's search box. Only producers with it in their name are shown.
+    #[serde(skip)]
+    achievement_view: AchievementView, // The achievements tab's search, filter and cached progress. Check achievements.rs for more info.
+    #[serde(skip)]
+    clone_target: String, // The region typed into the "clone region" box in the producers panel.
+    #[serde(skip)]
+    plan_draft: String, // The text in the "Import a factory plan" box in the build section.
+    #[serde(skip)]
+    plan_free: bool, // Whether importing a plan adds the producers for free. Check GameState::import_plan.
+    #[serde(skip)]
+    paste_draft: String, // The text in the "Paste a producer" box in the build section.
+    #[serde(skip)]
+    migration_report: Option<String>, // The result of the last dry run of the migrations on the stored save. Debug only, check dry_run_stored_save.
+    #[serde(skip)]
+    save_size_report: Option<String>, // The result of the last "Measure save size" button press in the settings tab.
+    #[serde(skip)]
+    keyboard_ore: Option<Good>, // The ore whose minigame the number keys play. None is the first ore. Changed with the up and down arrows.
+    #[serde(skip)]
+    sell_amounts: BTreeMap<Good, F>, // How much of each good the market section sells at a time. Goods that haven't been changed sell 1. Check display_market_section.
+    #[serde(skip)]
+    chain_rates: BTreeMap<Good, F>, // The rates typed into goods' windows, for the "build the whole chain" planner. Check chain.rs.
+    #[serde(skip)]
+    pending_chain: Option<ChainPlan>, // The chain waiting to be confirmed, if any. Check display_chain_confirmation.
+    #[serde(skip)]
+    save_draft: String, // The text in the "Paste a save" box, in the settings tab and the import window. Check display_save_draft.
+    #[serde(skip)]
+    import_window: bool, // Whether the import window from the Save menu is open. Check display_import_window.
+    #[serde(skip)]
+    pending_import: bool, // Whether importing the pasted save is waiting to be confirmed. Check display_import_confirmation.
+    #[serde(skip)]
+    backup_before_import: bool, // Whether confirming an import copies the current save to the clipboard first. On by default.
+    #[serde(skip)]
+    whats_new: Option<u32>, // The version the save was written in, if it's older than this build and the "What's new" window hasn't been closed yet.
+    save_version: u32, // The version of the save format. Saves from before versioning don't have this field, so it defaults to 0 for them.
+                       // Check save.rs for more info on save versions and migrations.
+}
+
+// Default implementation for IdleGame. Used for deserialization, and for resetting the game.
+impl Default for IdleGame {
+    fn default() -> Self {
+        Self {
+            prev_time: chrono::Utc::now(),
+            normalize_timer: F::from(I::from(0)),
+            game_timer: F::new(I::from(0), I::from(1)),
+            speed_multiplier: F::from(I::from(1)),
+            paused: false,
+            game_state: GameState::default(),
+            producer_index_marked_for_deletion: None,
+            ui_state: UiState::default(),
+            debug_amt_slider: I::from(100),
+            debug_seed: 0,
+            whats_new: None,
+            save_version: save::SAVE_VERSION,
+            settings: Settings::default(),
+            notifications: Notifications::default(),
+            sounds: Sounds::default(),
+            inventory_flashes: InventoryFlashes::default(),
+            rate_smoother: RateSmoother::default(),
+            effective_table: HashMap::new(),
+            ticker: ProductionTicker::default(),
+            previous_selection: Selection::default(),
+            tab_changed_at: f64::NEG_INFINITY,
+            offline_preview: None,
+            undo_stack: Vec::new(),
+            save_size_report: None,
+            keyboard_ore: None,
+            sell_amounts: BTreeMap::new(),
+            chain_rates: BTreeMap::new(),
+            pending_chain: None,
+            save_draft: String::new(),
+            import_window: false,
+            pending_import: false,
+            backup_before_import: true,
+            migration_report: None,
+            pending_reset: None,
+            eta_targets: BTreeMap::new(),
+            build_category: None,
+            build_search: String::new(),
+            achievement_view: AchievementView::default(),
+            clone_target: String::new(),
+            plan_draft: String::new(),
+            plan_free: false,
+            paste_draft: String::new(),
+        }
+    }
+}
+
+impl IdleGame {
+    // Retrieves the saved game from the local storage, or creates a new game if there is no saved game.
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        if let Some(storage) = cc.storage {
+            // The save is either in the compact format or eframe's usual RON, depending on the compact_save setting when it was saved.
+            let mut game: Self = match storage
+                .get_string(eframe::APP_KEY)
+                .and_then(|save| save::decode_compact(&save))
+            {
+                Some(compact) => compact.unwrap_or_default(),
+                None => eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default(),
+            };
+            game.game_state.reseed();
+            game.game_state.ensure_goods_present();
+            game.game_state.fix_next_id();
+            game.repair_window_ids();
+            game.note_whats_new(game.save_version);
+            // Normally, this game can calculate offline progress, but it's disabled for now unless the player opts in.
+            // Even then, the progress is only a preview on a copy of the game state, which the player can accept or discard.
+            // It's capped, and caught up in big steps rather than tick by tick. Check offline.rs for more info.
+            let now = chrono::Utc::now();
+            if game.settings.offline_progress {
+                let millis = (now - game.prev_time).num_milliseconds().max(0);
+                let away = F::new(I::from(millis), I::from(1000));
+                if away >= tick_rate() {
+                    game.offline_preview = Some(OfflinePreview::new(
+                        &game.game_state,
+                        &game.game_timer,
+                        away,
+                        game.settings.offline_cap_hours,
+                        &tick_rate(),
+                    ));
+                }
+            }
+            // Whether or not there's a preview, the live game picks up from now. Discarding the preview just keeps it as it was.
+            game.prev_time = now;
+            // It's off by default for three reasons:
+            // 1. The game is currently in development, and I don't want a sudden flurry of progress to happen while the game is closed and I'm changing the code.
+            // 2. While this is an idle game, it's not really an idle game. It's closer to Factorio, and some of the mechanics will require the player to be active.
+            // 3. The large amount of calculations that need to be done to calculate offline progress is very slow, and can easily create a lag spiral.
+            // That one's mostly solved now, by the cap and by catching up with advance_coarse.
+            return game;
+        }
+
+        Default::default()
+    }
+
+    // Exports the whole game as a JSON string, so it can be backed up or shared.
+    // The save ends with a checksum, so corruption can be caught when it's imported again. Check save.rs for more info.
+    pub fn export_save(&self) -> String {
+        save::add_checksum(
+            &serde_json::to_string(self).expect("the game should always be serializable"),
+        )
+    }
+
+    // Exports the game as a backup string, which is export_save in base64. Check the top of save.rs for why.
+    pub fn export_backup(&self) -> String {
+        save::encode_backup(&self.export_save())
+    }
+
+    // Imports a game from a JSON string made by export_save, or a backup string made by export_backup, replacing the current game.
+    // Saves from older builds are migrated to the current version first. Saves from newer builds are rejected.
+    // If anything goes wrong, the current game is left untouched. That includes the save not matching its checksum.
+    // This doesn't ask first. The settings tab does, with display_import_confirmation.
+    pub fn import_save(&mut self, save: &str) -> Result<(), SaveError> {
+        let backup = save::decode_backup(save).transpose()?;
+        let save = save::verify_checksum(backup.as_deref().unwrap_or(save))?;
+        let mut value: serde_json::Value =
+            serde_json::from_str(save).map_err(|e| SaveError::Malformed(e.to_string()))?;
+        // Migrating stamps the current version on the save, so the old one is read first for the "What's new" window.
+        let version = save::save_version(&value)?;
+        save::migrate(&mut value)?;
+        let mut game: Self =
+            serde_json::from_value(value).map_err(|e| SaveError::Malformed(e.to_string()))?;
+        game.game_state.reseed();
+        game.game_state.ensure_goods_present();
+        game.game_state.fix_next_id();
+        game.repair_window_ids();
+        // Same as loading from storage. Offline progress is disabled, so the time the save spent exported doesn't count.
+        game.prev_time = chrono::Utc::now();
+        game.note_whats_new(version);
+        *self = game;
+        Ok(())
+    }
+
+    // Dry-runs the migrations on a save made by export_save, and reports every field that would change when importing it.
+    // Nothing is imported. This is for checking migrations before they ship, check save::dry_run for more info.
+    pub fn dry_run_migration(save: &str) -> Result<MigrationReport, SaveError> {
+        let save = save::verify_checksum(save)?;
+        let value: serde_json::Value =
+            serde_json::from_str(save).map_err(|e| SaveError::Malformed(e.to_string()))?;
+        save::dry_run::<Self>(&value)
+    }
+
+    // Dry-runs the migrations on the save in storage, the one the game was loaded from. The report is for the debug tools in the settings tab.
+    // Stored saves are in eframe's RON or the compact format, so they're read as JSON values from whichever one it is.
+    fn dry_run_stored_save(storage: Option<&dyn eframe::Storage>) -> String {
+        let Some(storage) = storage else {
+            return String::from("There's no storage to read a save from.");
+        };
+        let value = match storage
+            .get_string(eframe::APP_KEY)
+            .and_then(|save| save::decode_compact::<serde_json::Value>(&save))
+        {
+            Some(compact) => compact,
+            None => eframe::get_value(storage, eframe::APP_KEY).ok_or_else(|| {
+                SaveError::Malformed(String::from("there's no readable save in storage"))
+            }),
+        };
+        match value.and_then(|value| save::dry_run::<Self>(&value)) {
+            Ok(report) => report.to_string(),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    // Fixes the duplicate window ids left over in old saves, and lets the player know if there were any. Check GameState::repair_window_ids.
+    fn repair_window_ids(&mut self) {
+        let repaired = self.game_state.repair_window_ids();
+        if repaired > 0 {
+            self.notifications.push(format!(
+                "Repaired {repaired} producer windows that shared an id"
+            ));
+        }
+    }
+
+    // Queues up the "What's new" window if the save was written in an older version, and there's something new since then.
+    // The save is stamped with the current version right away, so the window only ever shows once. Check changelog.rs for more info.
+    fn note_whats_new(&mut self, version: u32) {
+        if !changelog::entries_since(version).is_empty() {
+            self.whats_new = Some(version);
+        }
+        self.save_version = save::SAVE_VERSION;
+    }
+
+    // The changelog entries waiting to be shown in the "What's new" window, oldest first. Empty once the window is closed.
+    pub fn whats_new(&self) -> Vec<(u32, &'static [&'static str])> {
+        self.whats_new
+            .map_or_else(Vec::new, changelog::entries_since)
+    }
+
+    // Compares how big the save is as JSON and in the compact format. Used by the settings tab.
+    fn save_size_report(&self) -> String {
+        let json = self.export_save().len();
+        match save::encode_compact(self) {
+            Ok(compact) => format!(
+                "JSON: {json} bytes, compact: {} bytes ({:.0}% smaller)",
+                compact.len(),
+                100.0 - compact.len() as f64 / json as f64 * 100.0
+            ),
+            Err(e) => format!("JSON: {json} bytes, compact: failed ({e})"),
+        }
+    }
+
+    // Renders the save export and import controls in the settings tab.
+    fn display_save_transfer(&mut self, ui: &mut Ui) {
+        ui.label(RichText::new("Save transfer").strong());
+        ui.horizontal(|ui| {
+            if ui.button("Copy save to clipboard").clicked() {
+                ui.output().copied_text = self.export_save();
+                self.notifications.push("Copied the save to the clipboard");
+            }
+            self.display_copy_backup(ui);
+            self.display_save_draft(ui);
+        });
+    }
+
+    // Renders the button that copies a backup string to the clipboard. Check export_backup.
+    // Returns whether it was clicked, so the Save menu can close itself.
+    fn display_copy_backup(&mut self, ui: &mut Ui) -> bool {
+        let clicked = ui
+            .button("Copy backup to clipboard")
+            .on_hover_text("The whole save as a single line of text, for keeping somewhere safe")
+            .clicked();
+        if clicked {
+            ui.output().copied_text = self.export_backup();
+            self.notifications
+                .push("Copied a backup of the save to the clipboard");
+        }
+        clicked
+    }
+
+    // Renders the box for pasting a save or a backup, and the button to import it.
+    // Importing doesn't happen right away, since it replaces the whole game. It waits for display_import_confirmation.
+    fn display_save_draft(&mut self, ui: &mut Ui) {
+        ui.add(egui::TextEdit::singleline(&mut self.save_draft).hint_text("Paste a save here"));
+        if ui
+            .add_enabled(
+                !self.save_draft.trim().is_empty(),
+                egui::Button::new("Import"),
+            )
+            .clicked()
+        {
+            self.pending_import = true;
+        }
+    }
+
+    // Renders the import window from the Save menu, which is the settings tab's paste box on its own.
+    // It closes once the import is asked for, and the confirmation takes over from there.
+    fn display_import_window(&mut self, ctx: &egui::Context) {
+        if !self.import_window {
+            return;
+        }
+        let mut open = true;
+        egui::Window::new("Import a save")
+            .id(egui::Id::new("import_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Paste a save or a backup string to replace the current game with it.");
+                ui.horizontal(|ui| self.display_save_draft(ui));
+            });
+        if !open || self.pending_import {
+            self.import_window = false;
+        }
+    }
+
+    // Asks the player to confirm importing the pasted save, since it replaces the current game and can't be undone.
+    // The current save can be copied to the clipboard first, so the progress isn't lost if the import was a mistake.
+    // The backup is made with export_save, so it can be imported again like any other save.
+    fn display_import_confirmation(&mut self, ctx: &egui::Context) {
+        if !self.pending_import {
+            return;
+        }
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("Replace current game?")
+            .id(egui::Id::new("import_confirmation"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label("Importing this save replaces all of your current progress.");
+                ui.label("This can't be undone.");
+                ui.checkbox(
+                    &mut self.backup_before_import,
+                    "Copy the current save to the clipboard first",
+                );
+                ui.horizontal(|ui| {
+                    confirmed = ui.button("Import").clicked();
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            });
+        if confirmed {
+            let backup = self.backup_before_import.then(|| self.export_save());
+            let save = std::mem::take(&mut self.save_draft);
+            // Importing replaces everything, including the notifications, so they're pushed afterwards.
+            // If it fails, the current game is left untouched, and the pasted save stays in the box to be fixed.
+            match self.import_save(save.trim()) {
+                Ok(()) => self.notifications.push("Imported the save"),
+                Err(e) => {
+                    self.notifications
+                        .push(format!("Couldn't import the save: {e}"));
+                    self.save_draft = save;
+                }
+            }
+            if let Some(backup) = backup {
+                ctx.output().copied_text = backup;
+                self.notifications
+                    .push("Copied the old save to the clipboard");
+            }
+        }
+        if confirmed || cancelled || !open {
+            self.pending_import = false;
+        }
+    }
+
+    // Saves a snapshot of the game state, so the next action can be undone.
+    // Only the most recent snapshots are kept, so the undo stack can't eat all the memory.
+    fn push_undo(&mut self) {
+        self.push_undo_state(self.game_state.clone());
+    }
+
+    // Saves a copy of the game state from before an action that's already happened, for actions that only count as undoable if they worked.
+    fn push_undo_state(&mut self, game_state: GameState) {
+        self.undo_stack.push(game_state);
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    // Swaps in a whole other game state, like after an undo or a reset.
+    // Whatever the UI cached from the old one is thrown away, since the new one's stats revisions have nothing to do with the old one's.
+    fn replace_game_state(&mut self, game_state: GameState) {
+        self.game_state = game_state;
+        self.achievement_view.cache = AchievementCache::default();
+    }
+
+    // Restores the game state from before the last undoable action.
+    fn undo(&mut self) {
+        if let Some(game_state) = self.undo_stack.pop() {
+            self.replace_game_state(game_state);
+            self.notifications.push("Undid the last action");
+        }
+    }
+
+    // Enables or disables all producers matching the filter as a single undoable action, and tells the player how many changed.
+    fn toggle_producers(&mut self, enabled: bool, filter: impl Fn(&Producer) -> bool) {
+        self.push_undo();
+        let changed = self.game_state.set_producers_enabled(enabled, filter);
+        let verb = if enabled { "Enabled" } else { "Disabled" };
+        self.notifications
+            .push(format!("{verb} {changed} producers"));
+    }
+
+    // Renders the partial resets, which reset one part of the game instead of the whole thing.
+    // Each one has to be confirmed, and can be undone afterwards, since they're a lot easier to click by accident than the Reset button.
+    // Settings aren't part of the game state, so none of these touch them.
+    fn display_reset_section(&mut self, ui: &mut Ui) {
+        ui.label(RichText::new("Partial resets").strong());
+        ui.horizontal(|ui| {
+            for reset in PartialReset::iter() {
+                if ui
+                    .button(reset.to_string())
+                    .on_hover_text(reset.description())
+                    .clicked()
+                {
+                    self.pending_reset = Some(reset);
+                }
+            }
+        });
+        let Some(reset) = self.pending_reset else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label(format!("{reset}? {}", reset.description()));
+            if ui.button("Yes").clicked() {
+                self.push_undo();
+                match reset {
+                    PartialReset::Producers => {
+                        let removed = self.game_state.clear_producers();
+                        self.notifications
+                            .push(format!("Removed {removed} producers"));
+                    }
+                    PartialReset::Inventory => {
+                        self.game_state.clear_inventory();
+                        self.notifications.push("Cleared the inventory");
+                    }
+                    PartialReset::Minigames => {
+                        self.game_state.reset_minigames(&self.settings.minigames);
+                        self.notifications.push("Reset the minigames");
+                    }
+                }
+                self.pending_reset = None;
+            }
+            if ui.button("No").clicked() {
+                self.pending_reset = None;
+            }
+        });
+    }
+
+    // Renders one of the side panels. On wide screens they're real side panels, and on narrow ones they're tabs. Check update for more info.
+    fn display_panel(&mut self, ui: &mut Ui, panel: SidePanelTab, tick_rate: &F) {
+        self.settings.density.apply(ui);
+        match panel {
+            SidePanelTab::Inventory => {
+                ui.heading("Inventory");
+                self.display_inventory_grid(ui);
+            }
+            SidePanelTab::Producers => self.display_producers_panel(ui, tick_rate),
+        }
+    }
+
+    // Renders what an ore's minigame is worth, before the buttons: how many buttons it has, the best solve, and the expected ore per minute.
+    fn display_minigame_preview(&self, ui: &mut Ui, ore: Good) {
+        let difficulty = ore.properties().difficulty;
+        let interval = self.settings.minigames.click_interval(difficulty);
+        let best = self.game_state.stats().best_solve.get(&ore).copied();
+        ui.label(format!("{difficulty} buttons"));
+        match best {
+            Some(seconds) => ui.label(format!("Best: {seconds:.2}s")),
+            None => ui.label("Best: none yet"),
+        };
+        ui.label(format!(
+            "~{:.1} ore/min",
+            stats::ores_per_minute(difficulty, interval, best)
+        ))
+        .on_hover_text(
+            "If every solve is as fast as your best one. Before your first solve, it's a guess.",
+        );
+    }
+
+    // Renders the controls for cloning a region into another one. Shows what it'll cost first, and can be undone.
+    fn display_clone_region(&mut self, ui: &mut Ui, region: &str) {
+        let (built, total, cost) = self.game_state.clone_region_preview(region);
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.clone_target).hint_text("Clone into region"),
+            );
+            let target = self.clone_target.trim().to_string();
+            if ui
+                .add_enabled(!target.is_empty() && built > 0, egui::Button::new("Clone"))
+                .clicked()
+            {
+                self.push_undo();
+                let bought = self.game_state.clone_region(region, &target);
+                self.notifications.push(format!(
+                    "Cloned {bought} producers from {region} into {target}"
+                ));
+                self.clone_target.clear();
+            }
+        });
+        ui.label(format!("Would build {built} of {total} for ${cost}"));
+    }
+
+    fn display_producers_panel(&mut self, ui: &mut Ui, tick_rate: &F) {
+        // Renders the producers panel, with the bulk toggles, the region filter, and a row for every producer.
+        ui.heading("Producers");
+        // Everything under the heading scrolls, so the list stays reachable with lots of producers.
+        egui::ScrollArea::vertical()
+            .id_source("producers_scroll")
+            .show(ui, |ui| {
+                // Buttons for enabling or disabling producers in bulk, both all at once and by type.
+                ui.horizontal(|ui| {
+                    if ui.button("Enable all").clicked() {
+                        self.toggle_producers(true, |_| true);
+                    }
+                    if ui.button("Disable all").clicked() {
+                        self.toggle_producers(false, |_| true);
+                    }
+                });
+                ui.collapsing("By type", |ui| {
+                    let names: std::collections::BTreeSet<&'static str> = self
+                        .game_state
+                        .producer_breakdown()
+                        .keys()
+                        .map(|producer| producer.properties().name)
+                        .collect();
+                    egui::Grid::new("producer_type_toggles").show(ui, |grid_ui| {
+                        for name in names {
+                            grid_ui.label(name);
+                            if grid_ui.button("Enable").clicked() {
+                                self.toggle_producers(true, |producer| {
+                                    producer.properties().name == name
+                                });
+                            }
+                            if grid_ui.button("Disable").clicked() {
+                                self.toggle_producers(false, |producer| {
+                                    producer.properties().name == name
+                                });
+                            }
+                            grid_ui.end_row();
+                        }
+                    });
+                });
+                ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                // Filters the producer list by region. If the selected region has emptied out since, it falls back to showing everything.
+                let regions = self.game_state.regions();
+                if let RegionFilter::Region(name) = &self.ui_state.region_filter {
+                    if !regions.contains(name) {
+                        self.ui_state.region_filter = RegionFilter::All;
+                    }
+                }
+                egui::ComboBox::from_id_source("region_filter")
+                    .selected_text(self.ui_state.region_filter.to_string())
+                    .show_ui(ui, |ui| {
+                        let mut filters = vec![RegionFilter::All, RegionFilter::Unassigned];
+                        filters.extend(regions.iter().cloned().map(RegionFilter::Region));
+                        for filter in filters {
+                            let text = filter.to_string();
+                            ui.selectable_value(&mut self.ui_state.region_filter, filter, text);
+                        }
+                    });
+                if let RegionFilter::Region(name) = self.ui_state.region_filter.clone() {
+                    self.display_clone_region(ui, &name);
+                }
+                let palette = self.settings.palette();
+                let bulk = bulk_count(ui);
+                egui::Grid::new("producers_grid")
+                    .striped(self.settings.striped)
+                    .show(ui, |grid_ui| {
+                        for (id, element) in self.game_state.elements.iter_mut() {
+                            if !self.ui_state.region_filter.matches(&element.region) {
+                                continue;
+                            }
+                            // The row is colored by how efficient the producer is: green when fully running, yellow when partially, and red when stalled.
+                            let efficiency = element.efficiency(
+                                &self.game_state.inventory,
+                                tick_rate,
+                                &self.game_state.sim_settings,
+                                &self.game_state.storage_level,
+                            );
+                            let color = if efficiency >= F::from(I::from(1)) {
+                                palette.good
+                            } else if efficiency > F::from(I::from(0)) {
+                                palette.partial
+                            } else {
+                                palette.bad
+                            };
+                            if let ElemVariant::Producer(producer) = element.variant {
+                                // Renders the producer row for each producer.
+                                // Renders the producer name, and a button to open the producer's window.
+                                if grid_ui
+                                    .button(RichText::new(producer.to_string()).color(color))
+                                    .clicked()
+                                {
+                                    element.toggle_window();
+                                }
+                                // Renders a button to delete the producer. Shift or ctrl deletes more like it.
+                                if grid_ui
+                                    .button("X")
+                                    .on_hover_text(bulk_hint("Deletes", bulk))
+                                    .clicked()
+                                {
+                                    self.producer_index_marked_for_deletion = Some((*id, bulk));
+                                }
+                                grid_ui.end_row();
+                            }
+                        }
+                    });
+            });
+    }
+
+    fn display_build_section(&mut self, ui: &mut Ui) {
+        // Renders the build menu and the build queue.
+        // Buying a producer takes the money right away, but the producer has to be built before it starts producing.
+        ui.label(RichText::new("Build").strong());
+        // Producers copied from a producer's window can be pasted here, which buys a copy. Check clipboard.rs for more info.
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.paste_draft).hint_text("Paste a producer"));
+            if ui
+                .add_enabled(
+                    !self.paste_draft.trim().is_empty(),
+                    egui::Button::new("Paste"),
+                )
+                .clicked()
+            {
+                match ProducerConfig::from_json(&self.paste_draft) {
+                    Ok(config) => match self.game_state.try_buy_configured(config) {
+                        Ok(()) => self.paste_draft.clear(),
+                        Err(e) => self.notifications.push(e.to_string()),
+                    },
+                    Err(e) => self.notifications.push(e.to_string()),
+                }
+            }
+        });
+        // Whole factory layouts can be shared as plans, without any of the progress. Check plan.rs for more info.
+        ui.horizontal(|ui| {
+            if ui
+                .button("Copy factory plan")
+                .on_hover_text(
+                    "Copies every producer and its settings, but not your goods or money.",
+                )
+                .clicked()
+            {
+                ui.output().copied_text = self.game_state.export_plan();
+            }
+            ui.add(
+                egui::TextEdit::singleline(&mut self.plan_draft).hint_text("Import a factory plan"),
+            );
+            ui.checkbox(&mut self.plan_free, "Free").on_hover_text(
+                "Adds the producers without paying for them, for trying out designs.",
+            );
+            if ui
+                .add_enabled(
+                    !self.plan_draft.trim().is_empty(),
+                    egui::Button::new("Import"),
+                )
+                .clicked()
+            {
+                self.push_undo();
+                match self
+                    .game_state
+                    .import_plan(&self.plan_draft, self.plan_free)
+                {
+                    Ok(added) => {
+                        self.notifications
+                            .push(format!("Added {added} producers from the plan"));
+                        self.plan_draft.clear();
+                    }
+                    Err(e) => self.notifications.push(e.to_string()),
+                }
+            }
+        });
+        // Auto-build keeps buying producers until there are as many as the target. Check build.rs for more info.
+        let mut paused = self.game_state.auto_build_paused();
+        if ui
+            .checkbox(&mut paused, "Pause auto-build")
+            .on_hover_text("Stops auto-build from buying anything. The targets are kept.")
+            .changed()
+        {
+            self.game_state.set_auto_build_paused(paused);
+        }
+        // The build menu can be filtered by category, and searched by name. The filters aren't saved.
+        ui.horizontal_wrapped(|ui| {
+            ui.selectable_value(&mut self.build_category, None, "All");
+            for category in ProducerCategory::iter() {
+                ui.selectable_value(
+                    &mut self.build_category,
+                    Some(category),
+                    category.to_string(),
+                );
+            }
+            ui.add(egui::TextEdit::singleline(&mut self.build_search).hint_text("Search"));
+        });
+        let search = self.build_search.trim().to_lowercase();
+        let shown: Vec<Producer> = Producer::buildable()
+            .into_iter()
+            .filter(|producer| {
+                self.build_category
+                    .map_or(true, |category| producer.properties().category == category)
+            })
+            .filter(|producer| producer.to_string().to_lowercase().contains(&search))
+            .collect();
+        if shown.is_empty() {
+            ui.label("No producers match.");
+        }
+        // Holding shift or ctrl builds, and steps the auto-build targets, by more than one at a time. Check bulk_count.
+        let bulk = bulk_count(ui);
+        egui::Grid::new("build_grid")
+            .striped(self.settings.striped)
+            .show(ui, |grid_ui| {
+                for producer in shown {
+                    grid_ui.label(producer.to_string());
+                    // Costs are rounded up for display, and money is rounded down, so something never looks affordable when it isn't.
+                    let cost = producer.properties().cost;
+                    grid_ui.label(format!("${}", cost.ceil()));
+                    grid_ui.label(format!("{}s", build::construction_time(&producer)));
+                    let text = if bulk > 1 {
+                        format!("Build ×{bulk}")
+                    } else {
+                        String::from("Build")
+                    };
+                    // Grayed out if there isn't money for even one. Bulk builds buy as many as there's money for.
+                    if grid_ui
+                        .add_enabled(self.game_state.can_afford(producer), egui::Button::new(text))
+                        .on_hover_text(bulk_hint("Builds", bulk))
+                        .on_disabled_hover_text(format!(
+                            "Costs ${}, but you only have ${}",
+                            cost.ceil(),
+                            self.game_state.money().floor()
+                        ))
+                        .clicked()
+                    {
+                        match self.game_state.try_buy_many(producer, bulk) {
+                            Ok(bought) if bought < bulk => self
+                                .notifications
+                                .push(format!("Could only afford {bought} of {bulk}")),
+                            Ok(_) => {}
+                            Err(e) => self.notifications.push(e.to_string()),
+                        }
+                    }
+                    let mut target = self.game_state.auto_build_target(producer);
+                    grid_ui.horizontal(|ui| {
+                        let hint = bulk_hint("Changes the target", bulk);
+                        if ui.small_button("-").on_hover_text(&hint).clicked() {
+                            target = target.saturating_sub(bulk);
+                        }
+                        ui.add(egui::DragValue::new(&mut target).prefix("Auto-build to "))
+                            .on_hover_text("Buys one every few seconds, whenever there's money for it, until you own this many. 0 is off.");
+                        if ui.small_button("+").on_hover_text(&hint).clicked() {
+                            target = target.saturating_add(bulk);
+                        }
+                    });
+                    if target != self.game_state.auto_build_target(producer) {
+                        self.game_state.set_auto_build_target(producer, target);
+                    }
+                    grid_ui.end_row();
+                }
+            });
+        if self.game_state.build_queue.is_empty() {
+            return;
+        }
+        ui.label(RichText::new("Build queue").strong());
+        let mut cancelled = None;
+        egui::Grid::new("build_queue_grid").show(ui, |grid_ui| {
+            for (index, order) in self.game_state.build_queue.iter().enumerate() {
+                grid_ui.label(order.producer.to_string());
+                let progress = order.progress().to_f32().unwrap_or(0.0);
+                // Only the front of the queue is being built, so the rest just say they're waiting.
+                let text = if index == 0 {
+                    format!("{:.0}%", progress * 100.0)
+                } else {
+                    String::from("Waiting")
+                };
+                grid_ui.add(egui::ProgressBar::new(progress).text(text));
+                if grid_ui.button("Cancel").clicked() {
+                    cancelled = Some(index);
+                }
+                grid_ui.end_row();
+            }
+        });
+        if let Some(index) = cancelled {
+            self.game_state.cancel_build(index);
+        }
+    }
+
+    fn display_storage_section(&mut self, ui: &mut Ui) {
+        // Renders the storage upgrades. Every upgrade doubles the capacity of a whole group, and costs three times the last one.
+        ui.collapsing(RichText::new("Storage").strong(), |ui| {
+            egui::Grid::new("storage_grid")
+                .striped(self.settings.striped)
+                .show(ui, |grid_ui| {
+                    for group in GoodGroup::iter() {
+                        let Some(cost) = self.game_state.storage_upgrade_cost(group) else {
+                            continue;
+                        };
+                        grid_ui.label(group.to_string());
+                        grid_ui.label(format!("Level {}", self.game_state.storage_level(group)));
+                        if grid_ui
+                            .button(format!("Upgrade (${cost})"))
+                            .on_hover_text("Doubles the capacity of every good in the group.")
+                            .clicked()
+                        {
+                            if let Err(e) = self.game_state.upgrade_storage(group) {
+                                self.notifications.push(e.to_string());
+                            }
+                        }
+                        grid_ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn display_market_section(&mut self, ui: &mut Ui) {
+        // Renders the market, where goods are bought and sold by hand, a chosen amount at a time. The inventory's sell buttons sell all of a good instead.
+        // Money can't be bought or sold, and neither can goods without a price. Check GameState::sell and GameState::buy for more info.
+        // Selling more than the player has is greyed out, rather than quietly selling less than was asked for. Buying more than they can afford is too.
+        let mut to_sell = None;
+        let mut to_buy = None;
+        ui.collapsing(RichText::new("Market").strong(), |ui| {
+            egui::Grid::new("market_grid")
+                .striped(self.settings.striped)
+                .show(ui, |grid_ui| {
+                    for good in Good::sorted() {
+                        let price = self.game_state.sell_price(good);
+                        if good == Good::Money || price <= F::from(I::from(0)) {
+                            continue;
+                        }
+                        let owned = &self.game_state.inventory[&good];
+                        let amount = self
+                            .sell_amounts
+                            .entry(good)
+                            .or_insert_with(|| F::from(I::from(1)));
+                        grid_ui.label(good_text(good));
+                        let buy_price = self.game_state.buy_price(good);
+                        // Rounded the way selling and buying round, so the prices shown are the ones actually paid.
+                        grid_ui.label(format!(
+                            "Sells for ${}, buys for ${}",
+                            format_si(&round_money(&price)),
+                            format_si(&lib::round_money_up(&buy_price))
+                        ));
+                        grid_ui.add(
+                            NumericEdit::new(
+                                &format!("market_amount_{good:?}"),
+                                amount,
+                                F::from(I::from(1)),
+                            )
+                            .min(F::from(I::from(0))),
+                        );
+                        let amount = amount.floor();
+                        let can_sell = amount > F::from(I::from(0)) && &amount <= owned;
+                        if grid_ui
+                            .add_enabled(can_sell, egui::Button::new("Sell"))
+                            .on_hover_text(format!(
+                                "Sells {amount} for ${}, after tax",
+                                lib::round_money(&(&amount * &price))
+                            ))
+                            .on_disabled_hover_text(if &amount > owned {
+                                format!("You only have {} {good}", owned.floor())
+                            } else {
+                                "Pick how many to sell".to_string()
+                            })
+                            .clicked()
+                        {
+                            to_sell = Some((good, amount.clone()));
+                        }
+                        let cost = lib::round_money_up(&(&amount * &buy_price));
+                        let can_buy =
+                            amount > F::from(I::from(0)) && cost <= self.game_state.money();
+                        if grid_ui
+                            .add_enabled(can_buy, egui::Button::new("Buy"))
+                            .on_hover_text(format!("Buys {amount} for ${cost}"))
+                            .on_disabled_hover_text(if amount > F::from(I::from(0)) {
+                                format!("Costs ${cost}, which is more than you have")
+                            } else {
+                                "Pick how many to buy".to_string()
+                            })
+                            .clicked()
+                        {
+                            to_buy = Some((good, amount));
+                        }
+                        grid_ui.end_row();
+                    }
+                });
+        });
+        if let Some((good, amount)) = to_sell {
+            let earned = self.game_state.sell(good, &amount);
+            self.notifications
+                .push(format!("Sold {amount} {good} for ${}", earned.floor()));
+        }
+        if let Some((good, amount)) = to_buy {
+            match self.game_state.buy(good, &amount) {
+                Ok(cost) => self
+                    .notifications
+                    .push(format!("Bought {amount} {good} for ${}", cost.ceil())),
+                Err(e) => self.notifications.push(e.to_string()),
+            }
+        }
+    }
+
+    fn display_auto_sell_section(&mut self, ui: &mut Ui) {
+        // Renders the auto-sell rules for every good that can be sold.
+        // The keep amount is typed exactly, so fractional thresholds work too. Check widgets.rs for more info.
+        ui.collapsing(RichText::new("Auto-sell").strong(), |ui| {
+            egui::Grid::new("auto_sell_grid")
+                .striped(self.settings.striped)
+                .show(ui, |grid_ui| {
+                    for good in Good::sorted() {
+                        if good == Good::Money
+                            || self.game_state.sell_price(good) <= F::from(I::from(0))
+                        {
+                            continue;
+                        }
+                        let mut rule = self.game_state.auto_sell_rule(good);
+                        grid_ui.label(good_text(good));
+                        grid_ui.checkbox(&mut rule.enabled, "Auto-sell");
+                        let keep_id = format!("auto_sell_keep_{good:?}");
+                        grid_ui.add(
+                            NumericEdit::new(&keep_id, &mut rule.keep, F::from(I::from(10)))
+                                .min(F::from(I::from(0)))
+                                .prefix("Keep: "),
+                        );
+                        egui::ComboBox::from_id_source(format!("auto_sell_priority_{good:?}"))
+                            .selected_text(rule.priority.to_string())
+                            .show_ui(grid_ui, |ui| {
+                                for priority in SellPriority::iter() {
+                                    ui.selectable_value(
+                                        &mut rule.priority,
+                                        priority,
+                                        priority.to_string(),
+                                    );
+                                }
+                            });
+                        if rule != self.game_state.auto_sell_rule(good) {
+                            self.game_state.set_auto_sell_rule(good, rule);
+                        }
+                        grid_ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn display_inventory_grid(&mut self, ui: &mut Ui) {
+        // Renders the inventory grid. Displays the goods list, the amount of each good, and the theoretical production of each good.
+        // The inventory is a BTreeMap, which iterates in the order the goods are declared. That's grouped by group already, so it doesn't need sorting.
+        // Pinned goods are pulled out and shown first, above a separator, in the same order. Check GameState::inventory_order.
+        // Goods are tinted with their group's color. Check goods::Inventory for more info.
+        let production_table = self.game_state.production_table_theoretical();
+        // The effective rates go next to the theoretical ones too, so stalled producers stand out.
+        let effective_table = self.effective_table.clone();
+        // The sustainable rates go next to the theoretical ones, so it's clear which goods only keep up while their stockpiles last.
+        let sustainable = self.game_state.production_table_sustainable();
+        let now = ui.input().time;
+        let palette = self.settings.palette();
+        let unit = self.settings.rate_unit;
+        let abbreviate = self.settings.abbreviate_amounts;
+        // The measured rates are updated every frame, even while they're not shown, so they're already settled when they're turned on.
+        self.rate_smoother.update(&self.game_state.inventory, now);
+        // The footer is a bottom panel inside the inventory panel, so it stays stuck to the bottom no matter how many goods there are.
+        // It has to be added before the grid, since panels take their space out of whatever is left in the ui.
+        egui::TopBottomPanel::bottom("inventory_footer").show_inside(ui, |ui| {
+            self.display_inventory_footer(ui, &production_table, sustainable.settled);
+        });
+        let flashes = &mut self.inventory_flashes;
+        let mut to_sell = None;
+        let mut to_open = None;
+        let mut to_pin = None;
+        let order = self.game_state.inventory_order();
+        let pinned_count = self.game_state.pinned.len();
+        // The grid scrolls, so the footer stays visible even when there are more goods than fit in the panel.
+        egui::ScrollArea::vertical()
+            .id_source("inventory_scroll")
+            .show(ui, |ui| {
+                ui.with_layout(egui::Layout::left_to_right(Align::Min), |ui| {
+                    egui::Grid::new("inventory_grid")
+                        .striped(self.settings.striped)
+                        .show(ui, |grid_ui| {
+                            // The header, mostly so the two net rates can be told apart.
+                            for header in [
+                                "Good",
+                                "Amount",
+                                "Made",
+                                "Used",
+                                "Net",
+                                "Effective",
+                                "Sustainable",
+                                "",
+                            ] {
+                                grid_ui.label(RichText::new(header).strong());
+                            }
+                            grid_ui.end_row();
+                            for (index, good) in order.iter().enumerate() {
+                                // The pinned goods are split off from the rest with a line, as long as there are any.
+                                if index == pinned_count && pinned_count > 0 {
+                                    grid_ui.separator();
+                                    grid_ui.end_row();
+                                }
+                                let amount = &self.game_state.inventory[good];
+                                // Clicking a good's name opens its detail window, where its alarms are set up.
+                                grid_ui.horizontal(|ui| {
+                                    // The star pins the good to the top, or unpins it.
+                                    let pinned = self.game_state.is_pinned(*good);
+                                    let (star, hover) = if pinned {
+                                        ("★", "Unpin")
+                                    } else {
+                                        ("☆", "Pin to the top")
+                                    };
+                                    if ui
+                                        .add(egui::Button::new(star).frame(false))
+                                        .on_hover_text(hover)
+                                        .clicked()
+                                    {
+                                        to_pin = Some(*good);
+                                    }
+                                    if ui
+                                        .add(
+                                            egui::Label::new(good_text(*good))
+                                                .sense(egui::Sense::click()),
+                                        )
+                                        .on_hover_text("Click for details")
+                                        .clicked()
+                                    {
+                                        to_open = Some(*good);
+                                    }
+                                    // Goods with an alarm going off get marked, so the alarm is noticed even after the notification fades.
+                                    if let Some(level) = self
+                                        .game_state
+                                        .alarms
+                                        .get(good)
+                                        .and_then(|alarm| alarm.active)
+                                    {
+                                        ui.label(RichText::new("!").strong().color(palette.bad))
+                                            .on_hover_text(format!(
+                                                "{good} is past its {level} alarm"
+                                            ));
+                                    }
+                                });
+                                grid_ui.with_layout(
+                                    egui::Layout::right_to_left(Align::Min),
+                                    |ui| {
+                                        // Goods with a capacity show it next to the amount, so it's clear why a producer stalled.
+                                        let displayed = amount.floor().to_integer();
+                                        // Big amounts can be shortened, like 1.23M. Check lib::format_good_amount for more info.
+                                        let format_amount = |value: &F| {
+                                            if abbreviate {
+                                                format_good_amount(value)
+                                            } else {
+                                                value.floor().to_integer().to_string()
+                                            }
+                                        };
+                                        let shown = format_amount(amount);
+                                        // They also say how long until they fill up, when they're going up. Check time_to_reach for more info.
+                                        let text = match self.game_state.capacity(*good) {
+                                            Some(capacity) => match self
+                                                .game_state
+                                                .time_to_reach(*good, &capacity)
+                                            {
+                                                Some(seconds) if seconds > F::from(I::from(0)) => {
+                                                    format!(
+                                                        "{shown} / {} (full in {})",
+                                                        format_amount(&capacity),
+                                                        format_seconds(&seconds)
+                                                    )
+                                                }
+                                                _ => format!(
+                                                    "{shown} / {}",
+                                                    format_amount(&capacity)
+                                                ),
+                                            },
+                                            None => shown,
+                                        };
+                                        let mut text = RichText::new(text);
+                                        // The amount lights up for a moment whenever it changes. Check flash.rs for more info.
+                                        if let Some(color) =
+                                            flashes.update(*good, &displayed, now, &palette)
+                                        {
+                                            text = text.background_color(color);
+                                        }
+                                        // The amount is floored for display, so hovering shows exactly how much there is.
+                                        ui.label(text).on_hover_ui(|ui| {
+                                            ui.label(format!("Exactly {amount}"));
+                                            ui.label(format_exact(amount, EXACT_PLACES));
+                                        });
+                                    },
+                                );
+                                let alt = &(F::from(I::from(0)), F::from(I::from(0)));
+                                let (output, input) = production_table.get(good).unwrap_or(alt);
+                                grid_ui.with_layout(
+                                    egui::Layout::right_to_left(Align::Min),
+                                    |ui| {
+                                        ui.label(RichText::new(unit.format(output)));
+                                    },
+                                );
+                                grid_ui.with_layout(
+                                    egui::Layout::right_to_left(Align::Min),
+                                    |ui| {
+                                        ui.label(RichText::new(unit.format(&-input)));
+                                    },
+                                );
+                                grid_ui.with_layout(
+                                    egui::Layout::right_to_left(Align::Min),
+                                    |ui| {
+                                        // The net rate is either the theoretical one, or the measured one smoothed out. Check rates.rs for more info.
+                                        if self.settings.smooth_rates {
+                                            ui.label(
+                                                unit.format_approx(self.rate_smoother.rate(*good)),
+                                            )
+                                            .on_hover_text(format!(
+                                                "Measured. Theoretically {}",
+                                                unit.format(&(output - input))
+                                            ));
+                                        } else {
+                                            ui.label(RichText::new(unit.format(&(output - input))));
+                                        }
+                                    },
+                                );
+                                // The effective net rate, counting only as much of each producer as is running this tick.
+                                // It's red when it's below the theoretical one, since that means something making or using the good is stalled.
+                                let (made, used) = effective_table.get(good).unwrap_or(alt);
+                                let effective = made - used;
+                                grid_ui.with_layout(
+                                    egui::Layout::right_to_left(Align::Min),
+                                    |ui| {
+                                        let mut text = RichText::new(unit.format(&effective));
+                                        if effective < output - input {
+                                            text = text.color(palette.bad);
+                                        }
+                                        ui.label(text).on_hover_text(format!(
+                                            "Running right now. Makes {} and uses {}",
+                                            unit.format(made),
+                                            unit.format(used)
+                                        ));
+                                    },
+                                );
+                                // The sustainable net rate, once every producer slows down to what its inputs can keep up with. Check solver.rs for more info.
+                                let (made, used) = sustainable.table.get(good).unwrap_or(alt);
+                                grid_ui.with_layout(
+                                    egui::Layout::right_to_left(Align::Min),
+                                    |ui| {
+                                        ui.label(RichText::new(unit.format(&(made - used))))
+                                            .on_hover_text(format!(
+                                                "Once the stockpiles run out. Makes {} and uses {}",
+                                                unit.format(made),
+                                                unit.format(used)
+                                            ));
+                                    },
+                                );
+                                // Goods with a price can be sold from here. The tooltip shows what each one sells for after tax.
+                                let price = self.game_state.sell_price(*good);
+                                if *good != Good::Money && price > F::from(I::from(0)) {
+                                    if grid_ui
+                                        .button("Sell")
+                                        .on_hover_text(format!(
+                                            "Sells every whole {good} for ${price} each, after tax"
+                                        ))
+                                        .clicked()
+                                    {
+                                        to_sell = Some(*good);
+                                    }
+                                } else {
+                                    grid_ui.label("");
+                                }
+                                grid_ui.end_row();
+                            }
+                        });
+                });
+            });
+        if let Some(good) = to_open {
+            self.toggle_good_window(good);
+        }
+        if let Some(good) = to_pin {
+            self.game_state.toggle_pin(good);
+        }
+        // Selling changes the inventory, so it has to wait until the grid is done reading it.
+        if let Some(good) = to_sell {
+            let amount = self.game_state.inventory[&good].clone();
+            let earned = self.game_state.sell(good, &amount);
+            self.notifications
+                .push(format!("Sold {good} for ${}", earned.floor()));
+        }
+    }
 
-// Default implementation for GameState. Used for deserialization, and for resetting the game.
-impl Default for GameState {
-    fn default() -> Self {
-        Self {
-            inventory: {
-                // Creates a HashMap with an empty inventory. Note that it starts with all goods as 0, instead of having a blank HashMap.
-                // This is so that the player can see all the goods in the game, even if they don't have any.
-                // It's also more useful to fill the inventory's keys now, rather than at the render step.
-                // Why? I dunno, superstition I guess.
-                let mut map = HashMap::new();
-                for good in Good::iter() {
-                    map.insert(good, F::new(I::from(0), I::from(1)));
-                }
-                map
-            },
-            ore_minigames: {
-                // Fills the hashmap with all the ore minigames, depending on the ore type's difficulty.
-                let mut map = HashMap::new();
-                for good in Good::group_iter(GoodGroup::Ore) {
-                    map.insert(good, ores::OreMinigame::new(good.properties().difficulty));
+    // Renders the production ticker, on the right of the top bar. It's a glance at how the economy's doing, without opening anything.
+    // It shows money's net rate, and the fastest growing good if the settings say to, both smoothed. Check rates.rs for more info.
+    fn display_ticker(&self, ui: &mut Ui) {
+        let unit = self.settings.rate_unit;
+        let zero = F::from(I::from(0));
+        let palette = self.settings.palette();
+        let rate_text = |rate: &F| {
+            let sign = if rate > &zero { "+" } else { "" };
+            format!("{sign}{}{}", format_si(&unit.scale(rate)), unit.suffix())
+        };
+        ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+            let money = self.ticker.money();
+            let color = if money > zero {
+                palette.good
+            } else if money < zero {
+                palette.bad
+            } else {
+                ui.visuals().text_color()
+            };
+            ui.colored_label(color, format!("${}", rate_text(&money)))
+                .on_hover_text("How fast money is being made, from the production table");
+            if self.settings.ticker_fastest {
+                // This is right to left, so the rate goes in before the good's name.
+                if let Some((good, rate)) = self.ticker.fastest() {
+                    ui.separator();
+                    ui.label(rate_text(&rate));
+                    ui.label(good_text(good))
+                        .on_hover_text("The fastest growing good");
                 }
-                map
-            },
-            // There are no default elements, so it's just an empty HashMap.
-            // We could fill the hashmap with "blanks" here, but it's not necessary.
-            elements: HashMap::new(),
-        }
+            }
+        });
     }
-}
 
-impl GameState {
-    // Updates the game state by a single tick.
-    fn tick(&mut self, tick_rate: &F) {
-        // This for loop iterates over all the elements in the game, and updates the ones which are producers.
-        // This could probably be done in a more functional way, or abstracted into a function, but I'm lazy.
-        // However, this appears more than once, so I should probably abstract it at some point.
-        for (_id, element) in self.elements.iter_mut() {
-            if let ElemVariant::Producer(producer) = element.variant {
-                // Each producer's production is calculated by multiplying the production rate by the tick rate.
-                // This allows the production rate to be in units of "per second" for easier reading and balancing.
-                producer.tick(&mut self.inventory, tick_rate);
+    // Where a window that was just opened goes. It's next to the cursor, since that's where the button that opened it was,
+    // or in the middle of the screen if the cursor isn't over the game. Either way, it's kept inside the space the panels leave free.
+    fn new_window_pos(ctx: &egui::Context) -> egui::Pos2 {
+        let area = ctx.available_rect();
+        let pos = match ctx.input().pointer.hover_pos() {
+            Some(cursor) => cursor + NEW_WINDOW_OFFSET,
+            None => area.center() - NEW_WINDOW_SIZE / 2.0,
+        };
+        // The window's real size isn't known until it's rendered, so it's kept a rough window's size away from the far edges.
+        // If the free space is smaller than that, the window just goes in the top left of it.
+        let max = (area.max - NEW_WINDOW_SIZE).max(area.min);
+        pos.clamp(area.min, max)
+    }
+
+    // Opens or closes a good's detail window. The window is made the first time it's opened, and kept around after that.
+    fn toggle_good_window(&mut self, good: Good) {
+        let existing = self
+            .game_state
+            .elements
+            .values_mut()
+            .find(|element| matches!(element.variant, ElemVariant::Good(g) if g == good));
+        match existing {
+            Some(element) => element.toggle_window(),
+            None => {
+                self.game_state
+                    .add_element(ElemVariant::Good(good), &good.to_string(), true);
             }
         }
     }
 
-    fn production_table_theoretical(&self) -> HashMap<Good, (F, F)> {
-        // This function calculates the theoretical production of all the goods in the game.
-        // This is done by gathering all the inputs and outputs of all the producers in the game, and adding them together.
-        // Eventually, producers will interact with stockpiles rather than the inventory directly, so this will eventually be scrapped.
-        // It's good for now though.
-        let mut hashmap = HashMap::new();
-        for (_id, element) in self.elements.iter() {
-            if let ElemVariant::Producer(producer) = element.variant {
-                // Get the properties of the producer, which contains the inputs and outputs.
-                let properties = producer.properties();
-                // Iterate over the inputs and outputs, and add them to the hashmap.
-                for (good, amount) in properties.outputs.iter() {
-                    hashmap
-                        .entry(*good)
-                        .or_insert((F::from(I::from(0)), F::from(I::from(0))))
-                        .0 += amount;
+    // Renders the alarm controls for a good, in its detail window. Check alarms.rs for more info.
+    fn display_alarm_controls(ui: &mut Ui, good: Good, alarm: &mut GoodAlarm, palette: &Palette) {
+        ui.separator();
+        ui.label(RichText::new("Alarms").strong());
+        egui::Grid::new(format!("alarm_grid_{good:?}")).show(ui, |ui| {
+            let watermarks = [
+                (
+                    "Low",
+                    &mut alarm.alarm_low,
+                    "Goes off when there's less than this.",
+                ),
+                (
+                    "High",
+                    &mut alarm.alarm_high,
+                    "Goes off when there's more than this.",
+                ),
+            ];
+            for (name, watermark, hover) in watermarks {
+                let mut set = watermark.is_some();
+                if ui.checkbox(&mut set, name).on_hover_text(hover).changed() {
+                    *watermark = set.then(|| F::from(I::from(0)));
                 }
-                for (good, amount) in properties.inputs.iter() {
-                    hashmap
-                        .entry(*good)
-                        .or_insert((F::from(I::from(0)), F::from(I::from(0))))
-                        .1 += amount;
+                match watermark {
+                    Some(amount) => {
+                        ui.add(
+                            NumericEdit::new(
+                                &format!("alarm_{name}_{good:?}"),
+                                amount,
+                                F::from(I::from(10)),
+                            )
+                            .min(F::from(I::from(0))),
+                        );
+                    }
+                    None => {
+                        ui.label("");
+                    }
                 }
+                ui.end_row();
+            }
+        });
+        ui.checkbox(&mut alarm.pause_producers, "Pause producers")
+            .on_hover_text("A low alarm pauses the producers that use this good. A high alarm pauses the ones that make it.");
+        match alarm.active {
+            Some(level) => ui.label(
+                RichText::new(format!("Going off: {good} is past its {level} alarm"))
+                    .color(palette.bad),
+            ),
+            None => ui.label("Quiet"),
+        };
+    }
+
+    // Renders the production target controls for a good, in its detail window. Check targets.rs for more info.
+    fn display_production_target_controls(
+        ui: &mut Ui,
+        good: Good,
+        target: &mut Option<F>,
+        paused: usize,
+    ) {
+        ui.separator();
+        ui.label(RichText::new("Production target").strong());
+        ui.horizontal(|ui| {
+            let mut set = target.is_some();
+            if ui
+                .checkbox(&mut set, "Hold at")
+                .on_hover_text("Pauses this good's least important producers while there's enough, and turns them back on when it drops.")
+                .changed()
+            {
+                *target = set.then(|| F::from(I::from(100)));
             }
+            if let Some(amount) = target {
+                ui.add(
+                    NumericEdit::new(&format!("production_target_{good:?}"), amount, F::from(I::from(100)))
+                        .min(F::from(I::from(0))),
+                );
+            }
+        });
+        if target.is_some() {
+            ui.label(format!("{paused} producers paused by the target"));
         }
-        hashmap
-        // This function is very unoptimal because of how it's called. It's called every frame, and recalculates the entire hashmap.
-        // It only needs to be called when a producer is added or removed, or when a producer's inputs/outputs are changed.
-        // Even then, we don't have to recalculate the entire hashmap, just the contributions of the changed producer.
-        // I don't know if this is even worth fixing. Again, I plan on scrapping this function once it becomes obsolete.
     }
-}
 
-// Enum for the radio buttons that determine which section of the game the player is viewing.
-#[derive(serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Copy, EnumIter)]
-enum Selection {
-    Summary,
-    Metallurgy,
-}
+    // Renders the "time to reach" estimate for a good, in its detail window. The player types a target, and gets how long it'll take.
+    // The estimate is from before the target was last changed, so it catches up a frame later.
+    fn display_eta_controls(
+        ui: &mut Ui,
+        good: Good,
+        targets: &mut BTreeMap<Good, F>,
+        etas: &BTreeMap<Good, Option<F>>,
+    ) {
+        ui.separator();
+        ui.label(RichText::new("Planning").strong());
+        ui.horizontal(|ui| {
+            let target = targets.entry(good).or_insert_with(|| F::from(I::from(100)));
+            ui.label("Time to reach");
+            ui.add(
+                NumericEdit::new(
+                    &format!("eta_target_{good:?}"),
+                    target,
+                    F::from(I::from(100)),
+                )
+                .min(F::from(I::from(0))),
+            );
+            match etas.get(&good) {
+                Some(Some(seconds)) => ui.label(format_seconds(seconds)),
+                Some(None) => ui.label("Never, it isn't going up"),
+                None => ui.label(""),
+            };
+        });
+    }
 
-impl Default for Selection {
-    fn default() -> Self {
-        Self::Summary
+    // Renders the "build the whole chain" controls for a good, in its detail window. Returns whether the Plan button was clicked.
+    // The plan is shown for confirming before anything is bought. Check display_chain_confirmation.
+    fn display_chain_controls(ui: &mut Ui, good: Good, rates: &mut BTreeMap<Good, F>) -> bool {
+        let mut clicked = false;
+        ui.horizontal(|ui| {
+            let rate = rates.entry(good).or_insert_with(|| F::from(I::from(1)));
+            ui.label("Build a chain making");
+            ui.add(
+                NumericEdit::new(&format!("chain_rate_{good:?}"), rate, F::from(I::from(1)))
+                    .min(F::from(I::from(0))),
+            );
+            ui.label("per second");
+            clicked = ui
+                .add_enabled(*rate > F::from(I::from(0)), egui::Button::new("Plan"))
+                .on_hover_text(
+                    "Works out every producer it takes, and shows the cost before buying anything",
+                )
+                .clicked();
+        });
+        clicked
     }
-}
 
-impl Display for Selection {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Summary => write!(f, "Summary"),
-            Self::Metallurgy => write!(f, "Metallurgy"),
+    // Renders the recipes that make a good, in its detail window, with buttons to make them by hand. Check crafting.rs for how crafting works.
+    // Inputs there isn't enough of are shown in the palette's bad color. Returns the recipe and how many times to make it, if a button was clicked.
+    fn display_craft_controls(
+        ui: &mut Ui,
+        good: Good,
+        inventory: &Inventory,
+        storage: &StorageLevels,
+        palette: &Palette,
+    ) -> Option<(Producer, u64)> {
+        let recipes = crafting::recipes_for(good);
+        if recipes.is_empty() {
+            return None;
+        }
+        let mut clicked = None;
+        ui.separator();
+        ui.label(RichText::new("Make from").strong());
+        for producer in recipes {
+            let properties = producer.properties();
+            let missing = crafting::missing_inputs(producer, inventory);
+            let max = crafting::max_crafts(producer, inventory, storage);
+            ui.horizontal_wrapped(|ui| {
+                ui.label(format!("{}:", properties.name));
+                for (index, (input, amount)) in properties.recipe.inputs.iter().enumerate() {
+                    if index > 0 {
+                        ui.label("+");
+                    }
+                    let text = RichText::new(format!("{} {input}", format_good_amount(amount)));
+                    ui.label(if missing.contains(input) {
+                        text.color(palette.bad)
+                    } else {
+                        text
+                    });
+                }
+                let outputs: Vec<String> = properties
+                    .recipe
+                    .outputs
+                    .iter()
+                    .map(|(output, amount)| format!("{} {output}", format_good_amount(amount)))
+                    .collect();
+                ui.label(format!("makes {}", outputs.join(" + ")));
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(max > 0, egui::Button::new("Make one"))
+                    .clicked()
+                {
+                    clicked = Some((producer, 1));
+                }
+                if ui
+                    .add_enabled(max > 0, egui::Button::new(format!("Make max ({max})")))
+                    .clicked()
+                {
+                    clicked = Some((producer, max));
+                }
+                // Everything's there, but the outputs are full, so nothing can be made until there's room.
+                if max == 0 && missing.is_empty() {
+                    ui.label(RichText::new("No room for what it makes").color(palette.bad));
+                }
+            });
         }
+        clicked
     }
-}
 
-// The main game struct. Contains all the data that needs to be saved. Also contains the game state.
-// The additional parameters are for the egui integration, and for calculating the time between frames.
-#[derive(serde::Serialize, serde::Deserialize)]
-#[serde(default)]
-pub struct IdleGame {
-    prev_time: chrono::DateTime<chrono::Utc>, // The timestamp of the previous frame. Used for calculating the time between frames.
-    game_timer: F, // Holds the amount of "ticks" that have passed. Used for keeping the tick rate consistent, even if the framerate is inconsistent.
-    // This is done by ticking the game until game_timer is less than 1, and then rendering the game.
-    // Of course there is a limit in order to avoid a lag spiral.
-    game_state: GameState, // Stores the state of the game.
-    producer_index_marked_for_deletion: Option<usize>, // Hacky way of deleting producers. See line 288 for more info.
-    selection: Selection, // The current selection of the radio buttons. Used to determine which section of the game the player is viewing (currently only Summary and Metallurgy).
-    debug_amt_slider: I, // The amount of the selected good that is added to the inventory when the debug button is pressed.
-}
+    // Shows the planned chain, with what it costs, and buys it once it's confirmed. Check GameState::build_chain for what's bought
+    // when there isn't enough money for all of it.
+    fn display_chain_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(plan) = &self.pending_chain else {
+            return;
+        };
+        let palette = self.settings.palette();
+        let money = self.game_state.money();
+        let cost = plan.cost();
+        let unit = self.settings.rate_unit;
+        // Money here is shown rounded to cents and shortened, the same as everywhere else, so big chains don't print a huge fraction.
+        let money_text = |amount: &F| format!("${}", format_si(&round_money(amount)));
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new(format!("Chain for {} {}", unit.format(&plan.rate), plan.target))
+            .id(egui::Id::new("chain_confirmation"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                egui::Grid::new("chain_grid").striped(true).show(ui, |ui| {
+                    for (producer, count) in plan.producers.iter() {
+                        ui.label(producer.to_string());
+                        ui.label(format!("×{count}"));
+                        ui.label(money_text(&(producer.properties().cost * F::from(I::from(*count)))));
+                        ui.end_row();
+                    }
+                });
+                ui.label(format!("{} producers, costing {} in all.", plan.count(), money_text(&cost)));
+                for good in plan.unmet.iter() {
+                    ui.colored_label(
+                        palette.bad,
+                        format!("Nothing you can build makes {good}, so the chain will be short of it."),
+                    );
+                }
+                if cost > money {
+                    ui.colored_label(
+                        palette.partial,
+                        format!(
+                            "You only have {}, so only part of the chain will be built, in the same proportions.",
+                            money_text(&money)
+                        ),
+                    );
+                }
+                ui.horizontal(|ui| {
+                    confirmed = ui
+                        .add_enabled(!plan.producers.is_empty(), egui::Button::new("Build"))
+                        .clicked();
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            });
+        if confirmed {
+            if let Some(plan) = self.pending_chain.take() {
+                // Buying a whole chain is a lot of clicks' worth at once, so it can be undone.
+                self.push_undo();
+                let bought = self.game_state.build_chain(&plan);
+                self.notifications.push(format!(
+                    "Bought {bought} of {} producers for the {} chain",
+                    plan.count(),
+                    plan.target
+                ));
+            }
+        }
+        if cancelled || !open {
+            self.pending_chain = None;
+        }
+    }
 
-// Default implementation for IdleGame. Used for deserialization, and for resetting the game.
-impl Default for IdleGame {
-    fn default() -> Self {
-        Self {
-            prev_time: chrono::Utc::now(),
-            game_timer: F::new(I::from(0), I::from(1)),
-            game_state: GameState::default(),
-            producer_index_marked_for_deletion: None,
-            selection: Selection::default(),
-            debug_amt_slider: I::from(100),
+    fn display_inventory_footer(
+        &self,
+        ui: &mut Ui,
+        production_table: &HashMap<Good, (F, F)>,
+        settled: bool,
+    ) {
+        // Renders a summary of the inventory grid, as a quick health check of the economy.
+        // Counts how many goods are growing and shrinking, using the same production table as the grid so the two always agree.
+        let zero = F::from(I::from(0));
+        let (mut growing, mut shrinking) = (0, 0);
+        for (output, input) in production_table.values() {
+            let net = output - input;
+            if net > zero {
+                growing += 1;
+            } else if net < zero {
+                shrinking += 1;
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label(format!("Growing: {growing}"));
+            ui.separator();
+            ui.label(format!("Shrinking: {shrinking}"));
+            ui.separator();
+            ui.label(format!(
+                "Net worth: {:.0}",
+                self.game_state.net_worth().floor()
+            ));
+        });
+        // Cycles can keep the solver from settling, so the sustainable rates are only close. Check solver.rs for more info.
+        if !settled {
+            ui.colored_label(
+                self.settings.palette().bad,
+                "Some producers feed each other in a loop, so the sustainable rates are approximate.",
+            );
         }
     }
 }
 
-impl IdleGame {
-    // Retrieves the saved game from the local storage, or creates a new game if there is no saved game.
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        if let Some(storage) = cc.storage {
-            let mut game: Self = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-            // Normally, this game can calculate offline progress, but it's disabled for now thanks to this line.
-            game.prev_time = chrono::Utc::now();
-            // This is for three reasons:
-            // 1. The game is currently in development, and I don't want a sudden flurry of progress to happen while the game is closed and I'm changing the code.
-            // 2. While this is an idle game, it's not really an idle game. It's closer to Factorio, and some of the mechanics will require the player to be active.
-            // 3. The large amount of calculations that need to be done to calculate offline progress is very slow, and can easily create a lag spiral.
-            // Idle Spiral and Exponential Idle solved the offline progress problem, so maybe I'll see how they did it.
-            return game;
-        }
+// How many at a time the build and delete buttons work on, with shift or ctrl held. Check bulk_count.
+const BULK_SHIFT: usize = 10;
+const BULK_CTRL: usize = 100;
 
-        Default::default()
+// How many at a time the build and delete buttons (and the auto-build steppers) work on right now, going by the held modifiers.
+// Shift is 10 and ctrl is 100, like most factory games. Ctrl wins if both are held. On macs, it's cmd instead of ctrl.
+fn bulk_count(ui: &Ui) -> usize {
+    let modifiers = ui.input().modifiers;
+    if modifiers.command {
+        BULK_CTRL
+    } else if modifiers.shift {
+        BULK_SHIFT
+    } else {
+        1
     }
+}
 
-    fn display_inventory_grid(&self, ui: &mut Ui) {
-        // Renders the inventory grid. Displays the goods list, the amount of each good, and the theoretical production of each good.
-        // The inventory is currently stored in a hashmap, which is fine, but is inconsistently sorted.
-        // As such, I need to sort the inventory before displaying it.
-        // Not preferable, but so long as the amount of goods is small, it's fine.
-        let mut sorted_inventory = self.game_state.inventory.iter().collect::<Vec<_>>();
-        sorted_inventory.sort_by(|a, b| a.0.cmp(b.0));
-        let production_table = self.game_state.production_table_theoretical();
-        ui.with_layout(egui::Layout::left_to_right(Align::Min), |ui| {
-            egui::Grid::new("inventory_grid")
-                .striped(true)
-                .show(ui, |grid_ui| {
-                    for (good, amount) in sorted_inventory {
-                        grid_ui.label(good.to_string());
-                        grid_ui.with_layout(egui::Layout::right_to_left(Align::Min), |ui| {
-                            ui.label(RichText::new(format!("{:.0}", amount.floor())));
-                        });
-                        let alt = &(F::from(I::from(0)), F::from(I::from(0)));
-                        let (output, input) = production_table.get(good).unwrap_or(alt);
-                        grid_ui.with_layout(egui::Layout::right_to_left(Align::Min), |ui| {
-                            ui.label(RichText::new(format!("{output}/s")));
-                        });
-                        grid_ui.with_layout(egui::Layout::right_to_left(Align::Min), |ui| {
-                            ui.label(RichText::new(format!("{}/s", -input)));
-                        });
-                        grid_ui.with_layout(egui::Layout::right_to_left(Align::Min), |ui| {
-                            ui.label(RichText::new(format!("{}/s", output - input)));
-                        });
-                        grid_ui.end_row();
-                    }
-                });
-        });
-    }
+// The tooltip for a button that works in bulk, saying how many it does right now and how to change that.
+fn bulk_hint(verb: &str, count: usize) -> String {
+    format!("{verb} {count} at a time. Hold shift for {BULK_SHIFT}, or ctrl for {BULK_CTRL}.")
+}
+
+// How many decimal places the exact amounts on the inventory's hover popups go to.
+const EXACT_PLACES: usize = 20;
+
+// How many undo snapshots are kept at most.
+const UNDO_LIMIT: usize = 20;
+
+// Roughly how big a freshly opened element window is, and how far from the cursor it opens. Check new_window_pos.
+const NEW_WINDOW_SIZE: egui::Vec2 = egui::vec2(300.0, 200.0);
+const NEW_WINDOW_OFFSET: egui::Vec2 = egui::vec2(16.0, 16.0);
+
+// How wide the window has to be, in points, for the side panels to be shown as side panels. Narrower than this, they become tabs.
+// Two side panels take up around 500 points between them, so this leaves the tabs at least 300.
+const NARROW_LAYOUT_WIDTH: f32 = 800.0;
+
+// How much real time the tick loop is allowed to take per frame, in milliseconds. About half a frame at 60 fps.
+// This doesn't scale with the game speed. Ticks past the budget aren't lost, they stay in the game timer and are caught up on later,
+// so a high speed on a big factory just falls behind for a bit (and eventually gets caught up coarsely) instead of making less.
+const TICK_BUDGET_MS: i64 = 8;
+
+// The game speeds the top panel offers. Check IdleGame::speed_multiplier.
+const GAME_SPEEDS: [u32; 4] = [1, 2, 5, 10];
+
+// How often the game state is normalized, in seconds of real time. Check GameState::normalize.
+const NORMALIZE_INTERVAL_SECONDS: i64 = 30;
+
+// How long a tick is, in seconds. This is currently set to 20 ticks per second.
+fn tick_rate() -> F {
+    F::new(I::from(1), I::from(20))
 }
 
 // Debug constant for testing and fun. Will be set to false eventually.
@@ -219,11 +3324,9 @@ impl eframe::App for IdleGame {
     // 1. Updates the game state.
     // 2. Renders the game state.
     // Update is called every frame. Updating the game state is dependent on the time between frames, but rendering the game state is not.
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Determines how fast the game should tick. This is currently set to 20 ticks per second.
-        let tick_rate = F::new(I::from(1), I::from(20));
-        // This is the limit on how many ticks can be done per frame. This is to prevent a lag spiral.
-        let tick_limit = 100;
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // Determines how fast the game should tick. Check tick_rate for more info.
+        let tick_rate = tick_rate();
         // Gets the current timestamp.
         let now = chrono::Utc::now();
 
@@ -231,85 +3334,297 @@ impl eframe::App for IdleGame {
         let time_passed = now - self.prev_time;
         let millis_passed = time_passed.num_milliseconds();
         let seconds_passed = F::new(I::from(millis_passed), I::from(1000));
-        self.game_timer += seconds_passed;
+        // While paused, the time between frames is just dropped, so unpausing doesn't dump it all on the game at once.
+        // Time added to the game timer some other way (like the debug button) waits there until the game is unpaused.
+        // The game speed scales the time before it's added, so the ticks themselves stay the same length.
+        if !self.paused {
+            self.game_timer += &seconds_passed * &self.speed_multiplier;
+        }
         // Updates the previous time to the current time.
         // This is done here to keep the time between frames consistent, and not dependent on the amount of time it takes to update the game state or render the game.
+        // It's updated while paused too, for the same reason the time is dropped.
         self.prev_time = now;
-        // Updates the game state, with a limit on how many ticks can be done per frame.
-        let mut ticks = 0;
-        while self.game_timer >= tick_rate.clone() && ticks < tick_limit {
-            self.game_state.tick(&tick_rate);
-            self.game_timer -= tick_rate.clone();
-            ticks += 1;
+        if !self.paused {
+            // A huge backlog would take ages to tick through in budgeted frames, so it's caught up in coarse steps all at once.
+            // Check advance_coarse for how much accuracy that gives up.
+            if self.game_timer > coarse_backlog_threshold() {
+                let backlog = format_seconds(&self.game_timer);
+                self.game_timer = self.game_state.advance_coarse(&self.game_timer, &tick_rate);
+                self.notifications
+                    .push(format!("Caught up on {backlog} of game time"));
+            }
+            // Updates the game state, with a limit on how long the ticks can take per frame. This is to prevent a lag spiral.
+            // Whatever time doesn't get simulated (past the budget, or less than a whole tick) stays in the game timer for the next frame.
+            let budget = chrono::Duration::milliseconds(TICK_BUDGET_MS);
+            self.game_timer = self.game_state.advance_budgeted(
+                &self.game_timer,
+                &tick_rate,
+                budget,
+                &SystemClock,
+            );
+            // The offline progress preview keeps running alongside the live game until the player picks one of them.
+            // It's paused, sped up and budgeted along with the live game, so the time spent deciding counts the same in both.
+            if let Some(preview) = &mut self.offline_preview {
+                preview.advance(
+                    &(&seconds_passed * &self.speed_multiplier),
+                    &tick_rate,
+                    budget,
+                    &SystemClock,
+                );
+            }
+            // Every so often, big amounts are rounded so their denominators don't grow without bound. Check GameState::normalize.
+            self.normalize_timer += &seconds_passed;
+            if self.normalize_timer >= F::from(I::from(NORMALIZE_INTERVAL_SECONDS)) {
+                self.normalize_timer = F::from(I::from(0));
+                self.game_state.normalize();
+                if let Some(preview) = &mut self.offline_preview {
+                    preview.state.normalize();
+                }
+            }
+        }
+        // Tells the player about anything that happened during the ticks.
+        for event in self.game_state.drain_events() {
+            self.notifications.push(event.message());
+        }
+        // The theoretical production table is cached by the game state. The effective one changes every tick, so it's worked out once here instead.
+        self.effective_table = self.game_state.production_table_effective(&tick_rate);
+        self.ticker.update(
+            &self.game_state.production_table_theoretical(),
+            ctx.input().time,
+        );
+        // Runs whichever actions had their keys pressed. Check keybinds.rs for more info.
+        for action in self.settings.keybinds.pressed(ctx) {
+            match action {
+                Action::Save => {
+                    if let Some(storage) = frame.storage_mut() {
+                        eframe::App::save(self, storage);
+                        storage.flush();
+                        self.notifications.push("Saved the game");
+                    }
+                }
+                Action::Undo => self.undo(),
+                Action::NextTab => {
+                    let tabs: Vec<Selection> = Selection::iter().collect();
+                    let current = tabs
+                        .iter()
+                        .position(|tab| *tab == self.ui_state.selection)
+                        .unwrap_or(0);
+                    self.ui_state.selection = tabs[(current + 1) % tabs.len()];
+                }
+                Action::Mine => self.ui_state.selection = Selection::Metallurgy,
+                // The undo point is only kept if something was actually bought, so a failed buy doesn't leave an undo that does nothing.
+                // Cues can be silent, so a failure always says why in a notification too.
+                Action::QuickBuy => match self.game_state.cheapest_affordable() {
+                    Some(producer) => {
+                        let before = self.game_state.clone();
+                        match self.game_state.try_buy_producer(producer) {
+                            Ok(()) => {
+                                self.push_undo_state(before);
+                                self.notifications.push(format!("Bought {producer}"));
+                            }
+                            Err(e) => {
+                                self.sounds.play(Cue::Denied, &self.settings.sound);
+                                self.notifications.push(e.to_string());
+                            }
+                        }
+                    }
+                    None => {
+                        self.sounds.play(Cue::Denied, &self.settings.sound);
+                        self.notifications
+                            .push("There's no producer you can afford to quick-buy");
+                    }
+                },
+            }
         }
 
         // Render the top panel, with reset and quit (if non-browser) buttons.
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 if ui.button("Reset").clicked() {
-                    self.game_state = GameState::default();
+                    self.replace_game_state(GameState::default());
+                }
+                if ui
+                    .add_enabled(!self.undo_stack.is_empty(), egui::Button::new("Undo"))
+                    .clicked()
+                {
+                    self.undo();
+                }
+                // Stops time, without stopping the game from being played. Check the top of update for how the time is handled.
+                let (text, hover) = if self.paused {
+                    ("Resume", "Starts time again, from where it stopped")
+                } else {
+                    (
+                        "Pause",
+                        "Stops time. Nothing is made or used until it's resumed",
+                    )
+                };
+                if ui.button(text).on_hover_text(hover).clicked() {
+                    self.paused = !self.paused;
+                }
+                // Speeds the game up. Check speed_multiplier for more info.
+                for speed in GAME_SPEEDS {
+                    let speed = F::from(I::from(speed));
+                    let text = format!("{speed}×");
+                    ui.selectable_value(&mut self.speed_multiplier, speed, text);
                 }
+                // Backing up and restoring the save. On the web, this is the only way to get the save out of the browser.
+                ui.menu_button("Save", |ui| {
+                    if self.display_copy_backup(ui) {
+                        ui.close_menu();
+                    }
+                    if ui.button("Import a save…").clicked() {
+                        self.import_window = true;
+                        ui.close_menu();
+                    }
+                });
                 #[cfg(not(target_arch = "wasm32"))] // no Quit on web pages!
                 if ui.button("Quit").clicked() {
-                    _frame.close();
+                    frame.close();
                 }
+                self.display_ticker(ui);
             });
         });
 
-        // Renders the left inventory panel. Should be replaced with columns and put into the center panel.
-        egui::SidePanel::left("inventory_panel").show(ctx, |ui| {
-            ui.heading("Inventory");
-            self.display_inventory_grid(ui);
-        });
+        // On narrow screens (like phones), the side panels would leave no room for the tabs, so they become tabs of their own instead.
+        // Check NARROW_LAYOUT_WIDTH for the cutoff.
+        let narrow = ctx.available_rect().width() < NARROW_LAYOUT_WIDTH;
+        if !narrow {
+            // Renders the left inventory panel. Should be replaced with columns and put into the center panel.
+            egui::SidePanel::left("inventory_panel").show(ctx, |ui| {
+                self.display_panel(ui, SidePanelTab::Inventory, &tick_rate);
+            });
 
-        // Renders the right production panel. Should be replaced with columns and put into the center panel.
-        egui::SidePanel::right("producers_panel").show(ctx, |ui| {
-            ui.heading("Producers");
-            egui::Grid::new("producers_grid")
-                .striped(true)
-                .show(ui, |grid_ui| {
-                    for (id, element) in self.game_state.elements.iter_mut() {
-                        let Element {
-                            variant, is_open, ..
-                        } = element;
-                        if let ElemVariant::Producer(producer) = variant {
-                            // Renders the producer row for each producer.
-                            // Renders the producer name, and a button to open the producer's window.
-                            if grid_ui.button(producer.to_string()).clicked() {
-                                *is_open = !*is_open;
-                            }
-                            // Renders a button to delete the producer.
-                            if grid_ui.button("X").clicked() {
-                                self.producer_index_marked_for_deletion = Some(*id);
-                            }
-                            grid_ui.end_row();
-                        }
-                    }
-                });
-        });
+            // Renders the right production panel. Should be replaced with columns and put into the center panel.
+            egui::SidePanel::right("producers_panel").show(ctx, |ui| {
+                self.display_panel(ui, SidePanelTab::Producers, &tick_rate);
+            });
+        }
 
         // Hacky way to delete producers. This is because I can't figure out how to delete elements from a hashmap while mutably iterating over it.
         // Not to mention it's probably a bad idea to delete elements while iterating over them.
         // Who knows if it's even a hack at all? Either way, it feels wrong.
-        if let Some(i) = self.producer_index_marked_for_deletion {
-            self.game_state.elements.remove(&i);
+        if let Some((i, count)) = self.producer_index_marked_for_deletion {
+            // Anything sitting in the producer's buffers goes back into the inventory, rather than vanishing with the producer.
+            // With shift or ctrl held, more producers like it go too. Check bulk_count.
+            let removed = self.game_state.remove_similar(i, count);
+            if removed > 1 {
+                self.notifications
+                    .push(format!("Deleted {removed} producers"));
+            }
             self.producer_index_marked_for_deletion = None;
         }
 
         // Renders each element's window.
+        let regions = self.game_state.regions();
+        let palette = self.settings.palette();
+        let mut alarm_changes = Vec::new();
+        let mut target_changes = Vec::new();
+        let mut to_plan = None;
+        let mut to_craft = None;
+        // Estimating needs the whole game state, which is borrowed by the loop, so the estimates are worked out beforehand.
+        let etas: BTreeMap<Good, Option<F>> = self
+            .eta_targets
+            .iter()
+            .map(|(good, target)| (*good, self.game_state.time_to_reach(*good, target)))
+            .collect();
+        // Whether any window changed a producer in a way that changes the production table. Check GameState::invalidate_production_table.
+        let mut table_changed = false;
         for (_window_index, element) in self.game_state.elements.iter_mut() {
-            // We need to destruct the element to get mutable access to all of its fields. This is to avoid mutably borrowing the element twice in two different places.
-            let Element {
-                variant,
-                window_id,
-                is_open,
-            } = element;
+            // egui needs a mutable reference to is_open, while the window's contents need to read the rest of the element.
+            // Copying is_open out and writing it back afterwards avoids borrowing the element twice in two different places.
+            let mut is_open = element.is_open;
             // If is_open is false, the window will not be rendered. This is intended behavior from egui which simplifies the code.
-            egui::Window::new(window_id.clone())
-                .open(is_open)
-                .show(ctx, |ui| {
-                    variant.window_render(ui);
-                });
+            let efficiency = element.efficiency(
+                &self.game_state.inventory,
+                &tick_rate,
+                &self.game_state.sim_settings,
+                &self.game_state.storage_level,
+            );
+            // The title changes as the element does, so the window is identified by its window_id instead.
+            let window_id = egui::Id::new(&element.window_id);
+            let mut window = egui::Window::new(element.title(&self.game_state.inventory))
+                .id(window_id)
+                .open(&mut is_open);
+            // Windows go back where they were last shown, so they stay put across restarts. This only applies when egui doesn't remember the window itself.
+            if let Some([x, y]) = element.window_pos {
+                window = window.default_pos(egui::pos2(x, y));
+            }
+            if let Some([width, height]) = element.window_size {
+                window = window.default_size(egui::vec2(width, height));
+            }
+            // Windows that were just opened are moved into view and brought to the front, once. After that they stay wherever they're dragged.
+            if std::mem::take(&mut element.just_opened) {
+                window = window
+                    .current_pos(Self::new_window_pos(ctx))
+                    .constrain(true);
+                ctx.move_to_top(egui::LayerId::new(egui::Order::Middle, window_id));
+            }
+            let shown = window.show(ctx, |ui| {
+                // The size of the contents, rather than of the whole window, since that's what default_size sets.
+                let size = ui.max_rect().size();
+                table_changed |=
+                    element.window_render(ui, &efficiency, &regions, self.settings.rate_unit);
+                // Goods' windows also set up the good's alarms. They're stored in the game state, so changes are applied after the loop.
+                if let ElemVariant::Good(good) = element.variant {
+                    let old = self
+                        .game_state
+                        .alarms
+                        .get(&good)
+                        .cloned()
+                        .unwrap_or_default();
+                    let mut alarm = old.clone();
+                    Self::display_alarm_controls(ui, good, &mut alarm, &palette);
+                    if alarm != old {
+                        alarm_changes.push((good, alarm));
+                    }
+                    let old_target = self.game_state.production_targets.get(&good);
+                    let mut target = old_target.map(|target| target.target.clone());
+                    let paused = old_target.map_or(0, |target| target.paused.len());
+                    Self::display_production_target_controls(ui, good, &mut target, paused);
+                    if target != old_target.map(|target| target.target.clone()) {
+                        target_changes.push((good, target));
+                    }
+                    Self::display_eta_controls(ui, good, &mut self.eta_targets, &etas);
+                    if Self::display_chain_controls(ui, good, &mut self.chain_rates) {
+                        to_plan = Some(good);
+                    }
+                    if let Some(craft) = Self::display_craft_controls(
+                        ui,
+                        good,
+                        &self.game_state.inventory,
+                        &self.game_state.storage_level,
+                        &palette,
+                    ) {
+                        to_craft = Some(craft);
+                    }
+                }
+                size
+            });
+            // Remembers where the window is, for next time. Collapsed windows have no contents, so they keep the size they had.
+            if let Some(shown) = shown {
+                let pos = shown.response.rect.min;
+                element.window_pos = Some([pos.x, pos.y]);
+                if let Some(size) = shown.inner {
+                    element.window_size = Some([size.x, size.y]);
+                }
+            }
+            element.is_open = is_open;
+        }
+        if table_changed {
+            self.game_state.invalidate_production_table();
+        }
+        if let Some(good) = to_plan {
+            let rate = self.chain_rates.get(&good).cloned().unwrap_or_default();
+            self.pending_chain = Some(ChainPlan::new(good, &rate));
+        }
+        for (good, alarm) in alarm_changes {
+            self.game_state.set_alarm(good, alarm);
+        }
+        if let Some((producer, count)) = to_craft {
+            self.game_state.craft(producer, count);
+        }
+        for (good, target) in target_changes {
+            self.game_state.set_production_target(good, target);
         }
 
         // Renders the center panel. This is where the game will be played.
@@ -317,114 +3632,413 @@ impl eframe::App for IdleGame {
             // I'll need something to replicate a header bar. Top panel doesn't work as it's not a widget.
             // Guess I can mess around with styles to make it look like a header bar.
             ui.horizontal_top(|ui| {
+                // In the narrow layout, the side panels are tabs too. Picking one of the usual tabs goes back to it.
+                let side_panel = self.ui_state.side_panel.filter(|_| narrow);
                 for section in Selection::iter() {
-                    ui.selectable_value(&mut self.selection, section, section.to_string());
+                    let selected = side_panel.is_none() && self.ui_state.selection == section;
+                    if ui.selectable_label(selected, section.to_string()).clicked() {
+                        self.ui_state.selection = section;
+                        self.ui_state.side_panel = None;
+                    }
+                }
+                if narrow {
+                    ui.separator();
+                    for panel in SidePanelTab::iter() {
+                        if ui
+                            .selectable_label(side_panel == Some(panel), panel.to_string())
+                            .clicked()
+                        {
+                            self.ui_state.side_panel = Some(panel);
+                        }
+                    }
                 }
             });
+            let now = ui.input().time;
+            if self.ui_state.selection != self.previous_selection {
+                self.previous_selection = self.ui_state.selection;
+                self.tab_changed_at = now;
+            }
             ui.add(egui::Separator::default().horizontal().spacing(6.0));
+            if let (true, Some(panel)) = (narrow, self.ui_state.side_panel) {
+                self.display_panel(ui, panel, &tick_rate);
+                return;
+            }
             // Renders the main gameplay area, depending on the current selection.
-            match self.selection {
-                Selection::Summary => {
-                    // Displays a summary of the game state. Currently only displays debug buttons.
-                    ui.heading("Summary");
-                    ui.add(egui::Separator::default().horizontal().spacing(4.0));
-                    // Debug buttons.
-                    if DEBUG {
-                        // Adds a blank element to the game state, and opens its window.
-                        if ui.button("Add blank window").clicked() {
-                            // THIS IS EXTREMELY BAD.
-                            // Currently this is the only way to get unique window ids that don't conflict with other windows.
-                            // This only works because I'm not deleting elements from the hashmap. If I were to do that, this would break.
-                            // The only good solution is to have a global id generator that keeps track of all ids, and makes sure they're unique.
-                            // Or to do the old trick of randomly generating ids until you get one that isn't in use.
-                            // Either would work, but I need to add comments right now, so I'll do that later.
-                            // May god have mercy on my soul for this.
-                            let next_window_id = self.game_state.elements.len();
-                            self.game_state.elements.insert(next_window_id, Element {
-                                variant: ElemVariant::Blank,
-                                window_id: format!("Blank {next_window_id}"),
-                                is_open: true,
+            // Each tab gets its own scroll area, so long tabs can be scrolled on small screens.
+            // The id is the tab's name, so egui remembers each tab's scroll position separately.
+            let tab_output = egui::ScrollArea::vertical()
+                .id_source(self.ui_state.selection.to_string())
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    match self.ui_state.selection {
+                        Selection::Summary => {
+                            // Displays a summary of the game state. Currently only displays debug buttons.
+                            ui.heading("Summary");
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            // A quick look at which goods are in surplus and which are in deficit. Check heatmap.rs for more info.
+                            ui.collapsing(RichText::new("Economy at a glance").strong(), |ui| {
+                                heatmap::display_heatmap(
+                                    ui,
+                                    &self.game_state.production_table_theoretical(),
+                                    &self.settings.palette(),
+                                    self.settings.rate_unit,
+                                );
+                            });
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            self.display_build_section(ui);
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            self.display_market_section(ui);
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            self.display_auto_sell_section(ui);
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            self.display_storage_section(ui);
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            // Debug buttons.
+                            if DEBUG {
+                                // Adds a blank element to the game state, and opens its window.
+                                if ui.button("Add blank window").clicked() {
+                                    self.game_state.add_element(ElemVariant::Blank, "Blank", true);
+                                }
+                                // Resets the game with a fixed seed, so the minigame orders are reproducible.
+                                ui.horizontal(|ui| {
+                                    ui.add(egui::DragValue::new(&mut self.debug_seed).prefix("Seed: "));
+                                    if ui.button("Debug: Reset with seed").clicked() {
+                                        self.replace_game_state(GameState::new(Some(self.debug_seed)));
+                                    }
+                                });
+                                // Renders a control to pick how much to add/remove. Check widgets.rs for how it handles BigInt.
+                                ui.add(
+                                    NumericEdit::new("debug_amount", &mut self.debug_amt_slider, I::from(100))
+                                        .prefix("Debug Amount"),
+                                );
+                                let debug_amt = F::new(self.debug_amt_slider.clone(), I::from(1));
+                                // Renders a button that adds time to the game timer, causing the game to progress very quickly by a certain amount of time.
+                                // While paused, the time waits in the game timer, and is used up once the game is resumed.
+                                if ui.button(format!("Debug: Add {debug_amt} seconds")).clicked() {
+                                    self.game_timer += F::new(self.debug_amt_slider.clone(), I::from(1));
+                                }
+                                // Renders a button that adds a specified amount of dollars to the game state.
+                                if ui.button(format!("Debug: Add {debug_amt} dollars")).clicked() {
+                                    self.game_state.add_good(Good::Money, &debug_amt);
+                                }
+                                // Renders buttons for each ore.
+                                for ore in Good::group_iter(GoodGroup::Ore) {
+                                    // Renders a button that adds a specified amount of the ore to the game state.
+                                    if ui.button(format!("Debug: Add {} {}", debug_amt.clone(), ore)).clicked() {
+                                        self.game_state.add_good(ore, &debug_amt);
+                                    }
+                                    // Renders a button that adds a Gravity Drill for the ore to the game state, for free and without waiting for it to be built.
+                                    // It skips try_buy_producer on purpose, so the label says it's free. The build menu is the way to buy one.
+                                    if ui.button(format!("Debug: Spawn free {ore} gravity drill")).clicked() {
+                                        let producer = Producer::GravityDrill(ore);
+                                        self.game_state.add_element(
+                                            ElemVariant::Producer(producer),
+                                            &producer.to_string(),
+                                            false,
+                                        );
+                                    }
+                                    // Renders a button that adds a Coal Drill for the ore to the game state, for free and without waiting for it to be built.
+                                    if ui.button(format!("Debug: Spawn free {ore} coal drill")).clicked() {
+                                        let producer = Producer::CoalDrill(ore);
+                                        self.game_state.add_element(
+                                            ElemVariant::Producer(producer),
+                                            &producer.to_string(),
+                                            false,
+                                        );
+                                    }
+                                }
+                            }
+                        }
+                        Selection::Metallurgy => {
+                            // Displays the metallurgy tab, which right now are ore minigames for collecting each ore.
+                            ui.heading("Metallurgy");
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            ui.label("To mine a single ore, click the buttons in order from lowest to highest.\nThe order will randomly change every time you mine an ore, or click the buttons in the wrong order.\nThe number keys press the buttons of the marked ore. The up and down arrows pick which ore is marked.");
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            // Only one minigame can take the number keys, or one key would press the same button in all of them.
+                            let ores: Vec<Good> = Good::group_iter(GoodGroup::Ore).collect();
+                            let mut index = self.keyboard_ore.and_then(|ore| ores.iter().position(|o| *o == ore)).unwrap_or(0);
+                            if !ui.ctx().wants_keyboard_input() && !ores.is_empty() {
+                                if ui.input().key_pressed(egui::Key::ArrowDown) {
+                                    index = (index + 1) % ores.len();
+                                }
+                                if ui.input().key_pressed(egui::Key::ArrowUp) {
+                                    index = (index + ores.len() - 1) % ores.len();
+                                }
+                            }
+                            self.keyboard_ore = ores.get(index).copied();
+                            egui::Grid::new("ore_interface").show(ui, |ui| {
+                                for ore in ores {
+                                    // Each ore has its own mini-game, which is rendered here.
+                                    let keyboard = self.keyboard_ore == Some(ore);
+                                    if keyboard {
+                                        ui.label(RichText::new(format!("▶ {ore}")).strong()).on_hover_text("The number keys play this one");
+                                    } else {
+                                        ui.label(format!("{ore}"));
+                                    }
+                                    self.display_minigame_preview(ui, ore);
+                                    // Get the relevant ore mini-game state. If one doesn't exist, create one with the relevant difficulty.
+                                    let rng = &mut self.game_state.rng;
+                                    let om = self.game_state.ore_minigames.entry(ore).or_insert_with(|| ores::OreMinigame::new(ore.properties().difficulty, &self.settings.minigames, rng));
+                                    let mut solved = None;
+                                    ui.with_layout(egui::Layout::left_to_right(Align::Min), |ui| {
+                                        // Renders the buttons for the ore mini-game, and checks if the game has been interacted with.
+                                        let time_limit = self.settings.minigames.time_limit(ore.properties().time_limit_ms);
+                                        let om = om.ui(ui, &self.settings.minigames, keyboard, time_limit);
+                                        // The minigame doesn't know about sounds, so the cues are played from here. Check sound.rs for more info.
+                                        if om.take_mistake() {
+                                            self.sounds.play(Cue::Mistake, &self.settings.sound);
+                                        }
+                                        om.reset_if_failed(&self.settings.minigames, rng).do_if_solved(|om, reward| {
+                                            solved = Some((om.solve_time().unwrap_or(0.0), reward));
+                                            self.sounds.play(Cue::Solved, &self.settings.sound);
+                                        }).reset_if_solved(&self.settings.minigames, rng);
+                                    });
+                                    // The minigame is borrowed from the game state until here, so the reward waits until it's done.
+                                    if let Some((seconds, reward)) = solved {
+                                        self.game_state.record_solve(ore, seconds, &reward);
+                                    }
+                                    ui.end_row();
+                                }
                             });
                         }
-                        // Renders a slider to add/remove resources.
-                        // Rather strange, as egui (probably) doesn't support sliders for BigInt, so I need to convert between BigInt and i64.
-                        // Lets hope this is ultimately unnecessary.
-                        let mut temp = self.debug_amt_slider.to_i64().unwrap();
-                        ui.add(egui::Slider::new(&mut temp, -1000..=1000).text("Debug Amount"));
-                        self.debug_amt_slider = I::from(temp);
-                        let debug_amt = F::new(self.debug_amt_slider.clone(), I::from(1));
-                        // Renders a button that adds time to the game timer, causing the game to progress very quickly by a certain amount of time.
-                        if ui.button(format!("Debug: Add {debug_amt} seconds")).clicked() {
-                            self.game_timer += F::new(self.debug_amt_slider.clone(), I::from(1));
+                        Selection::Graph => {
+                            // Displays the production graph, which shows how goods flow between producers.
+                            ui.heading("Production Graph");
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            graph::display_production_graph(ui, &self.game_state, self.settings.rate_unit);
                         }
-                        // Renders a button that adds a specified amount of dollars to the game state.
-                        if ui.button(format!("Debug: Add {debug_amt} dollars")).clicked() {
-                            self.game_state.inventory.entry(Good::Money)
-                                .and_modify(|x| *x += debug_amt.clone())
-                                .or_insert(debug_amt.clone());
+                        Selection::Achievements => {
+                            // Displays the achievements, and the stats they're worked out from.
+                            ui.heading("Achievements");
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            self.achievement_view.ui(ui, self.game_state.stats());
                         }
-                        // Renders buttons for each ore.
-                        for ore in Good::group_iter(GoodGroup::Ore) {
-                            // Renders a button that adds a specified amount of the ore to the game state.
-                            if ui.button(format!("Debug: Add {} {}", debug_amt.clone(), ore)).clicked() {
-                                self.game_state.inventory.entry(ore)
-                                    .and_modify(|x| *x += debug_amt.clone())
-                                    .or_insert(debug_amt.clone());
+                        Selection::Settings => {
+                            // Displays the player's settings.
+                            ui.heading("Settings");
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            self.settings.ui(ui);
+                            ui.horizontal(|ui| {
+                                if ui.button("Measure save size").clicked() {
+                                    self.save_size_report = Some(self.save_size_report());
+                                }
+                                if let Some(report) = &self.save_size_report {
+                                    ui.label(report);
+                                }
+                            });
+                            self.display_save_transfer(ui);
+                            // Checks what the migrations would do to the stored save, without loading it. Only for testing migrations.
+                            if DEBUG {
+                                if ui.button("Debug: Dry-run migrations on stored save").clicked() {
+                                    self.migration_report = Some(Self::dry_run_stored_save(frame.storage()));
+                                }
+                                if let Some(report) = &self.migration_report {
+                                    ui.collapsing("Migration report", |ui| {
+                                        egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                                            ui.label(report);
+                                        });
+                                    });
+                                }
                             }
-                            // BAD BAD BAD
-                            // For more information, see line 326.
-                            let next_id = self.game_state.elements.len();
-                            // Renders a button that adds a Gravity Drill for the ore to the game state.
-                            if ui.button(format!("Debug: Add {ore} gravity drill")).clicked() {
-                                self.game_state.elements.insert(next_id, Element {
-                                    variant: ElemVariant::Producer(Producer::GravityDrill(ore)),
-                                    window_id: format!("{next_id}: {ore} Gravity Drill"),
-                                    is_open: false,
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            // Simulation settings are part of the game state, so they get reset along with the game.
+                            ui.label(RichText::new("Simulation").strong());
+                            let mut logistics = self.game_state.sim_settings.logistics;
+                            ui.horizontal(|ui| {
+                                ui.label("Logistics");
+                                for mode in LogisticsMode::iter() {
+                                    ui.selectable_value(&mut logistics, mode, mode.to_string())
+                                        .on_hover_text(mode.description());
+                                }
+                            });
+                            self.game_state.set_logistics_mode(logistics);
+                            // Buffers can be refilled at a limited speed, so an emptied buffer takes a while to fill back up.
+                            if logistics == LogisticsMode::Buffered {
+                                ui.horizontal(|ui| {
+                                    let fill_rate = &mut self.game_state.sim_settings.buffer_fill_rate;
+                                    let mut limited = fill_rate.is_some();
+                                    if ui
+                                        .checkbox(&mut limited, "Limit buffer refill speed")
+                                        .on_hover_text("How many seconds of input each buffer can take in per second. Unlimited buffers are topped up every tick.")
+                                        .changed()
+                                    {
+                                        *fill_rate = limited.then(|| F::from(I::from(2)));
+                                    }
+                                    if let Some(rate) = fill_rate {
+                                        ui.add(
+                                            NumericEdit::new("buffer_fill_rate", rate, F::new(I::from(1), I::from(2)))
+                                                .min(F::from(I::from(0))),
+                                        );
+                                    }
                                 });
                             }
-                            // Renders a button that adds a Coal Drill for the ore to the game state.
-                            if ui.button(format!("Debug: Add {ore} coal drill")).clicked() {
-                                self.game_state.elements.insert(next_id, Element {
-                                    variant: ElemVariant::Producer(Producer::CoalDrill(ore)),
-                                    window_id: format!("{next_id}: {ore} Coal Drill"),
-                                    is_open: false,
-                                });
+                            ui.horizontal(|ui| {
+                                ui.label("Starvation alert delay");
+                                let mut debounce = self
+                                    .game_state
+                                    .sim_settings
+                                    .starvation_debounce
+                                    .to_f64()
+                                    .unwrap_or(1.0);
+                                if ui
+                                    .add(egui::Slider::new(&mut debounce, 0.0..=10.0).suffix(" s"))
+                                    .changed()
+                                {
+                                    // The slider works in floats, so round to the nearest tenth of a second to keep the rational small.
+                                    self.game_state.sim_settings.starvation_debounce =
+                                        F::new(I::from((debounce * 10.0).round() as i64), I::from(10));
+                                }
+                            });
+                            ui.checkbox(
+                                &mut self.game_state.sim_settings.notify_recovered,
+                                "Notify when starved producers recover",
+                            );
+                            ui.checkbox(
+                                &mut self.game_state.sim_settings.auto_throttle,
+                                "Throttle producers on scarce inputs",
+                            )
+                            .on_hover_text("When an input is running out, producers using it slow down to match how fast it's made, instead of stalling and restarting.");
+                            let above = self.game_state.sim_settings.normalize_above.to_string();
+                            ui.horizontal(|ui| {
+                                ui.label("Round big amounts to");
+                                ui.add(
+                                    egui::DragValue::new(&mut self.game_state.sim_settings.normalize_places)
+                                        .clamp_range(0..=18)
+                                        .suffix(" decimal places"),
+                                )
+                                .on_hover_text(format!("Amounts over {above} are rounded every so often, so the math doesn't slow down over long sessions."));
+                            });
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            // The economy knobs are part of the simulation settings too, so they get reset along with the game.
+                            ui.label(RichText::new("Economy").strong());
+                            let economy_before = self.game_state.sim_settings.economy.clone();
+                            let economy = &mut self.game_state.sim_settings.economy;
+                            ui.horizontal(|ui| {
+                                ui.label("Sell price multiplier");
+                                let mut multiplier = economy.price_multiplier.to_f64().unwrap_or(1.0);
+                                if ui
+                                    .add(egui::Slider::new(&mut multiplier, 0.0..=5.0).suffix("×"))
+                                    .changed()
+                                {
+                                    // Rounded to the nearest hundredth, to keep the rational small.
+                                    economy.price_multiplier =
+                                        F::new(I::from((multiplier * 100.0).round() as i64), I::from(100));
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Sell tax");
+                                for kind in TaxKind::iter() {
+                                    if ui
+                                        .selectable_value(&mut economy.tax_kind, kind, kind.to_string())
+                                        .changed()
+                                    {
+                                        // The two kinds of tax mean completely different things, so switching starts the tax over.
+                                        economy.tax = F::from(I::from(0));
+                                    }
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                let mut tax = economy.tax.to_f64().unwrap_or(0.0);
+                                let slider = match economy.tax_kind {
+                                    TaxKind::Percentage => {
+                                        tax *= 100.0;
+                                        egui::Slider::new(&mut tax, 0.0..=99.0).suffix("%")
+                                    }
+                                    TaxKind::Flat => egui::Slider::new(&mut tax, 0.0..=10.0).prefix("$"),
+                                };
+                                if ui.add(slider).changed() {
+                                    economy.tax = match economy.tax_kind {
+                                        TaxKind::Percentage => {
+                                            F::new(I::from(tax.round() as i64), I::from(100))
+                                        }
+                                        TaxKind::Flat => {
+                                            F::new(I::from((tax * 100.0).round() as i64), I::from(100))
+                                        }
+                                    };
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Buy price multiplier");
+                                let mut spread = economy.buy_spread.to_f64().unwrap_or(1.5);
+                                if ui
+                                    .add(egui::Slider::new(&mut spread, 1.0..=5.0).suffix("×"))
+                                    .on_hover_text("How many times the base price goods cost to buy from the market.")
+                                    .changed()
+                                {
+                                    // Rounded to the nearest hundredth, to keep the rational small.
+                                    economy.buy_spread =
+                                        F::new(I::from((spread * 100.0).round() as i64), I::from(100));
+                                }
+                            });
+                            // Producers selling their output directly make money at the new prices. Check GameState::set_economy.
+                            if self.game_state.sim_settings.economy != economy_before {
+                                self.game_state.invalidate_production_table();
                             }
+                            ui.add(egui::Separator::default().horizontal().spacing(4.0));
+                            self.display_reset_section(ui);
                         }
                     }
-                }
-                Selection::Metallurgy => {
-                    // Displays the metallurgy tab, which right now are ore minigames for collecting each ore.
-                    ui.heading("Metallurgy");
-                    ui.add(egui::Separator::default().horizontal().spacing(4.0));
-                    ui.label("To mine a single ore, click the buttons in order from lowest to highest.\nThe order will randomly change every time you mine an ore, or click the buttons in the wrong order.");
-                    ui.add(egui::Separator::default().horizontal().spacing(4.0));
-                    egui::Grid::new("ore_interface").show(ui, |ui| {
-                        for ore in Good::group_iter(GoodGroup::Ore) {
-                            // Each ore has its own mini-game, which is rendered here.
-                            ui.label(format!("{ore}"));
-                            // Get the relevant ore mini-game state. If one doesn't exist, create one with the relevant difficulty.
-                            let om = self.game_state.ore_minigames.entry(ore).or_insert(ores::OreMinigame::new(ore.properties().difficulty));
-                            ui.with_layout(egui::Layout::left_to_right(Align::Min), |ui| {
-                                // Renders the buttons for the ore mini-game, and checks if the game has been interacted with.
-                                om.ui(ui).reset_if_failed().do_if_solved(|_| {
-                                    self.game_state.inventory.entry(ore)
-                                        .and_modify(|x| *x += F::from(I::from(1)))
-                                        .or_insert(F::from(I::from(1)));
-                                }).reset_if_solved();
-                            });
-                            ui.end_row();
-                        }
-                    });
-                }
+                });
+            // Fades the new tab in, by covering it with the background color and fading that away.
+            // It's purely visual, and happens after the tab is drawn, so the tab is fully usable while it fades.
+            let transition = self.settings.tab_transition();
+            let elapsed = now - self.tab_changed_at;
+            if transition > 0.0 && elapsed < transition {
+                let cover = 1.0 - (elapsed / transition) as f32;
+                let fill = ui.visuals().panel_fill;
+                ui.painter()
+                    .rect_filled(tab_output.inner_rect, 0.0, fill.linear_multiply(cover));
             }
         });
+        // Renders the offline progress preview, if there is one. Check offline.rs for more info.
+        let choice = self
+            .offline_preview
+            .as_ref()
+            .and_then(|preview| preview.show(ctx, &self.settings.palette()));
+        match choice {
+            Some(OfflineChoice::Accept) => {
+                if let Some(preview) = self.offline_preview.take() {
+                    // Accepting can be undone, in case the player changes their mind.
+                    self.push_undo();
+                    self.replace_game_state(preview.state);
+                    self.game_timer = preview.game_timer;
+                    for event in preview.events {
+                        self.notifications.push(event.message());
+                    }
+                }
+            }
+            Some(OfflineChoice::Discard) => self.offline_preview = None,
+            None => {}
+        }
+        // Shows the chain planned from a good's window, until it's bought or cancelled. Check chain.rs for more info.
+        self.display_chain_confirmation(ctx);
+        // Lets a save be pasted from the Save menu, then asks before a pasted save replaces the current game. Check display_save_transfer.
+        self.display_import_window(ctx);
+        self.display_import_confirmation(ctx);
+        // Shows what's changed since the save's version, until the player closes it. Check changelog.rs for more info.
+        if let Some(version) = self.whats_new {
+            if changelog::display_whats_new(ctx, version) {
+                self.whats_new = None;
+            }
+        }
+        // Renders the notifications on top of everything else.
+        self.notifications.show(ctx);
+
         // Re-render the game state.
         ctx.request_repaint();
     }
 
     // Saves the game on closing.
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
+        if self.settings.compact_save {
+            match save::encode_compact(self) {
+                Ok(save) => storage.set_string(eframe::APP_KEY, save),
+                // Something went wrong with the compact format, so fall back to RON rather than not saving at all.
+                Err(_) => eframe::set_value(storage, eframe::APP_KEY, self),
+            }
+        } else {
+            eframe::set_value(storage, eframe::APP_KEY, self);
+        }
     }
 }
@@ -0,0 +1,57 @@
+/*
+This is the notification code. Notifications are short messages (toasts) that pop up in the corner of the screen and fade away on their own.
+They're used to tell the player about things that happened without them having to go looking, like "Disabled 5 producers".
+Notifications aren't saved, since they're only relevant right when they happen.
+ */
+
+use chrono::{DateTime, Duration, Utc};
+
+// How long a notification stays on screen, in milliseconds.
+const NOTIFICATION_LIFETIME_MS: i64 = 4000;
+// The most notifications that can be on screen at once. Older ones get pushed out first.
+const MAX_NOTIFICATIONS: usize = 5;
+
+// A single notification, and when it was pushed.
+struct Notification {
+    text: String,
+    created: DateTime<Utc>,
+}
+
+// The queue of notifications currently on screen.
+#[derive(Default)]
+pub struct Notifications {
+    queue: Vec<Notification>,
+}
+
+impl Notifications {
+    // Pushes a new notification onto the queue. It'll show up on the next frame.
+    pub fn push(&mut self, text: impl Into<String>) {
+        self.queue.push(Notification {
+            text: text.into(),
+            created: Utc::now(),
+        });
+        if self.queue.len() > MAX_NOTIFICATIONS {
+            self.queue.remove(0);
+        }
+    }
+
+    // Renders the notifications in the bottom right corner of the screen, and removes the ones which have expired.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let now = Utc::now();
+        let lifetime = Duration::milliseconds(NOTIFICATION_LIFETIME_MS);
+        self.queue
+            .retain(|notification| now - notification.created < lifetime);
+        if self.queue.is_empty() {
+            return;
+        }
+        egui::Area::new("notifications")
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-8.0, -8.0))
+            .show(ctx, |ui| {
+                for notification in self.queue.iter() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.label(&notification.text);
+                    });
+                }
+            });
+    }
+}
@@ -0,0 +1,167 @@
+/*
+This is the offline progress code. When the game is opened, the time it spent closed can be simulated, but not applied right away.
+Instead, the result is worked out on a copy of the game state and shown to the player, who can keep it or throw it away.
+Throwing it away is handy while testing, when a sudden flurry of progress from a stale save isn't wanted.
+None of this is saved. If the game is closed with the preview still up, it's as if the player discarded it.
+Only so much offline time is simulated, however long the game was closed. The cap is in the settings, and it's 8 hours by default.
+That keeps a save that's been sitting around for a month from burying the player in goods, or taking forever to catch up.
+The catching up itself is done in big steps rather than tick by tick, which is what stops it from becoming a lag spiral. Check advance_coarse.
+This window is the only place offline progress is shown, so the per-good diff (inventory_diff, and how show formats it) lives here too.
+ */
+
+use crate::idle::events::GameEvent;
+use crate::idle::goods::{Good, Inventory};
+use crate::idle::lib::{format_good_amount, format_seconds};
+use crate::idle::settings::Palette;
+use crate::idle::widgets::good_text;
+use crate::idle::{coarse_backlog_threshold, Clock, GameState};
+use egui::RichText;
+use num::{BigInt, BigRational};
+
+type F = BigRational;
+type I = BigInt;
+
+// What the player picked in the offline progress window.
+pub enum OfflineChoice {
+    Accept,
+    Discard,
+}
+
+// The would-be result of the offline progress, waiting for the player to accept or discard it.
+pub struct OfflinePreview {
+    pub state: GameState,       // The game state with the offline time simulated.
+    pub game_timer: F, // The time left over after simulating, which didn't make a whole tick.
+    pub seconds: F,    // How much offline time was simulated, in seconds. Never more than the cap.
+    pub away: F, // How long the game was actually closed for, in seconds. More than seconds if the cap was hit.
+    pub events: Vec<GameEvent>, // Everything that happened while the game was closed. Told to the player if they accept.
+    before: Inventory,          // The inventory from before the offline time, to compare against.
+}
+
+impl OfflinePreview {
+    // Simulates the time the game was closed for on a copy of the game state, up to the cap in hours. The original isn't touched.
+    pub fn new(state: &GameState, game_timer: &F, away: F, cap_hours: u32, tick_rate: &F) -> Self {
+        let seconds = capped_seconds(&away, cap_hours);
+        let before = state.inventory().clone();
+        let mut state = state.clone();
+        // Being closed for a long time is exactly the huge backlog advance_coarse is for. Check it for how accurate that is.
+        let backlog = game_timer + &seconds;
+        let game_timer = if backlog > coarse_backlog_threshold() {
+            state.advance_coarse(&backlog, tick_rate)
+        } else {
+            state.advance(&backlog, tick_rate)
+        };
+        let events = state.drain_events();
+        Self {
+            state,
+            game_timer,
+            seconds,
+            away,
+            events,
+            before,
+        }
+    }
+
+    // Keeps the preview in step with the live game while the window is up, so accepting doesn't lose the time spent deciding.
+    // The seconds are game time, already scaled by the game speed. It's advanced the same way the live game is every frame:
+    // coarsely if the backlog has grown huge, then tick by tick within the budget, with the rest left in the game timer for next time.
+    pub fn advance(
+        &mut self,
+        seconds: &F,
+        tick_rate: &F,
+        budget: chrono::Duration,
+        clock: &impl Clock,
+    ) {
+        self.game_timer += seconds;
+        if self.game_timer > coarse_backlog_threshold() {
+            self.game_timer = self.state.advance_coarse(&self.game_timer, tick_rate);
+        }
+        self.game_timer = self
+            .state
+            .advance_budgeted(&self.game_timer, tick_rate, budget, clock);
+        self.events.extend(self.state.drain_events());
+    }
+
+    // How much of each good was gained (positive) or spent (negative) while the game was closed, sorted the way the UI shows goods.
+    // Goods that didn't change are left out.
+    pub fn diff(&self) -> Vec<(Good, F)> {
+        inventory_diff(&self.before, self.state.inventory())
+    }
+
+    // Renders the preview as a window in the middle of the screen. Returns what the player picked, if they picked anything this frame.
+    pub fn show(&self, ctx: &egui::Context, palette: &Palette) -> Option<OfflineChoice> {
+        let mut choice = None;
+        egui::Window::new("While you were away")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "The game was closed for {}.",
+                    format_seconds(&self.away)
+                ));
+                if self.away > self.seconds {
+                    ui.label(format!(
+                        "Only {} of it counts. The cap can be changed in the settings.",
+                        format_seconds(&self.seconds)
+                    ));
+                }
+                let diff = self.diff();
+                if diff.is_empty() {
+                    ui.label("Nothing changed.");
+                } else {
+                    egui::Grid::new("offline_diff_grid").show(ui, |ui| {
+                        for (good, change) in diff.iter() {
+                            ui.label(good_text(*good));
+                            let color = if *change > F::from(I::from(0)) {
+                                palette.good
+                            } else {
+                                palette.bad
+                            };
+                            // Same formatting as the inventory, with a plus sign on gains so they stand out from spending.
+                            let sign = if *change > F::from(I::from(0)) {
+                                "+"
+                            } else {
+                                ""
+                            };
+                            ui.label(
+                                RichText::new(format!("{sign}{}", format_good_amount(change)))
+                                    .color(color),
+                            );
+                            ui.end_row();
+                        }
+                    });
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Accept").clicked() {
+                        choice = Some(OfflineChoice::Accept);
+                    }
+                    if ui
+                        .button("Discard")
+                        .on_hover_text("Keeps the game as it was when it was closed.")
+                        .clicked()
+                    {
+                        choice = Some(OfflineChoice::Discard);
+                    }
+                });
+            });
+        choice
+    }
+}
+
+// How much offline time is simulated for the time the game was closed, in seconds. It's capped at the given number of hours.
+pub fn capped_seconds(away: &F, cap_hours: u32) -> F {
+    away.clone()
+        .min(F::from(I::from(cap_hours) * I::from(3600)))
+}
+
+// Compares two inventories, returning how much each good changed from before to after. Unchanged goods are left out.
+pub fn inventory_diff(before: &Inventory, after: &Inventory) -> Vec<(Good, F)> {
+    let zero = F::from(I::from(0));
+    Good::sorted()
+        .into_iter()
+        .filter_map(|good| {
+            let change = after.get(&good).unwrap_or(&zero) - before.get(&good).unwrap_or(&zero);
+            (change != zero).then_some((good, change))
+        })
+        .collect()
+}
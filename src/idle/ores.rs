@@ -1,18 +1,94 @@
+use crate::idle::settings::{FailBehavior, MinigameSettings};
+use num::{BigInt, BigRational};
 use rand::prelude::*;
 
+type F = BigRational;
+type I = BigInt;
+
 // This file used to contain ores, but the ores became abstracted into the goods system.
 // So now, this file contains the ore minigame functionality.
 
-// The ore minigame is a minigame that is used to mine ores. Every time you successfully complete the minigame, you get a single ore.
-// If you click the buttons in the correct order, you win and get some ore.
-// If you click the buttons in the wrong order, you lose and have to start over.
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+// How many times a nearly sorted order is reshuffled before giving up. Check shuffled_order for what happens then.
+const MAX_RESHUFFLES: u32 = 32;
+
+// The most a streak can multiply the reward by. Past this, the streak still counts up, but the reward stays the same.
+// Without it, a long enough streak would out-produce every drill in the game. Check OreMinigame::reward.
+const MAX_STREAK_MULTIPLIER: u32 = 10;
+
+// The number keys, in order, for playing the minigames with the keyboard. Key 1 is button 1, and so on. Check OreMinigame::key_press.
+const NUMBER_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+
+// Counts the inversions in an order, which is how many pairs of buttons are the wrong way around.
+// A sorted order has none, and a reversed one has the most, n * (n - 1) / 2.
+pub fn inversions(order: &[u32]) -> usize {
+    let mut count = 0;
+    for (i, a) in order.iter().enumerate() {
+        count += order[i + 1..].iter().filter(|b| a > b).count();
+    }
+    count
+}
+
+// The fewest inversions a scrambled order of the given difficulty can have. This is a quarter of the most it could have.
+// Tiny difficulties round down to zero, since there's hardly any way to scramble two buttons.
+pub fn min_inversions(difficulty: u32) -> usize {
+    let n = difficulty as usize;
+    n * n.saturating_sub(1) / 8
+}
+
+// Shuffles the buttons of a minigame, from 1 to difficulty.
+// A uniform shuffle sometimes comes out nearly sorted, which makes for a trivial minigame.
+// If scrambled is set, those shuffles are thrown out and reshuffled, until the order has at least min_inversions.
+// The reshuffles are bounded, and if they all come out too sorted, the order is reversed instead.
+// Reversing turns k inversions into the most possible minus k, so it always ends up scrambled enough.
+pub fn shuffled_order(difficulty: u32, scrambled: bool, rng: &mut impl Rng) -> Vec<u32> {
+    let mut order: Vec<u32> = (1..=difficulty).collect();
+    order.shuffle(rng);
+    if !scrambled {
+        return order;
+    }
+    let min = min_inversions(difficulty);
+    for _ in 0..MAX_RESHUFFLES {
+        if inversions(&order) >= min {
+            return order;
+        }
+        order.shuffle(rng);
+    }
+    if inversions(&order) < min {
+        order.reverse();
+    }
+    order
+}
+
+// The ore minigame is a minigame that is used to mine ores. Every time you successfully complete the minigame, you get some ore.
+// If you click the buttons in the correct order, you win and get some ore. Solving it again without failing in between gets more, check reward.
+// If you click the buttons in the wrong order, you lose and have to start over. Or step back, or wait, depending on the settings.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct OreMinigame {
-    order: Vec<u32>, // A list of numbers from 1 to difficulty, in a random order.
-    next: u32,       // The next number the player needs to click.
-    difficulty: u32, // The difficulty of the minigame. This is the number of buttons.
-    failed: bool,    // Whether the player has failed the minigame.
+    order: Vec<u32>,  // A list of numbers from 1 to difficulty, in a random order.
+    next: u32,        // The next number the player needs to click.
+    difficulty: u32,  // The difficulty of the minigame. This is the number of buttons.
+    failed: bool,     // Whether the player has failed the minigame.
+    grace_used: bool, // Whether the one forgiven mistake has been used up this attempt. Only matters with forgiving minigames.
+    streak: u32, // How many solves in a row this one would make, counting itself. Goes back to 1 on any failure. Saved, so reloading doesn't end a streak.
+    #[serde(skip)]
+    mistake: bool, // Whether a wrong button was clicked since the last call to take_mistake. Forgiven clicks count too.
+    #[serde(skip)]
+    last_click: Option<f64>, // When the last accepted click happened, in egui's time. Used to ignore clicks that come too fast.
+    #[serde(skip)]
+    first_click: Option<f64>, // When the first accepted click of this attempt happened. Used to time solves, check solve_time.
+    #[serde(skip)]
+    locked_until: Option<f64>, // When the lockout from the last wrong click ends, with FailBehavior::Lockout. Check is_locked.
 }
 
 // Default implementation for the ore minigame, assuming a difficulty of 5.
@@ -29,30 +105,138 @@ impl Default for OreMinigame {
             next: 1,
             difficulty: 5,
             failed: false,
+            grace_used: false,
+            streak: 1,
+            mistake: false,
+            last_click: None,
+            first_click: None,
+            locked_until: None,
         }
     }
 }
 
 impl OreMinigame {
     // Generates an ore minigame with a given difficulty.
-    pub fn new(difficulty: u32) -> Self {
-        let mut rng = thread_rng();
+    // The rng is passed in so that the game state can decide whether the order is seeded or not.
+    // Scrambled minigames never come out nearly sorted. Check shuffled_order for more info.
+    pub fn new(difficulty: u32, settings: &MinigameSettings, rng: &mut impl Rng) -> Self {
         Self {
-            order: {
-                let mut vec: Vec<u32> = (1..=difficulty).collect();
-                vec.shuffle(&mut rng);
-                vec
-            },
+            order: shuffled_order(difficulty, settings.scrambled, rng),
             next: 1,
             difficulty,
             failed: false,
+            grace_used: false,
+            streak: 1,
+            mistake: false,
+            last_click: None,
+            first_click: None,
+            locked_until: None,
+        }
+    }
+
+    // Handles a click on one of the buttons. The right button moves the minigame along, and the wrong one fails it.
+    // With forgiving minigames, the first wrong click of an attempt only uses up the grace, and nothing else happens.
+    // Clicks that come faster than the click interval are ignored entirely, so mashing (or an autoclicker) gets nowhere.
+    // The time is in seconds, and only needs to go up. The ui passes in egui's time.
+    // Clicks during a lockout are ignored the same way, and don't count as mistakes either.
+    pub fn press(&mut self, value: u32, settings: &MinigameSettings, now: f64) {
+        if self.is_locked(now) {
+            return;
+        }
+        if let Some(last_click) = self.last_click {
+            if now - last_click < settings.click_interval(self.difficulty) {
+                return;
+            }
+        }
+        self.last_click = Some(now);
+        self.first_click.get_or_insert(now);
+        if value == self.next {
+            // If the button was the next button to be clicked, increment the next button to be clicked.
+            self.next += 1;
+        } else if settings.forgiving && !self.grace_used {
+            self.grace_used = true;
+            self.mistake = true;
+        } else {
+            // Otherwise, fail the minigame.
+            self.failed = true;
+            self.mistake = true;
+        }
+    }
+
+    // Handles a number key being pressed, as if the button with that number was clicked. Check press for what happens then.
+    // Keys for buttons that are already done, or that the minigame doesn't have, are ignored, the same as their buttons can't be clicked.
+    // Returns whether the key went to a button.
+    pub fn key_press(&mut self, key: egui::Key, settings: &MinigameSettings, now: f64) -> bool {
+        let Some(index) = NUMBER_KEYS.iter().position(|k| *k == key) else {
+            return false;
+        };
+        let value = index as u32 + 1;
+        if value < self.next || value > self.difficulty {
+            return false;
+        }
+        self.press(value, settings, now);
+        true
+    }
+
+    // Fails the minigame if it's timed, and the time ran out. The time starts at the first accepted click of the attempt.
+    // It fails the same way a wrong click does, so reset_if_failed deals with it however the settings say to.
+    // The timer is stopped as well, so it starts again on the next click instead of running out again straight away, after a setback or a lockout.
+    // The time limit is in seconds. None means the minigame isn't timed. Check MinigameSettings::time_limit.
+    pub fn check_time(&mut self, time_limit: Option<f64>, now: f64) {
+        let (Some(limit), Some(first_click)) = (time_limit, self.first_click) else {
+            return;
+        };
+        if !self.is_solved() && !self.failed && now - first_click > limit {
+            self.failed = true;
+            self.mistake = true;
+            self.first_click = None;
         }
     }
 
+    // How many seconds are left to finish, if the minigame is timed and the time has started. Check check_time.
+    pub fn time_left(&self, time_limit: Option<f64>, now: f64) -> Option<f64> {
+        let (Some(limit), Some(first_click)) = (time_limit, self.first_click) else {
+            return None;
+        };
+        Some((limit - (now - first_click)).max(0.0))
+    }
+
+    // Whether the buttons are locked out after a wrong click, at the given time. Only happens with FailBehavior::Lockout.
+    pub fn is_locked(&self, now: f64) -> bool {
+        self.locked_until.map_or(false, |until| now < until)
+    }
+
+    // The next number the player needs to click.
+    pub fn next(&self) -> u32 {
+        self.next
+    }
+
+    // The order the buttons are shown in.
+    pub fn order(&self) -> &[u32] {
+        &self.order
+    }
+
+    // Whether a wrong button was clicked since this was last called. Used by the UI to play the mistake sound.
+    pub fn take_mistake(&mut self) -> bool {
+        std::mem::take(&mut self.mistake)
+    }
+
     // Renders the buttons for the ore minigame.
-    pub fn ui(&mut self, ui: &mut egui::Ui) -> &mut Self {
+    // With keyboard set, the number keys press the buttons too. Only one minigame should have it at a time, or one key would press them all.
+    // Timed minigames are given their time limit, in seconds, and fail when it runs out. Check check_time for more info.
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        settings: &MinigameSettings,
+        keyboard: bool,
+        time_limit: Option<f64>,
+    ) -> &mut Self {
+        let mut pressed = None;
+        self.check_time(time_limit, ui.input().time);
+        let time_left = self.time_left(time_limit, ui.input().time);
+        let locked = self.is_locked(ui.input().time);
         ui.horizontal(|ui| {
-            for (_i, value) in self.order.iter().enumerate() {
+            for value in self.order.iter() {
                 ui.scope(|ui| {
                     // Render each individual button, depending on its value.
                     if value == &self.next {
@@ -64,22 +248,54 @@ impl OreMinigame {
                         ui.visuals_mut().widgets.active.bg_fill =
                             egui::Color32::from_rgb(73, 102, 59);
                     }
-                    // Render a button as inactive if the player has already clicked it.
-                    let button =
-                        ui.add_enabled(value >= &self.next, egui::Button::new(format!("{value}")));
-                    // Depending on if the button was the next button to be clicked, either increment the next button to be clicked or fail the minigame.
+                    // Render a button as inactive if the player has already clicked it, or if they're locked out.
+                    let button = ui.add_enabled(
+                        value >= &self.next && !locked,
+                        egui::Button::new(format!("{value}")),
+                    );
+                    // The click is handled after the loop, since press needs to change the minigame while it's being iterated over.
                     if button.clicked() {
-                        if value == &self.next {
-                            // If the button was the next button to be clicked, increment the next button to be clicked.
-                            self.next += 1;
-                        } else {
-                            // Otherwise, fail the minigame.
-                            self.failed = true;
-                        }
+                        pressed = Some(*value);
                     }
                 });
             }
+            // Lets the player know they've used up their forgiven mistake.
+            if settings.forgiving && self.grace_used {
+                ui.label("Grace used");
+            }
+            if locked {
+                ui.label("Locked out");
+            }
+            if let Some(seconds) = time_left {
+                ui.label(format!("{seconds:.1}s left"));
+            }
+            if self.streak > 1 {
+                ui.label(format!("Streak x{}", self.reward()))
+                    .on_hover_text(format!(
+                        "{} solves in a row. Failing starts it over.",
+                        self.streak - 1
+                    ));
+            }
         });
+        // The lockout and the timer run out on their own, so the ui has to keep redrawing to notice.
+        if locked || time_left.is_some() {
+            ui.ctx().request_repaint();
+        }
+        let now = ui.input().time;
+        if let Some(value) = pressed {
+            self.press(value, settings, now);
+        } else if keyboard && !ui.ctx().wants_keyboard_input() {
+            // Typing into a text box shouldn't play the minigame. At most one key counts per frame, like one click.
+            let keys: Vec<egui::Key> = NUMBER_KEYS
+                .into_iter()
+                .filter(|key| ui.input().key_pressed(*key))
+                .collect();
+            for key in keys {
+                if self.key_press(key, settings, now) {
+                    break;
+                }
+            }
+        }
         self
     }
 
@@ -89,37 +305,89 @@ impl OreMinigame {
     }
 
     // Resets the ore minigame with the same difficulty.
-    pub fn reset(&mut self) -> &mut Self {
-        *self = Self::new(self.difficulty);
+    // The last click carries over, so the click interval can't be skipped by solving (or failing) the minigame.
+    // The streak carries over too. Whoever calls this decides what happens to it, check reset_if_failed and reset_if_solved.
+    pub fn reset(&mut self, settings: &MinigameSettings, rng: &mut impl Rng) -> &mut Self {
+        let last_click = self.last_click;
+        let streak = self.streak;
+        *self = Self::new(self.difficulty, settings, rng);
+        self.last_click = last_click;
+        self.streak = streak;
         self
     }
 
+    // How many solves in a row the next one would make, counting itself. 1 means there's no streak going.
+    pub fn streak(&self) -> u32 {
+        self.streak
+    }
+
+    // How much ore solving the minigame gives, right now. It's the streak, up to MAX_STREAK_MULTIPLIER.
+    // So the first solve gives one ore, the next one two, and so on, until a failure starts it over at one.
+    pub fn reward(&self) -> F {
+        F::from(I::from(self.streak.clamp(1, MAX_STREAK_MULTIPLIER)))
+    }
+
     // Determines if the player has won the minigame. Done by checking if the next button to be clicked is greater than the difficulty.
     // If it is, that means there are no more buttons to be clicked, and the player has won.
     pub fn is_solved(&self) -> bool {
         self.next > self.difficulty
     }
 
-    // Resets the ore minigame if the player has failed the minigame.
-    pub fn reset_if_failed(&mut self) -> &mut Self {
-        if self.is_failed() {
-            self.reset();
+    // How long the solve took, in seconds, from the first accepted click to the last one. None if it isn't solved.
+    pub fn solve_time(&self) -> Option<f64> {
+        match (self.is_solved(), self.first_click, self.last_click) {
+            (true, Some(first), Some(last)) => Some(last - first),
+            _ => None,
+        }
+    }
+
+    // Deals with the player having failed the minigame, however the settings say to. Check FailBehavior for the options.
+    // Reset starts over with a new order. Setback moves next back by setback_steps, but never before the first button.
+    // Lockout keeps all the progress, and locks the buttons for the lockout time, counted from the wrong click.
+    // Either way, the minigame isn't failed anymore afterwards, and the streak is over. Forgiven mistakes aren't failures, so they keep it going.
+    pub fn reset_if_failed(
+        &mut self,
+        settings: &MinigameSettings,
+        rng: &mut impl Rng,
+    ) -> &mut Self {
+        if !self.is_failed() {
+            return self;
+        }
+        self.streak = 1;
+        match settings.fail_behavior {
+            FailBehavior::Reset => {
+                self.reset(settings, rng);
+            }
+            FailBehavior::Setback => {
+                self.next = self.next.saturating_sub(settings.setback_steps).max(1);
+                self.failed = false;
+            }
+            FailBehavior::Lockout => {
+                self.locked_until = self.last_click.map(|click| click + settings.lockout());
+                self.failed = false;
+            }
         }
         self
     }
 
-    // Does something if the player has won the minigame.
-    pub fn do_if_solved(&mut self, f: impl FnOnce(&mut Self)) -> &mut Self {
+    // Does something if the player has won the minigame. The closure is given how much ore the solve is worth, check reward.
+    pub fn do_if_solved(&mut self, f: impl FnOnce(&mut Self, F)) -> &mut Self {
         if self.is_solved() {
-            f(self);
+            let reward = self.reward();
+            f(self, reward);
         }
         self
     }
 
-    // Resets the ore minigame if the player has won the minigame.
-    pub fn reset_if_solved(&mut self) -> &mut Self {
+    // Resets the ore minigame if the player has won the minigame. The streak goes up by one, so the next solve is worth more.
+    pub fn reset_if_solved(
+        &mut self,
+        settings: &MinigameSettings,
+        rng: &mut impl Rng,
+    ) -> &mut Self {
         if self.is_solved() {
-            self.reset();
+            self.streak = self.streak.saturating_add(1);
+            self.reset(settings, rng);
         }
         self
     }
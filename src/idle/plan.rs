@@ -0,0 +1,71 @@
+/*
+This is the factory plan code. A plan is the layout of a factory without any of the progress: just which producers there are, and how they're set up.
+It's for sharing designs, so it leaves out everything a save has that isn't the layout, like the inventory, money, stats and the build queue.
+Each producer is stored as its configuration, the same as copying a single producer. Check clipboard.rs for what that covers.
+
+Importing a plan adds its producers alongside whatever's already there, with fresh ids.
+Normally they're bought like any other producer, in the order they're listed, until the money runs out.
+A free import (for sandboxing) adds them straight away instead, without paying. Those don't count as built, same as the debug producers.
+Plans are meant to be shared, so everything in one is checked before any of it is used. A bad plan changes nothing.
+ */
+
+use crate::idle::clipboard::{PasteError, ProducerConfig};
+use crate::idle::producers::Producer;
+use std::fmt::{Display, Formatter};
+
+// The most producers a plan can have. Anything bigger is almost certainly a mistake, or someone trying to freeze the game.
+pub const MAX_PLAN_PRODUCERS: usize = 1000;
+
+// A factory layout, ready to be shared.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct FactoryPlan {
+    pub producers: Vec<ProducerConfig>, // Every producer in the factory, in the order they were added.
+}
+
+// The ways importing a plan can go wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanError {
+    Malformed(String),      // The text isn't JSON, or isn't a plan.
+    NotBuildable(Producer), // One of the producers can't be built.
+    TooBig(usize), // The plan has more producers than MAX_PLAN_PRODUCERS. Holds how many it has.
+}
+
+impl Display for PlanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanError::Malformed(reason) => write!(f, "That isn't a factory plan: {reason}"),
+            PlanError::NotBuildable(producer) => {
+                write!(f, "The plan has a {producer}, which can't be built")
+            }
+            PlanError::TooBig(count) => write!(
+                f,
+                "The plan has {count} producers, but plans can only have up to {MAX_PLAN_PRODUCERS}"
+            ),
+        }
+    }
+}
+
+impl FactoryPlan {
+    // The plan as JSON, for sharing.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a factory plan should always be serializable")
+    }
+
+    // Reads a plan from JSON, and checks every producer in it the same way pasting a single producer does.
+    pub fn from_json(json: &str) -> Result<Self, PlanError> {
+        let plan: Self =
+            serde_json::from_str(json.trim()).map_err(|e| PlanError::Malformed(e.to_string()))?;
+        if plan.producers.len() > MAX_PLAN_PRODUCERS {
+            return Err(PlanError::TooBig(plan.producers.len()));
+        }
+        for config in plan.producers.iter() {
+            // The producer's configuration goes through the same check as pasting it, so the two can't drift apart.
+            ProducerConfig::from_json(&config.to_json()).map_err(|e| match e {
+                PasteError::NotBuildable(producer) => PlanError::NotBuildable(producer),
+                PasteError::Malformed(reason) => PlanError::Malformed(reason),
+            })?;
+        }
+        Ok(plan)
+    }
+}
@@ -1,6 +1,8 @@
-use crate::idle::goods::{Good, GoodGroup};
+use crate::idle::goods::{Good, GoodGroup, Inventory, StorageLevels};
+use crate::idle::recipe::Recipe;
+use crate::idle::registry::{self, ProducerId};
+use crate::idle::settings::EconomySettings;
 use num::{BigInt, BigRational};
-use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use strum_macros::EnumIter;
 
@@ -20,22 +22,46 @@ type I = BigInt;
     PartialOrd,
     Ord,
     Debug,
+    Default,
 )]
 pub enum Producer {
-    None,               // Blank producer for debug purposes
+    #[default]
+    None, // Blank producer for debug purposes
     GravityDrill(Good), // Drills ore for free. Not intended to be used in the game, only for debugging.
     // I mean come on it's called a gravity drill. How does gravity drill for free?
     CoalDrill(Good), // Drills ore at a rate of 1 per second, at a cost of 1/4 coal per second.
+    Smelter(Good), // Smelts 2 of an ore into 1 of its bar per second, burning 1/2 coal per second. Holds the ore, not the bar.
+    #[strum(disabled)]
+    Registered(ProducerId), // A producer added at runtime. Saved as its key. Check registry.rs for more info.
+}
+
+// The kinds of producer, for sorting the build menu. Every producer belongs to exactly one.
+#[derive(PartialEq, Eq, Clone, Copy, EnumIter, Debug)]
+pub enum ProducerCategory {
+    Debug,   // Producers that only exist for testing, like the gravity drill.
+    Drill,   // Producers that dig ore out of the ground.
+    Smelter, // Producers that turn ore into bars.
+}
+
+impl Display for ProducerCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProducerCategory::Debug => write!(f, "Debug"),
+            ProducerCategory::Drill => write!(f, "Drills"),
+            ProducerCategory::Smelter => write!(f, "Smelters"),
+        }
+    }
 }
 
 // Describes the properties of a producer.
 // This uses a pattern I call "databasing", where the properties of a producer are stored in a properties function.
 // The structure of the properties is as follows:
 pub struct ProducerProperties {
-    pub name: &'static str,        // The name of the producer
-    pub cost: F,                   // The cost of the producer
-    pub outputs: HashMap<Good, F>, // The outputs of the producer. Consumes 1 input per second (up to the maximum input).
-    pub inputs: HashMap<Good, F>, // The inputs of the producer. Produces 1 output per second (up to the maximum output).
+    pub name: &'static str,         // The name of the producer
+    pub cost: F,                    // The cost of the producer
+    pub recipe: Recipe, // What the producer uses and makes per second. Check recipe.rs for more info.
+    pub warmup: F, // How many seconds the producer takes to ramp up to full output after being built. Zero means instant.
+    pub category: ProducerCategory, // What kind of producer it is. Used to filter the build menu.
 }
 
 impl Producer {
@@ -45,78 +71,244 @@ impl Producer {
             Producer::None => ProducerProperties {
                 name: "None",
                 cost: F::from(I::from(0)),
-                outputs: HashMap::new(),
-                inputs: HashMap::new(),
+                recipe: Recipe {
+                    inputs: Inventory::new(),
+                    outputs: Inventory::new(),
+                },
+                warmup: F::from(I::from(0)),
+                category: ProducerCategory::Debug,
             },
             Producer::GravityDrill(good) => ProducerProperties {
                 name: "Gravity Drill",
                 cost: F::from(I::from(10)),
-                outputs: {
-                    let mut map = HashMap::new();
-                    map.insert(*good, F::from(I::from(1)));
-                    map
+                recipe: Recipe {
+                    inputs: Inventory::new(),
+                    outputs: {
+                        let mut map = Inventory::new();
+                        map.insert(*good, F::from(I::from(1)));
+                        map
+                    },
                 },
-                inputs: { HashMap::new() },
+                warmup: F::from(I::from(0)),
+                category: ProducerCategory::Debug,
             },
             Producer::CoalDrill(good) => ProducerProperties {
                 name: "Coal Drill",
                 cost: F::from(I::from(10)),
-                outputs: {
-                    let mut map = HashMap::new();
-                    map.insert(*good, F::from(I::from(1)));
-                    map
+                recipe: Recipe {
+                    inputs: {
+                        let mut map = Inventory::new();
+                        map.insert(Good::Coal, F::new(I::from(1), I::from(4)));
+                        map
+                    },
+                    outputs: {
+                        let mut map = Inventory::new();
+                        map.insert(*good, F::from(I::from(1)));
+                        map
+                    },
                 },
-                inputs: {
-                    let mut map = HashMap::new();
-                    map.insert(Good::Coal, F::new(I::from(1), I::from(4)));
-                    map
+                warmup: F::from(I::from(0)),
+                category: ProducerCategory::Drill,
+            },
+            // Ores without a bar can't be smelted, so their smelters do nothing. They're never buildable. Check buildable.
+            Producer::Smelter(ore) => ProducerProperties {
+                name: "Smelter",
+                cost: F::from(I::from(50)),
+                recipe: Recipe {
+                    inputs: {
+                        let mut map = Inventory::new();
+                        if ore.bar().is_some() {
+                            map.insert(*ore, F::from(I::from(2)));
+                            map.insert(Good::Coal, F::new(I::from(1), I::from(2)));
+                        }
+                        map
+                    },
+                    outputs: {
+                        let mut map = Inventory::new();
+                        if let Some(bar) = ore.bar() {
+                            map.insert(bar, F::from(I::from(1)));
+                        }
+                        map
+                    },
                 },
+                warmup: F::from(I::from(3)),
+                category: ProducerCategory::Smelter,
             },
+            Producer::Registered(id) => registry::producer_properties(*id),
         }
     }
 
+    // Lists every producer that the player can build. Producer::None is just a placeholder, so it's left out.
+    // Registered producers come last, in the order they were registered.
+    pub fn buildable() -> Vec<Producer> {
+        let mut producers = Vec::new();
+        for ore in Good::group_iter(GoodGroup::Ore) {
+            producers.push(Producer::GravityDrill(ore));
+            producers.push(Producer::CoalDrill(ore));
+        }
+        for ore in Good::group_iter(GoodGroup::Ore).filter(|ore| ore.bar().is_some()) {
+            producers.push(Producer::Smelter(ore));
+        }
+        producers.extend(registry::producers());
+        producers
+    }
+
     // To get the default producer for a good group, call this function.
     // Currently never used, but may be used in the future.
     pub fn _default_for_group(group: GoodGroup) -> Producer {
         match group {
             GoodGroup::Money => Producer::None,
             GoodGroup::Ore => Producer::GravityDrill(Good::_default_for_group(group)),
+            GoodGroup::Bar => Producer::Smelter(Good::_default_for_group(GoodGroup::Ore)),
         }
     }
 
     // Ticks the producer based on the tick rate. First, makes sure that the producer has enough inputs to produce outputs, then produces outputs.
     // Producers are "all or nothing", meaning that if they don't have enough inputs to produce outputs, they produce nothing.
     // This is to prevent weird inconsistencies, and is likely more expected by the player.
-    pub fn tick(&self, inventory: &mut HashMap<Good, F>, tick_rate: &F) {
-        if self.has_enough_inputs(inventory, tick_rate) {
-            self.tick_inventory(inventory, tick_rate);
+    // Outputs are different. If a whole tick of outputs doesn't fit in the inventory, the producer runs for as much of the tick as does fit,
+    // so its outputs fill up to exactly their capacity, and it stalls once they're full. Check room_ratio.
+    // Unless discard_excess is set, in which case it keeps running and whatever doesn't fit is thrown away.
+    // If sell is set, the outputs are sold as they're made instead of being stored. Check deposited_outputs for more info.
+    pub fn tick(
+        &self,
+        inventory: &mut Inventory,
+        tick_rate: &F,
+        discard_excess: bool,
+        storage: &StorageLevels,
+        sell: Option<&EconomySettings>,
+    ) {
+        let tick_rate = if discard_excess {
+            tick_rate.clone()
+        } else {
+            tick_rate * self.room_ratio(inventory, tick_rate, storage, sell)
+        };
+        if tick_rate <= F::from(I::from(0)) {
+            return;
+        }
+        if self.has_enough_inputs(inventory, &tick_rate) {
+            self.tick_inventory(inventory, &tick_rate, storage, sell);
         }
     }
 
-    // Checks to see if the producer has enough inputs to produce outputs.
-    // Currently references the player inventory. Will be changed to reference the producer inventory in the future.
-    fn has_enough_inputs(&self, inventory: &HashMap<Good, F>, tick_rate: &F) -> bool {
-        for (good, amount) in self.properties().inputs.iter() {
-            let alt_amount = F::from(I::from(0));
-            let inventory_amount = inventory.get(good).unwrap_or(&alt_amount);
-            if *inventory_amount < amount * tick_rate {
-                return false;
+    // What the producer actually puts into the inventory per second. Normally that's just its outputs.
+    // If sell is set, every output with a sell price is turned into money at that price (after tax) instead, so it never touches storage.
+    // Outputs that can't be sold are still deposited as they are. Money can't be sold for money, but it has no price, so that works out.
+    pub fn deposited_outputs(&self, sell: Option<&EconomySettings>) -> Inventory {
+        let mut deposited = Inventory::new();
+        for (good, amount) in self.properties().recipe.outputs.iter() {
+            let price = sell.map(|economy| economy.sell_price(good.properties().base_price));
+            let (good, amount) = match price {
+                Some(price) if price > F::from(I::from(0)) => (Good::Money, amount * price),
+                _ => (*good, amount.clone()),
+            };
+            *deposited.entry(good).or_insert_with(|| F::from(I::from(0))) += amount;
+        }
+        deposited
+    }
+
+    // Ticks the producer using its own buffers instead of the player inventory.
+    // Inputs are taken out of the input buffer, and outputs are put into the output buffer. Same all or nothing rule as tick.
+    // Moving goods between the buffers and the player inventory is the element's job. Check element.rs for the logistics.
+    pub fn tick_buffered(
+        &self,
+        input_buffer: &mut Inventory,
+        output_buffer: &mut Inventory,
+        tick_rate: &F,
+        sell: Option<&EconomySettings>,
+    ) {
+        if self.has_enough_inputs(input_buffer, tick_rate) {
+            let recipe = self.deposited_recipe(sell);
+            recipe.take_inputs(input_buffer, tick_rate);
+            recipe.add_outputs(output_buffer, tick_rate);
+        }
+    }
+
+    // Calculates how much of a whole tick of outputs fits in the inventory, from 0 (an output is full) to 1 (it all fits).
+    // This is the fraction of the fullest output that there's room for. Goods without a capacity always fit.
+    // Sold outputs are checked as the money they turn into, which has no capacity.
+    pub fn room_ratio(
+        &self,
+        inventory: &Inventory,
+        tick_rate: &F,
+        storage: &StorageLevels,
+        sell: Option<&EconomySettings>,
+    ) -> F {
+        let zero = F::from(I::from(0));
+        let one = F::from(I::from(1));
+        let mut ratio = one.clone();
+        for (good, amount) in self.deposited_outputs(sell).iter() {
+            let made = amount * tick_rate;
+            if made <= zero {
+                continue;
+            }
+            if let Some(room) = good.room(inventory, storage) {
+                ratio = ratio.min(room / made);
             }
         }
-        true
+        ratio.clamp(zero, one)
+    }
+
+    // Checks to see if the producer has enough inputs to produce outputs.
+    // This works on whatever it's given, which is either the player inventory or the producer's input buffer.
+    // This is the fast path, for when it doesn't matter which inputs are short. Check check_inputs for the details.
+    fn has_enough_inputs(&self, inventory: &Inventory, tick_rate: &F) -> bool {
+        self.input_ratio(inventory, tick_rate) >= F::from(I::from(1))
     }
 
-    // Ticks the inventory based on the tick rate. First, removes inputs, then adds outputs.
-    fn tick_inventory(&self, inventory: &mut HashMap<Good, F>, tick_rate: &F) {
-        for (good, amount) in self.properties().outputs.iter() {
-            let inventory_amount = inventory.entry(*good).or_insert(F::from(I::from(0)));
-            *inventory_amount += amount * tick_rate;
+    // Checks to see if the producer has enough inputs for a whole tick, and if not, which inputs it's short on.
+    // The missing inputs are in the same order as the inputs themselves, so they're listed the same way every time.
+    pub fn check_inputs(&self, inventory: &Inventory, tick_rate: &F) -> Result<(), Vec<Good>> {
+        let missing = self
+            .properties()
+            .recipe
+            .missing_inputs(inventory, tick_rate);
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
         }
-        for (good, amount) in self.properties().inputs.iter() {
-            let inventory_amount = inventory.entry(*good).or_insert(F::from(I::from(0)));
-            *inventory_amount -= amount * tick_rate;
+    }
+
+    // Calculates how much of a full tick the available inputs could cover, from 0 (completely starved) to 1 (fully fed).
+    // This is the fraction of the scarcest input that's available. Producers with no inputs are always fully fed.
+    pub fn input_ratio(&self, inventory: &Inventory, tick_rate: &F) -> F {
+        self.properties().recipe.input_ratio(inventory, tick_rate)
+    }
+
+    // Calculates how close the producer is to its theoretical output, given the inputs available.
+    // Producers are all or nothing, so this is either 1 (fully fed) or 0 (stalled).
+    // If producers ever run partially when starved, this should return the input ratio itself.
+    pub fn efficiency(&self, inventory: &Inventory, tick_rate: &F) -> F {
+        if self.has_enough_inputs(inventory, tick_rate) {
+            F::from(I::from(1))
+        } else {
+            F::from(I::from(0))
         }
     }
+
+    // The producer's recipe as it actually runs: its inputs, and what it deposits instead of its outputs. Check deposited_outputs.
+    pub fn deposited_recipe(&self, sell: Option<&EconomySettings>) -> Recipe {
+        Recipe::new(
+            self.properties().recipe.inputs,
+            self.deposited_outputs(sell),
+        )
+    }
+
+    // Ticks the inventory based on the tick rate. First, adds every output at once, then removes inputs.
+    // Outputs are capped at each good's capacity, so anything that doesn't fit is lost. Check tick for when that can happen.
+    // If sell is set, the outputs are sold instead. That's the only difference. Check deposited_outputs for more info.
+    fn tick_inventory(
+        &self,
+        inventory: &mut Inventory,
+        tick_rate: &F,
+        storage: &StorageLevels,
+        sell: Option<&EconomySettings>,
+    ) {
+        let recipe = self.deposited_recipe(sell);
+        recipe.store_outputs(inventory, tick_rate, storage);
+        recipe.take_inputs(inventory, tick_rate);
+    }
 }
 
 impl Display for Producer {
@@ -125,12 +317,8 @@ impl Display for Producer {
             Producer::None => write!(f, "None"),
             Producer::GravityDrill(good) => write!(f, "Gravity Drill ({good})"),
             Producer::CoalDrill(good) => write!(f, "Coal Drill ({good})"),
+            Producer::Smelter(good) => write!(f, "Smelter ({good})"),
+            Producer::Registered(_) => write!(f, "{}", self.properties().name),
         }
     }
 }
-
-impl Default for Producer {
-    fn default() -> Self {
-        Self::None
-    }
-}
@@ -0,0 +1,111 @@
+/*
+This is the rate smoothing code. It measures how fast each good is actually changing, as seen from frame to frame.
+The simulation only ever moves in whole ticks, and leftover time smaller than a tick waits for the next frame.
+So some frames get one tick and some get none, and a rate measured over single frames jumps around even when production is steady.
+To hide that stutter, the measured rates are smoothed with an exponential moving average (EMA) before they're shown.
+This is purely for display. The simulation never reads any of it, and none of it is saved.
+
+The production ticker in the top bar uses the same smoothing, but on the theoretical rates from the production table instead of measured ones.
+Those don't stutter, but they jump whenever a producer is built or finishes warming up, and the ticker eases into the new rate instead.
+ */
+
+use crate::idle::goods::{Good, Inventory};
+use num::{BigInt, BigRational, ToPrimitive};
+use std::collections::{BTreeMap, HashMap};
+
+type F = BigRational;
+type I = BigInt;
+
+// How quickly the smoothed rate follows the measured one, in seconds. After this long, a change is about two thirds of the way in.
+const SMOOTHING_SECONDS: f64 = 1.0;
+
+// Moves a smoothed rate towards a new measurement of it. How far it moves depends on how long it's been since the last one,
+// so the smoothing takes the same time no matter the framerate.
+fn smooth(rate: &mut f64, measured: f64, elapsed: f64) {
+    let weight = 1.0 - (-elapsed / SMOOTHING_SECONDS).exp();
+    *rate += (measured - *rate) * weight;
+}
+
+// The smoothed rates of every good.
+#[derive(Default)]
+pub struct RateSmoother {
+    previous: Option<(f64, BTreeMap<Good, f64>)>, // When the last frame was, in egui's time, and the inventory as of then.
+    rates: BTreeMap<Good, f64>,                   // The smoothed rate of each good, per second.
+}
+
+impl RateSmoother {
+    // Measures how much each good changed since last frame, and folds that into the smoothed rates.
+    pub fn update(&mut self, inventory: &Inventory, now: f64) {
+        let amounts: BTreeMap<Good, f64> = inventory
+            .iter()
+            .map(|(good, amount)| (*good, amount.to_f64().unwrap_or(0.0)))
+            .collect();
+        if let Some((then, previous)) = &self.previous {
+            let elapsed = now - then;
+            if elapsed > 0.0 {
+                for (good, amount) in amounts.iter() {
+                    let measured = (amount - previous.get(good).unwrap_or(amount)) / elapsed;
+                    smooth(
+                        self.rates.entry(*good).or_insert(measured),
+                        measured,
+                        elapsed,
+                    );
+                }
+            }
+        }
+        self.previous = Some((now, amounts));
+    }
+
+    // The smoothed rate of a good, per second. Goods that haven't been measured yet are at zero.
+    pub fn rate(&self, good: Good) -> f64 {
+        self.rates.get(&good).copied().unwrap_or(0.0)
+    }
+}
+
+// The smoothed net rates shown in the top bar's production ticker.
+#[derive(Default)]
+pub struct ProductionTicker {
+    then: Option<f64>,          // When the ticker was last updated, in egui's time.
+    rates: BTreeMap<Good, f64>, // The smoothed net rate of each good, per second.
+}
+
+impl ProductionTicker {
+    // Folds the net rates from a production table into the smoothed ones. The table is (produced, consumed), like everywhere else.
+    // Goods that are missing from the table are treated as not moving, so their rates ease down to zero.
+    pub fn update(&mut self, production_table: &HashMap<Good, (F, F)>, now: f64) {
+        let elapsed = self.then.map_or(0.0, |then| now - then);
+        self.then = Some(now);
+        for (good, (made, used)) in production_table.iter() {
+            let net = (made - used).to_f64().unwrap_or(0.0);
+            // Goods showing up for the first time start out at their rate, rather than easing up from zero.
+            let rate = self.rates.entry(*good).or_insert(net);
+            smooth(rate, net, elapsed);
+        }
+        for (good, rate) in self.rates.iter_mut() {
+            if !production_table.contains_key(good) {
+                smooth(rate, 0.0, elapsed);
+            }
+        }
+    }
+
+    // The smoothed net rate of money, per second. This is the headline number of the ticker.
+    pub fn money(&self) -> F {
+        self.rate(Good::Money)
+    }
+
+    // The good (other than money) going up the fastest, and its smoothed net rate per second. None if nothing is going up.
+    pub fn fastest(&self) -> Option<(Good, F)> {
+        self.rates
+            .iter()
+            .filter(|(good, rate)| **good != Good::Money && **rate > 0.0)
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(good, _rate)| (*good, self.rate(*good)))
+    }
+
+    fn rate(&self, good: Good) -> F {
+        self.rates
+            .get(&good)
+            .and_then(|rate| F::from_float(*rate))
+            .unwrap_or_else(|| F::from(I::from(0)))
+    }
+}
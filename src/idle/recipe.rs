@@ -0,0 +1,111 @@
+/*
+This is the recipe code. A recipe is what goes into making something and what comes out of it, per second, as one value.
+Every producer has one (check ProducerProperties), registered producers bring their own (check ProducerDefinition),
+crafting makes them by hand (check crafting.rs), and the chain planner strings them together (check chain.rs).
+Either side can have any number of goods, like an ore with a byproduct, or none at all, like a drill that needs nothing.
+Recipes are all or nothing: every output is made in the same tick, or none of them are. Check Producer::tick for how producers do that.
+Everything here works on "times", which is how many seconds of the recipe to use. A tick is times = tick_rate, and a craft is times = 1.
+ */
+
+use crate::idle::goods::{Good, Inventory, StorageLevels};
+use num::{BigInt, BigRational, ToPrimitive};
+
+type F = BigRational;
+type I = BigInt;
+
+// What a recipe uses and makes per second. Check the top of this file for more info.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Recipe {
+    pub inputs: Inventory,  // What the recipe uses per second.
+    pub outputs: Inventory, // What the recipe makes per second.
+}
+
+impl Recipe {
+    pub fn new(inputs: Inventory, outputs: Inventory) -> Self {
+        Recipe { inputs, outputs }
+    }
+
+    // How much of a good the recipe makes per second, after taking out what it uses of it. Negative if it uses more than it makes.
+    pub fn net(&self, good: Good) -> F {
+        let zero = F::from(I::from(0));
+        self.outputs.get(&good).unwrap_or(&zero) - self.inputs.get(&good).unwrap_or(&zero)
+    }
+
+    // The inputs there isn't enough of for the given number of seconds, in the order the recipe lists them.
+    pub fn missing_inputs(&self, inventory: &Inventory, times: &F) -> Vec<Good> {
+        let zero = F::from(I::from(0));
+        self.inputs
+            .iter()
+            .filter(|(good, amount)| inventory.get(*good).unwrap_or(&zero) < &(*amount * times))
+            .map(|(good, _amount)| *good)
+            .collect()
+    }
+
+    // How much of the given number of seconds the inventory could cover, from 0 (completely starved) to 1 (fully fed).
+    // This is the fraction of the scarcest input that's there. Recipes with no inputs are always fully fed.
+    pub fn input_ratio(&self, inventory: &Inventory, times: &F) -> F {
+        let zero = F::from(I::from(0));
+        let one = F::from(I::from(1));
+        let mut ratio = one.clone();
+        for (good, amount) in self.inputs.iter() {
+            let needed = amount * times;
+            if needed <= zero {
+                continue;
+            }
+            let inventory_amount = inventory.get(good).unwrap_or(&zero);
+            ratio = ratio.min(inventory_amount / needed);
+        }
+        ratio.clamp(zero, one)
+    }
+
+    // How many whole times the recipe can be made in one go. Every input has to be there, and every output has to fit.
+    // Anything too big to count is as good as no limit at all.
+    pub fn max_times(&self, inventory: &Inventory, storage: &StorageLevels) -> u64 {
+        let zero = F::from(I::from(0));
+        // How many times an amount goes into what there is, rounded down.
+        let times = |have: &F, need: &F| -> u64 {
+            if need <= &zero {
+                return u64::MAX;
+            }
+            (have / need)
+                .floor()
+                .to_integer()
+                .to_u64()
+                .unwrap_or(u64::MAX)
+        };
+        let from_inputs = self
+            .inputs
+            .iter()
+            .map(|(good, need)| times(inventory.get(good).unwrap_or(&zero), need));
+        let from_outputs = self.outputs.iter().filter_map(|(good, made)| {
+            good.room(inventory, storage).map(|room| times(&room, made))
+        });
+        from_inputs.chain(from_outputs).min().unwrap_or(u64::MAX)
+    }
+
+    // Takes the inputs for the given number of seconds out of the inventory. It doesn't check there's enough, so check first.
+    pub fn take_inputs(&self, inventory: &mut Inventory, times: &F) {
+        for (good, amount) in self.inputs.iter() {
+            *inventory
+                .entry(*good)
+                .or_insert_with(|| F::from(I::from(0))) -= amount * times;
+        }
+    }
+
+    // Puts the outputs for the given number of seconds into the inventory, all of them at once.
+    // Outputs are capped at each good's capacity, so anything that doesn't fit is lost. Check Good::store.
+    pub fn store_outputs(&self, inventory: &mut Inventory, times: &F, storage: &StorageLevels) {
+        for (good, amount) in self.outputs.iter() {
+            good.store(inventory, amount * times, storage);
+        }
+    }
+
+    // Same as store_outputs, but without any capacity. Only used for output buffers, which have no capacity of their own.
+    pub fn add_outputs(&self, inventory: &mut Inventory, times: &F) {
+        for (good, amount) in self.outputs.iter() {
+            *inventory
+                .entry(*good)
+                .or_insert_with(|| F::from(I::from(0))) += amount * times;
+        }
+    }
+}
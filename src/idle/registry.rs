@@ -0,0 +1,281 @@
+/*
+This is the content registry. The built-in goods and producers are enum variants, databased in their properties functions,
+which means adding one means editing the core enums. The registry lets content be added at startup instead, without touching them,
+so an alternative main (or one day a mod loader) can bring its own goods and producers.
+
+Registered content gets a dynamic id, wrapped in Good::Registered and Producer::Registered, and its properties live here instead of in a match.
+Everything else in the game goes through properties(), so registered content works everywhere the built-in content does.
+The ids depend on the order things were registered in, so they're never saved. Saves store the key each thing was registered under instead,
+just like the built-in goods are saved under their keys. That keeps saves stable no matter what order the content is registered in.
+Loading a save with a key that hasn't been registered fails, the same as loading a save with a good that was removed.
+
+The rules are:
+- Register everything at startup, before any game state is made or loaded. Game states made before a good is registered won't have it in their inventory until the save is loaded again.
+- The registry is global and only grows. Nothing can be unregistered, since saves and game states might still be using it.
+- Keys have to look like identifiers (letters, digits and underscores, not starting with a digit), which is what RON saves need them to be.
+- Keys can't clash with each other, or with the built-in goods.
+- A good whose key changed can list its old keys, so saves made under them still load. Old keys follow the same rules as keys.
+  Saves are always written with the current key, so an old key only has to be kept until nobody has a save that old.
+- Registered goods come after every built-in good in the inventory, in the order they were registered, whatever their group.
+Names are leaked to make them 'static, like the built-in names. That's fine, since content is only registered once, at startup.
+ */
+
+use crate::idle::goods::{Good, GoodGroup, GoodProperties};
+use crate::idle::producers::{ProducerCategory, ProducerProperties};
+use crate::idle::recipe::Recipe;
+use num::BigRational;
+use std::fmt::{Display, Formatter};
+use std::sync::RwLock;
+use strum::IntoEnumIterator;
+
+type F = BigRational;
+
+// The id of a registered good. Only the registry makes these, so every one of them is registered.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Debug)]
+pub struct GoodId(usize);
+
+// The id of a registered producer. Same as GoodId, only the registry makes these.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Debug)]
+pub struct ProducerId(usize);
+
+// A good to register. Mirrors GoodProperties, with a key to save it under.
+#[derive(Debug, Clone)]
+pub struct GoodDefinition {
+    pub key: String, // What the good is saved under. Check the top of this file for the rules.
+    pub name: String, // The name the good is shown with.
+    pub group: GoodGroup, // The group of the good.
+    pub difficulty: u32, // The difficulty of the good's minigame, if it's an ore.
+    pub capacity: Option<u32>, // How much of the good the player can hold. None means there's no limit.
+    pub base_price: u32,       // How much one of the good sells for. Zero means it can't be sold.
+    pub time_limit_ms: Option<u32>, // How long the good's minigame gives to finish, if it's an ore and minigames are timed. None is never timed.
+    pub old_keys: Vec<String>, // Keys the good used to be saved under, which still load as it. Check the top of this file.
+}
+
+// A producer to register. Mirrors ProducerProperties, with a key to save it under.
+#[derive(Debug, Clone)]
+pub struct ProducerDefinition {
+    pub key: String, // What the producer is saved under. Check the top of this file for the rules.
+    pub name: String, // The name the producer is shown with.
+    pub cost: F,     // How much the producer costs to build.
+    pub recipe: Recipe, // What the producer uses and makes per second. Can be built-in or registered goods.
+    pub warmup: F,      // How many seconds the producer takes to reach full output.
+    pub category: ProducerCategory, // Which category of the build menu the producer is under.
+}
+
+// The ways registering content can go wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    InvalidKey(String),   // The key doesn't look like an identifier.
+    DuplicateKey(String), // Something is already registered (or built in) under the key.
+}
+
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::InvalidKey(key) => write!(
+                f,
+                "\"{key}\" isn't a valid key. Keys are letters, digits and underscores, and can't start with a digit"
+            ),
+            RegistryError::DuplicateKey(key) => {
+                write!(f, "Something is already registered as \"{key}\"")
+            }
+        }
+    }
+}
+
+// A registered good, as the registry keeps it.
+struct GoodEntry {
+    key: &'static str,
+    old_keys: Vec<String>,
+    properties: GoodProperties,
+}
+
+// A registered producer, as the registry keeps it.
+struct ProducerEntry {
+    key: &'static str,
+    name: &'static str,
+    definition: ProducerDefinition,
+}
+
+// Everything that's been registered. Check the top of this file for more info.
+static GOODS: RwLock<Vec<GoodEntry>> = RwLock::new(Vec::new());
+static PRODUCERS: RwLock<Vec<ProducerEntry>> = RwLock::new(Vec::new());
+
+// Whether a key looks like an identifier. Check the top of this file for why they have to.
+fn valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .map_or(false, |first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Leaks a string to make it 'static. Check the top of this file for why that's fine.
+fn leak(text: String) -> &'static str {
+    Box::leak(text.into_boxed_str())
+}
+
+// Registers a good. Returns the good to use it as.
+pub(crate) fn register_good(definition: GoodDefinition) -> Result<Good, RegistryError> {
+    // The write lock is taken before checking for clashes, so nothing can register the same key between the check and the push.
+    let mut goods = GOODS.write().expect("the registry shouldn't be poisoned");
+    // Old keys follow the same rules as the key, and the good's own keys can't repeat either.
+    let mut seen: Vec<&String> = Vec::new();
+    for key in std::iter::once(&definition.key).chain(definition.old_keys.iter()) {
+        if !valid_key(key) {
+            return Err(RegistryError::InvalidKey(key.clone()));
+        }
+        if seen.contains(&key)
+            || builtin_good_by_key(key).is_some()
+            || registered_good_by_key(&goods, key).is_some()
+        {
+            return Err(RegistryError::DuplicateKey(key.clone()));
+        }
+        seen.push(key);
+    }
+    let id = GoodId(goods.len());
+    goods.push(GoodEntry {
+        key: leak(definition.key),
+        old_keys: definition.old_keys,
+        properties: GoodProperties {
+            name: leak(definition.name),
+            group: definition.group,
+            difficulty: definition.difficulty,
+            capacity: definition.capacity,
+            base_price: definition.base_price,
+            time_limit_ms: definition.time_limit_ms,
+        },
+    });
+    Ok(Good::Registered(id))
+}
+
+// Registers a producer. Returns the producer to use it as.
+pub(crate) fn register_producer(
+    definition: ProducerDefinition,
+) -> Result<crate::idle::producers::Producer, RegistryError> {
+    // Same as register_good, the check and the push happen under the one write lock.
+    let mut producers = PRODUCERS
+        .write()
+        .expect("the registry shouldn't be poisoned");
+    if !valid_key(&definition.key) {
+        return Err(RegistryError::InvalidKey(definition.key));
+    }
+    if registered_producer_by_key(&producers, &definition.key).is_some() {
+        return Err(RegistryError::DuplicateKey(definition.key));
+    }
+    let id = ProducerId(producers.len());
+    producers.push(ProducerEntry {
+        key: leak(definition.key.clone()),
+        name: leak(definition.name.clone()),
+        definition,
+    });
+    Ok(crate::idle::producers::Producer::Registered(id))
+}
+
+// Every registered good, in the order they were registered.
+pub(crate) fn goods() -> Vec<Good> {
+    let goods = GOODS.read().expect("the registry shouldn't be poisoned");
+    (0..goods.len())
+        .map(|id| Good::Registered(GoodId(id)))
+        .collect()
+}
+
+// Every registered producer, in the order they were registered.
+pub(crate) fn producers() -> Vec<crate::idle::producers::Producer> {
+    let producers = PRODUCERS
+        .read()
+        .expect("the registry shouldn't be poisoned");
+    (0..producers.len())
+        .map(|id| crate::idle::producers::Producer::Registered(ProducerId(id)))
+        .collect()
+}
+
+// The properties of a registered good.
+pub(crate) fn good_properties(id: GoodId) -> GoodProperties {
+    GOODS.read().expect("the registry shouldn't be poisoned")[id.0].properties
+}
+
+// The key a registered good is saved under.
+pub(crate) fn good_key(id: GoodId) -> &'static str {
+    GOODS.read().expect("the registry shouldn't be poisoned")[id.0].key
+}
+
+// Finds a good by the key it's saved under, or one it used to be saved under. Built-in goods are found by their keys. Check Good::key.
+pub(crate) fn good_by_key(key: &str) -> Option<Good> {
+    builtin_good_by_key(key).or_else(|| {
+        registered_good_by_key(
+            &GOODS.read().expect("the registry shouldn't be poisoned"),
+            key,
+        )
+    })
+}
+
+// The built-in half of good_by_key.
+fn builtin_good_by_key(key: &str) -> Option<Good> {
+    Good::iter().find(|good| good.key() == key || good.old_keys().contains(&key))
+}
+
+// The registered half of good_by_key. Takes the goods instead of locking them, so register_good can use it under its write lock.
+fn registered_good_by_key(goods: &[GoodEntry], key: &str) -> Option<Good> {
+    goods
+        .iter()
+        .position(|entry| entry.key == key || entry.old_keys.iter().any(|old| old == key))
+        .map(|id| Good::Registered(GoodId(id)))
+}
+
+// The properties of a registered producer.
+pub(crate) fn producer_properties(id: ProducerId) -> ProducerProperties {
+    let producers = PRODUCERS
+        .read()
+        .expect("the registry shouldn't be poisoned");
+    let entry = &producers[id.0];
+    ProducerProperties {
+        name: entry.name,
+        cost: entry.definition.cost.clone(),
+        recipe: entry.definition.recipe.clone(),
+        warmup: entry.definition.warmup.clone(),
+        category: entry.definition.category,
+    }
+}
+
+// The key a registered producer is saved under.
+pub(crate) fn producer_key(id: ProducerId) -> &'static str {
+    PRODUCERS
+        .read()
+        .expect("the registry shouldn't be poisoned")[id.0]
+        .key
+}
+
+// Finds a registered producer by the key it's saved under.
+pub(crate) fn producer_by_key(key: &str) -> Option<ProducerId> {
+    registered_producer_by_key(
+        &PRODUCERS
+            .read()
+            .expect("the registry shouldn't be poisoned"),
+        key,
+    )
+}
+
+// Same as registered_good_by_key, for producers.
+fn registered_producer_by_key(producers: &[ProducerEntry], key: &str) -> Option<ProducerId> {
+    producers
+        .iter()
+        .position(|entry| entry.key == key)
+        .map(ProducerId)
+}
+
+// Registered producers are saved as their key, as a plain string.
+impl serde::Serialize for ProducerId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(producer_key(*self))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ProducerId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        producer_by_key(&key).ok_or_else(|| {
+            serde::de::Error::custom(format!("no producer is registered as \"{key}\""))
+        })
+    }
+}
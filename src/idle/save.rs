@@ -0,0 +1,348 @@
+/*
+This is the save versioning code. Saves are exported as JSON, and every save records the version of the format it was written in.
+When an older save is imported, it's run through a chain of migrations to bring it up to the current version before it's loaded.
+Most changes to the game state don't need a migration, since serde(default) fills in missing fields.
+Migrations are only needed when something is renamed, moved, or changes meaning.
+
+This file also has the compact save format, which is MessagePack instead of eframe's usual RON.
+Big rationals get serialized as lists of digits, so text formats bloat quickly as the numbers grow.
+MessagePack with named fields is much smaller, but still self-describing, so serde(default) keeps working when fields are added.
+(A purely positional format like bincode would break every save whenever a field is added.)
+
+Exported saves end with a CRC32 checksum of the JSON, on its own line. A save that doesn't match its checksum got corrupted
+(or hand-edited) after it was exported, and is rejected before anything is loaded from it.
+Saves exported before checksums existed don't have one, so they're loaded without checking. Removing the line does the same,
+which is the way to load a save that was edited on purpose.
+
+Saves can also be exported as backups, which are the same exported save, base64 encoded into a single line.
+On the web, eframe keeps the save in the browser's local storage, and there's no easy way to copy that out. A backup string can be pasted
+anywhere (a chat, a notes app, a web form) without the newline or the quotes getting mangled. Importing takes either kind.
+
+Migrations can be dry-run, for checking them before they ship. Check dry_run. It migrates a copy of a save, loads it, and writes it back out,
+then lists every field that changed along the way, without touching the game. Fields removed by a migration, or dropped because nothing
+reads them anymore, are where data gets lost, so those are the ones to look out for.
+ */
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+// The version of the save format that this build writes.
+// Bump this whenever a change needs a migration, and add the migration to MIGRATIONS.
+pub const SAVE_VERSION: u32 = 2;
+
+// Migrations, in order. The migration at index i upgrades a save from version i to version i + 1.
+// So MIGRATIONS.len() should always equal SAVE_VERSION.
+const MIGRATIONS: [fn(&mut Value); SAVE_VERSION as usize] = [migrate_v0_to_v1, migrate_v1_to_v2];
+
+// Version 0 is every save from before saves were versioned. The only difference is the missing save_version field,
+// which gets stamped on after the migration chain has run, so there's nothing to do here.
+fn migrate_v0_to_v1(_save: &mut Value) {}
+
+// Version 2 moved the selected tab into the saved UI state, along with the region filter.
+fn migrate_v1_to_v2(save: &mut Value) {
+    if let Some(selection) = save
+        .as_object_mut()
+        .and_then(|save| save.remove("selection"))
+    {
+        save["ui_state"] = serde_json::json!({ "selection": selection });
+    }
+}
+
+// The ways importing a save can go wrong.
+#[derive(Debug)]
+pub enum SaveError {
+    Malformed(String), // The save isn't valid JSON, or doesn't match the game state after migrating.
+    TooNew(u32), // The save was written by a newer build than this one, so it can't be safely migrated.
+    ChecksumMismatch { expected: u32, found: u32 }, // The save doesn't match its checksum, so it's been changed since it was exported.
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Malformed(reason) => write!(f, "The save couldn't be read: {reason}"),
+            SaveError::TooNew(version) => write!(
+                f,
+                "The save is from a newer version of the game (save version {version}, this build supports up to {SAVE_VERSION})"
+            ),
+            SaveError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "The save has been changed or corrupted since it was exported (checksum {found:08x}, expected {expected:08x})"
+            ),
+        }
+    }
+}
+
+// What the checksum line at the end of an exported save starts with.
+const CHECKSUM_PREFIX: &str = "crc32:";
+
+// The CRC32 of some bytes, the same one zip and PNG use. It's small enough to just write out, rather than pulling in a crate.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+// Puts the checksum line on the end of an exported save. JSON from serde_json never has a raw newline in it, so the line can't be confused for part of the save.
+pub fn add_checksum(json: &str) -> String {
+    format!("{json}\n{CHECKSUM_PREFIX}{:08x}", crc32(json.as_bytes()))
+}
+
+// Checks the checksum on an exported save, and returns the save without it. Saves without a checksum line are returned as they are.
+pub fn verify_checksum(save: &str) -> Result<&str, SaveError> {
+    let save = save.trim_end();
+    let Some((json, line)) = save.rsplit_once('\n') else {
+        return Ok(save);
+    };
+    let Some(hex) = line.trim().strip_prefix(CHECKSUM_PREFIX) else {
+        return Ok(save);
+    };
+    let expected = u32::from_str_radix(hex, 16)
+        .map_err(|_| SaveError::Malformed(format!("invalid checksum {hex}")))?;
+    let found = crc32(json.as_bytes());
+    if found != expected {
+        return Err(SaveError::ChecksumMismatch { expected, found });
+    }
+    Ok(json)
+}
+
+// Reads the version of a save. Saves without a version are from before versioning, so they're version 0.
+pub fn save_version(save: &Value) -> Result<u32, SaveError> {
+    match save.get("save_version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| SaveError::Malformed(format!("invalid save_version {version}"))),
+    }
+}
+
+// Runs every migration between the save's version and the current version, then stamps the current version on the save.
+pub fn migrate(save: &mut Value) -> Result<(), SaveError> {
+    if !save.is_object() {
+        return Err(SaveError::Malformed(String::from(
+            "the save isn't a JSON object",
+        )));
+    }
+    let version = save_version(save)?;
+    if version > SAVE_VERSION {
+        return Err(SaveError::TooNew(version));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(save);
+    }
+    save["save_version"] = Value::from(SAVE_VERSION);
+    Ok(())
+}
+
+// A change to one field of a save, found by dry_run. Paths are the object keys down to the field, joined with dots, like "ui_state.selection".
+// Lists are treated as single values, so a change anywhere in one shows up as the whole list changing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaveChange {
+    Added(String, Value),             // A migration added the field.
+    Removed(String, Value), // A migration removed the field, and it didn't turn up anywhere else.
+    Renamed(String, String), // A migration moved the field from the first path to the second, with the same value.
+    Changed(String, Value, Value), // A migration changed the field's value, from the first one to the second.
+    Defaulted(String, Value), // The field was missing after migrating, so loading filled in its default.
+    Dropped(String, Value), // The field was still there after migrating, but loading threw it away, since nothing reads it.
+    Normalized(String, Value, Value), // Loading changed the field's value, like rounding or fixing it up.
+}
+
+impl SaveChange {
+    // Whether the change loses something from the save.
+    pub fn is_lossy(&self) -> bool {
+        matches!(self, SaveChange::Removed(..) | SaveChange::Dropped(..))
+    }
+}
+
+impl Display for SaveChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveChange::Added(path, value) => write!(f, "Added {path} = {value}"),
+            SaveChange::Removed(path, value) => write!(f, "Removed {path} (was {value})"),
+            SaveChange::Renamed(from, to) => write!(f, "Renamed {from} to {to}"),
+            SaveChange::Changed(path, from, to) => write!(f, "Changed {path} from {from} to {to}"),
+            SaveChange::Defaulted(path, value) => write!(f, "Defaulted {path} to {value}"),
+            SaveChange::Dropped(path, value) => write!(f, "Dropped {path} (was {value})"),
+            SaveChange::Normalized(path, from, to) => {
+                write!(f, "Normalized {path} from {from} to {to}")
+            }
+        }
+    }
+}
+
+// What a dry run of the migrations found. Check dry_run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationReport {
+    pub from_version: u32,        // The version the save was written in.
+    pub changes: Vec<SaveChange>, // Everything that changed, with the migrations' changes first and then loading's.
+}
+
+impl MigrationReport {
+    // Whether anything in the save would be lost by loading it.
+    pub fn is_lossy(&self) -> bool {
+        self.changes.iter().any(SaveChange::is_lossy)
+    }
+}
+
+impl Display for MigrationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Version {} to {SAVE_VERSION}: {} changes",
+            self.from_version,
+            self.changes.len()
+        )?;
+        if self.is_lossy() {
+            write!(f, ", some of them lossy")?;
+        }
+        for change in self.changes.iter() {
+            write!(f, "\n{change}")?;
+        }
+        Ok(())
+    }
+}
+
+// Flattens a save into its fields, by path. Objects are gone into, and everything else (lists too) is a field of its own.
+// Empty objects are kept as fields, so adding or removing one still shows up.
+fn flatten(value: &Value, path: String, fields: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map.iter() {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                flatten(value, path, fields);
+            }
+        }
+        _ => {
+            fields.insert(path, value.clone());
+        }
+    }
+}
+
+// Compares two versions of a save, field by field. The constructors say what to call fields that were added, removed and changed.
+// Removed fields that turn up somewhere else with the same value count as renamed, if renames are being looked for.
+fn diff(
+    before: &Value,
+    after: &Value,
+    renames: bool,
+    added: fn(String, Value) -> SaveChange,
+    removed: fn(String, Value) -> SaveChange,
+    changed: fn(String, Value, Value) -> SaveChange,
+) -> Vec<SaveChange> {
+    let (mut old, mut new) = (BTreeMap::new(), BTreeMap::new());
+    flatten(before, String::new(), &mut old);
+    flatten(after, String::new(), &mut new);
+    let mut changes = Vec::new();
+    let mut gone = Vec::new();
+    for (path, value) in old.iter() {
+        match new.remove(path) {
+            Some(now) if &now != value => changes.push(changed(path.clone(), value.clone(), now)),
+            Some(_) => {}
+            None => gone.push((path.clone(), value.clone())),
+        }
+    }
+    // Whatever's left in new wasn't in old, so it's been added.
+    for (path, value) in gone {
+        let moved = new
+            .iter()
+            .find(|(_path, now)| renames && **now == value)
+            .map(|(path, _now)| path.clone());
+        match moved {
+            Some(to) => {
+                new.remove(&to);
+                changes.push(SaveChange::Renamed(path, to));
+            }
+            None => changes.push(removed(path, value)),
+        }
+    }
+    changes.extend(new.into_iter().map(|(path, value)| added(path, value)));
+    changes
+}
+
+// Dry-runs the migrations on a copy of a save, and reports what would change, without loading anything.
+// The save is migrated, then loaded as T and written back out again, so fields that serde fills in or throws away show up too.
+// Fails the same way migrate does, or if the migrated save doesn't load as T.
+pub fn dry_run<T: Serialize + DeserializeOwned>(
+    save: &Value,
+) -> Result<MigrationReport, SaveError> {
+    let from_version = save_version(save)?;
+    let mut migrated = save.clone();
+    migrate(&mut migrated)?;
+    let loaded: T = serde_json::from_value(migrated.clone())
+        .map_err(|e| SaveError::Malformed(e.to_string()))?;
+    let reloaded = serde_json::to_value(loaded).map_err(|e| SaveError::Malformed(e.to_string()))?;
+    let mut changes = diff(
+        save,
+        &migrated,
+        true,
+        SaveChange::Added,
+        SaveChange::Removed,
+        SaveChange::Changed,
+    );
+    changes.extend(diff(
+        &migrated,
+        &reloaded,
+        false,
+        SaveChange::Defaulted,
+        SaveChange::Dropped,
+        SaveChange::Normalized,
+    ));
+    Ok(MigrationReport {
+        from_version,
+        changes,
+    })
+}
+
+// Backups start with this, so they can be told apart from plain exported saves when importing. Base64 never has a colon in it.
+const BACKUP_PREFIX: &str = "base64:";
+
+// Encodes an exported save as a backup string. The checksum line is kept, so it's still checked when the backup is imported.
+pub fn encode_backup(save: &str) -> String {
+    format!("{BACKUP_PREFIX}{}", base64::encode(save))
+}
+
+// Decodes a backup string back into the exported save. Returns None if the string isn't a backup at all.
+// Whatever was pasted might be cut off or mangled, so it's checked to be text here, and the JSON is checked when it's imported.
+pub fn decode_backup(backup: &str) -> Option<Result<String, SaveError>> {
+    let encoded = backup.trim().strip_prefix(BACKUP_PREFIX)?;
+    Some(
+        base64::decode(encoded.trim())
+            .map_err(|e| SaveError::Malformed(e.to_string()))
+            .and_then(|bytes| {
+                String::from_utf8(bytes).map_err(|e| SaveError::Malformed(e.to_string()))
+            }),
+    )
+}
+
+// Compact saves start with this, so they can be told apart from RON saves when loading.
+const COMPACT_PREFIX: &str = "msgpack:";
+
+// Encodes a value in the compact save format. eframe's storage only holds strings, so the MessagePack bytes are base64 encoded.
+pub fn encode_compact<T: Serialize>(value: &T) -> Result<String, SaveError> {
+    let bytes = rmp_serde::to_vec_named(value).map_err(|e| SaveError::Malformed(e.to_string()))?;
+    Ok(format!("{COMPACT_PREFIX}{}", base64::encode(bytes)))
+}
+
+// Decodes a value from the compact save format. Returns None if the string isn't a compact save at all.
+pub fn decode_compact<T: DeserializeOwned>(save: &str) -> Option<Result<T, SaveError>> {
+    let encoded = save.strip_prefix(COMPACT_PREFIX)?;
+    Some(
+        base64::decode(encoded)
+            .map_err(|e| SaveError::Malformed(e.to_string()))
+            .and_then(|bytes| {
+                rmp_serde::from_slice(&bytes).map_err(|e| SaveError::Malformed(e.to_string()))
+            }),
+    )
+}
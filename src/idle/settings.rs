@@ -0,0 +1,547 @@
+/*
+This is the settings code. Settings are the player's preferences for how the game looks and behaves.
+They're saved along with the game, but they aren't part of the game state, so resetting the game doesn't touch them.
+SimSettings are the exception. They change how the simulation itself works, so they live in the game state instead.
+ */
+
+use crate::idle::goods::{Good, GoodGroup};
+use crate::idle::keybinds::Keybinds;
+use egui::Ui;
+use num::{BigInt, BigRational};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+type F = BigRational;
+type I = BigInt;
+
+// How tightly packed the grids and panels are. Compact fits more rows on small screens.
+#[derive(
+    serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Copy, EnumIter, Debug, Default,
+)]
+pub enum Density {
+    Compact,
+    #[default]
+    Comfortable,
+}
+
+impl Density {
+    // Applies the density to a ui, by changing the spacing between widgets.
+    // Comfortable is egui's default spacing, so it's left as is.
+    pub fn apply(&self, ui: &mut Ui) {
+        match self {
+            Density::Compact => {
+                let spacing = ui.spacing_mut();
+                spacing.item_spacing = egui::vec2(4.0, 1.0);
+                spacing.button_padding = egui::vec2(2.0, 0.0);
+            }
+            Density::Comfortable => {}
+        }
+    }
+}
+
+impl Display for Density {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Density::Compact => write!(f, "Compact"),
+            Density::Comfortable => write!(f, "Comfortable"),
+        }
+    }
+}
+
+// The unit production rates are shown in. Rates are always per second inside the simulation, this only changes how they're displayed.
+// Slow producers are easier to read per minute, since their per second rates are tiny fractions.
+#[derive(
+    serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Copy, EnumIter, Debug, Default,
+)]
+pub enum RateUnit {
+    #[default]
+    PerSecond,
+    PerMinute,
+}
+
+impl RateUnit {
+    // Converts a rate per second into this unit.
+    pub fn scale(&self, per_second: &F) -> F {
+        match self {
+            RateUnit::PerSecond => per_second.clone(),
+            RateUnit::PerMinute => per_second * F::from(I::from(60)),
+        }
+    }
+
+    // The label put after a rate in this unit, like "1/4/s".
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            RateUnit::PerSecond => "/s",
+            RateUnit::PerMinute => "/min",
+        }
+    }
+
+    // Formats a rate per second in this unit, with the suffix.
+    pub fn format(&self, per_second: &F) -> String {
+        format!("{}{}", self.scale(per_second), self.suffix())
+    }
+
+    // Formats an approximate rate per second in this unit, to two decimal places. Used for measured rates, which aren't exact.
+    pub fn format_approx(&self, per_second: f64) -> String {
+        let scaled = match self {
+            RateUnit::PerSecond => per_second,
+            RateUnit::PerMinute => per_second * 60.0,
+        };
+        format!("{scaled:.2}{}", self.suffix())
+    }
+}
+
+impl Display for RateUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RateUnit::PerSecond => write!(f, "Per second"),
+            RateUnit::PerMinute => write!(f, "Per minute"),
+        }
+    }
+}
+
+// The player's settings.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub density: Density,            // How tightly packed the grids and panels are.
+    pub striped: bool,               // Whether the grids have alternating row colors.
+    pub rate_unit: RateUnit,         // The unit production rates are shown in.
+    pub smooth_rates: bool, // Whether the net rate column shows the measured rate, smoothed, instead of the theoretical one. Check rates.rs.
+    pub abbreviate_amounts: bool, // Whether the inventory shortens big amounts, like 1.23M, instead of writing out every digit.
+    pub compact_save: bool, // Whether the game is saved to storage in the compact MessagePack format instead of RON. Check save.rs for more info.
+    pub minigames: MinigameSettings, // Difficulty and accessibility options for the ore minigames.
+    pub sound: SoundSettings, // Whether sounds are played, and how loud.
+    pub colorblind: bool, // Whether the good/bad colors use a palette that's easier to tell apart for colorblind players.
+    pub reduce_motion: bool, // Whether animations are turned off, for players who find them distracting or uncomfortable.
+    pub tab_transition_ms: u32, // How long the fade between tabs takes, in milliseconds. Zero turns it off.
+    pub offline_progress: bool, // Whether the time the game spent closed is simulated when it's opened again. Check offline.rs for more info.
+    pub offline_cap_hours: u32, // The most hours of offline progress that are simulated, however long the game was closed for.
+    pub ticker_fastest: bool, // Whether the production ticker in the top bar also shows the fastest growing good, next to money.
+    pub keybinds: Keybinds,   // Which keys trigger which actions. Check keybinds.rs for more info.
+}
+
+// Options for the game's sounds. Check sound.rs for how they're played.
+// They aren't in the settings window yet, since there's no audio backend to play through. They're still saved, so they're kept for when there is.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SoundSettings {
+    pub enabled: bool, // Whether sounds are played at all. Off by default, so the game doesn't surprise anyone.
+    pub volume: f32,   // How loud the sounds are, from 0 to 1.
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            volume: 0.5,
+        }
+    }
+}
+
+// The colors the UI uses to say whether something is going well or badly.
+// Anything colored by how good or bad it is should take its colors from here, so the colorblind palette applies everywhere.
+pub struct Palette {
+    pub good: egui::Color32,    // Running at full output, or going up.
+    pub partial: egui::Color32, // Somewhere in between.
+    pub bad: egui::Color32,     // Stalled, or going down.
+}
+
+// What happens when a wrong button is clicked in an ore minigame. Check OreMinigame::reset_if_failed for how each one plays out.
+#[derive(
+    serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Copy, EnumIter, Debug, Default,
+)]
+pub enum FailBehavior {
+    #[default]
+    Reset, // The whole minigame starts over, with a new button order. This is how it always worked.
+    Setback, // The progress goes back a few buttons, but the button order stays the same.
+    Lockout, // Nothing is lost, but the buttons can't be clicked for a little while.
+}
+
+impl Display for FailBehavior {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailBehavior::Reset => write!(f, "Start over"),
+            FailBehavior::Setback => write!(f, "Step back"),
+            FailBehavior::Lockout => write!(f, "Lock out"),
+        }
+    }
+}
+
+// Options for the ore minigames. Check ores.rs for how they're used.
+// These are all mild by default, so the minigames play the way they always have.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct MinigameSettings {
+    pub forgiving: bool, // Whether the first wrong click in an attempt is forgiven instead of failing the minigame.
+    pub click_interval_ms: u32, // The shortest time between two accepted clicks, in milliseconds. Faster clicks are ignored. Zero turns it off.
+    pub click_interval_overrides: BTreeMap<u32, u32>, // Click intervals for specific difficulties, replacing click_interval_ms for them.
+    pub scrambled: bool, // Whether nearly sorted button orders are reshuffled, so no minigame is trivially easy. Check ores::shuffled_order.
+    pub fail_behavior: FailBehavior, // What a wrong click costs. Check FailBehavior for the options.
+    pub setback_steps: u32, // How many buttons a wrong click steps back, with FailBehavior::Setback.
+    pub lockout_ms: u32, // How long the buttons are locked after a wrong click, in milliseconds, with FailBehavior::Lockout.
+    pub timed: bool, // Whether minigames have to be finished in time, once the first button is clicked. Check OreMinigame::check_time.
+}
+
+impl Default for MinigameSettings {
+    fn default() -> Self {
+        Self {
+            forgiving: false,
+            // Low enough that nobody clicking on purpose will ever hit it, but it still stops autoclickers.
+            click_interval_ms: 50,
+            click_interval_overrides: BTreeMap::new(),
+            scrambled: false,
+            fail_behavior: FailBehavior::default(),
+            setback_steps: 2,
+            lockout_ms: 2000,
+            timed: false,
+        }
+    }
+}
+
+impl MinigameSettings {
+    // The shortest time between two accepted clicks for a minigame of the given difficulty, in seconds.
+    pub fn click_interval(&self, difficulty: u32) -> f64 {
+        let ms = self
+            .click_interval_overrides
+            .get(&difficulty)
+            .copied()
+            .unwrap_or(self.click_interval_ms);
+        f64::from(ms) / 1000.0
+    }
+
+    // How long a minigame gives to finish, in seconds, given its good's time limit. None if it's untimed, or minigames aren't timed at all.
+    pub fn time_limit(&self, time_limit_ms: Option<u32>) -> Option<f64> {
+        time_limit_ms
+            .filter(|_| self.timed)
+            .map(|ms| f64::from(ms) / 1000.0)
+    }
+
+    // How long the buttons are locked after a wrong click, in seconds, with FailBehavior::Lockout.
+    pub fn lockout(&self) -> f64 {
+        f64::from(self.lockout_ms) / 1000.0
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            density: Density::default(),
+            striped: true,
+            rate_unit: RateUnit::default(),
+            smooth_rates: false,
+            abbreviate_amounts: false,
+            compact_save: false,
+            minigames: MinigameSettings::default(),
+            sound: SoundSettings::default(),
+            colorblind: false,
+            reduce_motion: false,
+            tab_transition_ms: 150,
+            // Off by default. Check IdleGame::new for why.
+            offline_progress: false,
+            offline_cap_hours: 8,
+            ticker_fastest: true,
+            keybinds: Keybinds::default(),
+        }
+    }
+}
+
+impl Settings {
+    // How long the fade between tabs takes, in seconds. Reduced motion turns it off entirely.
+    pub fn tab_transition(&self) -> f64 {
+        if self.reduce_motion {
+            0.0
+        } else {
+            f64::from(self.tab_transition_ms) / 1000.0
+        }
+    }
+
+    // The palette to color things with. The colorblind palette is blue, yellow and orange, from the Okabe-Ito colors.
+    pub fn palette(&self) -> Palette {
+        if self.colorblind {
+            Palette {
+                good: egui::Color32::from_rgb(86, 180, 233),
+                partial: egui::Color32::from_rgb(240, 228, 66),
+                bad: egui::Color32::from_rgb(213, 94, 0),
+            }
+        } else {
+            Palette {
+                good: egui::Color32::from_rgb(110, 170, 90),
+                partial: egui::Color32::from_rgb(200, 170, 60),
+                bad: egui::Color32::from_rgb(200, 80, 70),
+            }
+        }
+    }
+
+    // Renders the settings controls.
+    pub fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("settings_grid").show(ui, |ui| {
+            ui.label("Density");
+            ui.horizontal(|ui| {
+                for density in Density::iter() {
+                    ui.selectable_value(&mut self.density, density, density.to_string());
+                }
+            });
+            ui.end_row();
+            ui.label("Striped grids");
+            ui.checkbox(&mut self.striped, "");
+            ui.end_row();
+            ui.label("Production rates");
+            ui.horizontal(|ui| {
+                for unit in RateUnit::iter() {
+                    ui.selectable_value(&mut self.rate_unit, unit, unit.to_string());
+                }
+            });
+            ui.end_row();
+            ui.label("Measured net rates");
+            ui.checkbox(&mut self.smooth_rates, "")
+                .on_hover_text("The net column shows how fast each good is really changing, smoothed out, instead of the theoretical rate.");
+            ui.end_row();
+            ui.label("Short amounts");
+            ui.checkbox(&mut self.abbreviate_amounts, "")
+                .on_hover_text("The inventory shortens big amounts, like 1.23M for 1,234,567. Hover over an amount to see all of it.");
+            ui.end_row();
+            ui.label("Reduce motion");
+            ui.checkbox(&mut self.reduce_motion, "")
+                .on_hover_text("Turns off animations, like the fade between tabs.");
+            ui.end_row();
+            ui.label("Tab transition");
+            ui.add_enabled(
+                !self.reduce_motion,
+                egui::Slider::new(&mut self.tab_transition_ms, 0..=1000).suffix(" ms"),
+            );
+            ui.end_row();
+            ui.label("Colorblind colors");
+            ui.checkbox(&mut self.colorblind, "")
+                .on_hover_text("Uses blue and orange instead of green and red.");
+            ui.end_row();
+            ui.label("Compact saves");
+            ui.checkbox(&mut self.compact_save, "")
+                .on_hover_text("Saves in a smaller binary format. Exported saves are always JSON.");
+            ui.end_row();
+            ui.label("Offline progress");
+            ui.checkbox(&mut self.offline_progress, "")
+                .on_hover_text("When the game is opened, shows what would have happened while it was closed, to keep or discard.");
+            ui.end_row();
+            ui.label("Offline progress cap");
+            ui.add_enabled(
+                self.offline_progress,
+                egui::Slider::new(&mut self.offline_cap_hours, 1..=72).suffix(" h"),
+            )
+            .on_hover_text("Being closed for longer than this only counts as this long.");
+            ui.end_row();
+            ui.label("Ticker shows fastest good");
+            ui.checkbox(&mut self.ticker_fastest, "")
+                .on_hover_text("The production ticker in the top bar shows the fastest growing good, as well as money.");
+            ui.end_row();
+            // The sound settings aren't shown while there's no audio backend, since they wouldn't do anything. Check sound.rs.
+            ui.label("Forgiving minigames");
+            ui.checkbox(&mut self.minigames.forgiving, "")
+                .on_hover_text("The first wrong click in each minigame attempt is forgiven. The second one still fails.");
+            ui.end_row();
+            ui.label("Timed minigames");
+            ui.checkbox(&mut self.minigames.timed, "")
+                .on_hover_text("Once the first button is clicked, the rest have to be clicked in time, or it counts as a mistake. Harder ores give less time per button.");
+            ui.end_row();
+            ui.label("Scramble minigames");
+            ui.checkbox(&mut self.minigames.scrambled, "")
+                .on_hover_text("Button orders that come out nearly in order are reshuffled, so every minigame takes some thought.");
+            ui.end_row();
+            ui.label("Minigame mistakes");
+            ui.horizontal(|ui| {
+                for behavior in FailBehavior::iter() {
+                    ui.selectable_value(&mut self.minigames.fail_behavior, behavior, behavior.to_string());
+                }
+            })
+            .response
+            .on_hover_text("What a wrong click costs: the whole attempt, a few buttons of progress, or a short wait.");
+            ui.end_row();
+            match self.minigames.fail_behavior {
+                FailBehavior::Reset => {}
+                FailBehavior::Setback => {
+                    ui.label("Step back by");
+                    ui.add(egui::Slider::new(&mut self.minigames.setback_steps, 1..=10).suffix(" buttons"));
+                    ui.end_row();
+                }
+                FailBehavior::Lockout => {
+                    ui.label("Lock out for");
+                    ui.add(egui::Slider::new(&mut self.minigames.lockout_ms, 0..=10000).suffix(" ms"));
+                    ui.end_row();
+                }
+            }
+            ui.label("Minigame click interval");
+            ui.add(egui::Slider::new(&mut self.minigames.click_interval_ms, 0..=500).suffix(" ms"))
+                .on_hover_text("Clicks faster than this are ignored. They don't count as mistakes.");
+            ui.end_row();
+        });
+        // Each difficulty can have its own click interval, since harder minigames have more buttons to click through.
+        ui.collapsing("Click interval by difficulty", |ui| {
+            let difficulties: std::collections::BTreeSet<u32> = Good::group_iter(GoodGroup::Ore)
+                .map(|ore| ore.properties().difficulty)
+                .collect();
+            egui::Grid::new("click_interval_grid").show(ui, |ui| {
+                for difficulty in difficulties {
+                    ui.label(format!("{difficulty} buttons"));
+                    let mut overridden = self
+                        .minigames
+                        .click_interval_overrides
+                        .contains_key(&difficulty);
+                    if ui.checkbox(&mut overridden, "Custom").changed() {
+                        if overridden {
+                            self.minigames
+                                .click_interval_overrides
+                                .insert(difficulty, self.minigames.click_interval_ms);
+                        } else {
+                            self.minigames.click_interval_overrides.remove(&difficulty);
+                        }
+                    }
+                    if let Some(ms) = self.minigames.click_interval_overrides.get_mut(&difficulty) {
+                        ui.add(egui::Slider::new(ms, 0..=500).suffix(" ms"));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        let warning = self.palette().partial;
+        ui.collapsing("Keybinds", |ui| self.keybinds.ui(ui, warning));
+    }
+}
+
+// Where producers take their inputs from and put their outputs.
+// Buffers are opt-in, and Shared stays the default. Making buffers the only model would change how every existing save plays:
+// inputs would sit in buffers out of the player's reach, outputs would show up a tick late, and the production table, the solver
+// and the steady producer shortcut all work on what's in the inventory. Check Element::tick for how the two modes differ.
+#[derive(
+    serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Copy, EnumIter, Debug, Default,
+)]
+pub enum LogisticsMode {
+    #[default]
+    Shared, // Producers work directly on the player inventory. This is how the game has always worked.
+    Buffered, // Producers work on their own input and output buffers, which are filled and emptied from the player inventory every tick.
+              // Outputs that don't fit in the inventory back up in the output buffer, until it's full and the producer stalls.
+}
+
+impl LogisticsMode {
+    // A short explanation of the mode, for tooltips.
+    pub fn description(&self) -> &'static str {
+        match self {
+            LogisticsMode::Shared => {
+                "Producers take inputs from and put outputs straight into the inventory."
+            }
+            LogisticsMode::Buffered => {
+                "Producers keep a small buffer of inputs, refilled from the inventory every tick. Outputs back up when the inventory is full."
+            }
+        }
+    }
+}
+
+impl Display for LogisticsMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogisticsMode::Shared => write!(f, "Shared inventory"),
+            LogisticsMode::Buffered => write!(f, "Producer buffers"),
+        }
+    }
+}
+
+// How the sell tax is taken out of the sell price.
+#[derive(
+    serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Copy, EnumIter, Debug, Default,
+)]
+pub enum TaxKind {
+    #[default]
+    Percentage, // The tax is a fraction of the price, from 0 up to (but not including) 1.
+    Flat, // The tax is a fixed amount of money taken off every good sold.
+}
+
+impl Display for TaxKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaxKind::Percentage => write!(f, "Percentage"),
+            TaxKind::Flat => write!(f, "Flat"),
+        }
+    }
+}
+
+// How much more goods cost to buy than their base price, by default. Check EconomySettings::buy_price.
+fn default_buy_spread() -> F {
+    F::new(I::from(3), I::from(2))
+}
+
+// Knobs for tuning the economy without recompiling. These apply whenever goods are turned into money, or money into goods.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct EconomySettings {
+    pub price_multiplier: F, // Every good's base price is multiplied by this.
+    pub tax_kind: TaxKind,   // Whether the tax is a percentage or a flat amount.
+    pub tax: F, // The tax itself. A fraction for percentage taxes, or an amount of money for flat taxes. Check sell_price for more info.
+    pub buy_spread: F, // How many times the base price goods cost to buy. Never less than 1. Check buy_price for more info.
+}
+
+impl Default for EconomySettings {
+    fn default() -> Self {
+        Self {
+            price_multiplier: F::from(I::from(1)),
+            tax_kind: TaxKind::default(),
+            tax: F::from(I::from(0)),
+            buy_spread: default_buy_spread(),
+        }
+    }
+}
+
+impl EconomySettings {
+    // Calculates how much money a single good with the given base price sells for, after the multiplier and the tax.
+    // Percentage taxes are clamped to [0, 1), so selling always gets something. Flat taxes can't push the price below zero.
+    pub fn sell_price(&self, base_price: u32) -> F {
+        let zero = F::from(I::from(0));
+        let price = F::from(I::from(base_price)) * self.price_multiplier.clone().max(zero.clone());
+        match self.tax_kind {
+            TaxKind::Percentage => {
+                let max_tax = F::new(I::from(99), I::from(100));
+                let tax = self.tax.clone().clamp(zero, max_tax);
+                &price - &price * tax
+            }
+            TaxKind::Flat => (price - self.tax.clone().max(zero.clone())).max(zero),
+        }
+    }
+
+    // Calculates how much money a single good with the given base price costs to buy, after the multiplier and the spread.
+    // There's no tax on buying. The spread is clamped to at least 1, so buying a good always costs at least as much as selling it makes,
+    // and goods can't be bought and sold back over and over for free money.
+    pub fn buy_price(&self, base_price: u32) -> F {
+        let zero = F::from(I::from(0));
+        let spread = self.buy_spread.clone().max(F::from(I::from(1)));
+        F::from(I::from(base_price)) * self.price_multiplier.clone().max(zero) * spread
+    }
+}
+
+// Settings which change how the simulation works. These are part of the game state.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SimSettings {
+    pub logistics: LogisticsMode, // Where producers take their inputs from and put their outputs.
+    pub starvation_debounce: F, // How many seconds a producer has to stay starved (or fed) before the player is told about it.
+    pub notify_recovered: bool, // Whether the player is also told when a starved producer starts running again.
+    pub economy: EconomySettings, // Knobs for tuning the sell prices of goods.
+    pub normalize_places: u32, // How many decimal places big amounts are rounded to when the game state is normalized. Check GameState::normalize.
+    pub normalize_above: F, // How big an amount has to be before normalizing rounds it. Smaller amounts always stay exact.
+    pub auto_throttle: bool, // Whether producers slow down to match a scarce input, instead of stalling and starting over and over. Check GameState::throttles.
+    pub buffer_fill_rate: Option<F>, // How fast input buffers are refilled, in seconds of input per second. None refills them right away. Check Element::fill_input_buffer.
+}
+
+impl Default for SimSettings {
+    fn default() -> Self {
+        Self {
+            logistics: LogisticsMode::default(),
+            starvation_debounce: F::from(I::from(1)),
+            notify_recovered: true,
+            economy: EconomySettings::default(),
+            normalize_places: 6,
+            normalize_above: F::from(I::from(1000)),
+            auto_throttle: false,
+            buffer_fill_rate: None,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+/*
+This is the production solver. The theoretical production table assumes every producer runs flat out, which isn't true when a producer's inputs
+are made slower than it uses them. A coal drill fed by a gravity drill that only makes half the coal it needs runs at half speed, forever.
+The solver works out how fast every producer can actually keep running, once the stockpiles are gone, and what that makes of every good.
+
+It works by adjusting each producer's run rate until everything it uses is made at least as fast as it's used:
+- Every producer starts at its full rate (after its throughput cap).
+- Each good gets a supply ratio, which is how much of it is made over how much of it is used at the current rates.
+- Each producer's rate is then scaled by the worst supply ratio of its inputs, and capped at its full rate. Goods that nothing makes have a ratio of 0.
+- That changes what gets made and used, so it goes again, until nothing changes.
+Goods running short are shared out between their consumers in proportion to how much each one uses.
+The demand is worked out again every round, from the current rates. A consumer held back by some other input only claims what it actually uses,
+so whatever it leaves goes to the other consumers, and their rates go back up. A smelter starved of ore doesn't hold back a drill burning the same coal.
+Chains settle in about as many rounds as they are long.
+Cycles (a producer that needs, somewhere up the chain, what it makes itself) can keep shrinking a little every round without ever stopping.
+Those are cut off after MAX_ROUNDS, and the solution says it didn't settle, so the inventory can warn that the numbers are approximate.
+ */
+
+use crate::idle::goods::{Good, Inventory};
+use num::{BigInt, BigRational};
+use std::collections::HashMap;
+
+type F = BigRational;
+type I = BigInt;
+
+// How many rounds the solver goes before giving up on a cycle. Check the top of this file for more info.
+const MAX_ROUNDS: usize = 64;
+
+// What one producer makes and uses per second, at its full rate.
+pub(crate) struct Flow {
+    pub outputs: Inventory,
+    pub inputs: Inventory,
+}
+
+// What the solver worked out.
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub table: HashMap<Good, (F, F)>, // The sustainable production table, as (produced, consumed), just like the theoretical one.
+    pub settled: bool, // False if the solver gave up before the rates stopped changing. Only happens with cycles.
+}
+
+// Adds up what the flows make and use, with each one running at the given fraction of its full rate.
+fn table(flows: &[Flow], rates: &[F]) -> HashMap<Good, (F, F)> {
+    let mut table: HashMap<Good, (F, F)> = HashMap::new();
+    for (flow, rate) in flows.iter().zip(rates) {
+        for (good, amount) in flow.outputs.iter() {
+            table.entry(*good).or_default().0 += amount * rate;
+        }
+        for (good, amount) in flow.inputs.iter() {
+            table.entry(*good).or_default().1 += amount * rate;
+        }
+    }
+    table
+}
+
+// Works out the sustainable production table for the given flows. Check the top of this file for how.
+pub(crate) fn solve(flows: &[Flow]) -> Solution {
+    let (zero, one) = (F::from(I::from(0)), F::from(I::from(1)));
+    let mut rates = vec![one.clone(); flows.len()];
+    for _ in 0..MAX_ROUNDS {
+        // What's made and used at the current rates. The used side is the demand, so it follows the consumers as they slow down or speed up.
+        let current = table(flows, &rates);
+        let ratio = |good: &Good| {
+            let (made, used) = current
+                .get(good)
+                .map_or((&zero, &zero), |(made, used)| (made, used));
+            if used > &zero {
+                made / used
+            } else {
+                one.clone()
+            }
+        };
+        let next: Vec<F> = flows
+            .iter()
+            .zip(rates.iter())
+            .map(|(flow, rate)| {
+                let supply = flow.inputs.keys().map(ratio).min();
+                supply.map_or_else(|| one.clone(), |supply| (rate * supply).min(one.clone()))
+            })
+            .collect();
+        if next == rates {
+            return Solution {
+                table: current,
+                settled: true,
+            };
+        }
+        rates = next;
+    }
+    Solution {
+        table: table(flows, &rates),
+        settled: false,
+    }
+}
@@ -0,0 +1,57 @@
+/*
+This is the sound code. The game asks for a sound by its cue ("the minigame was solved"), and doesn't care how it gets played.
+That way nothing else depends on an audio backend, and the minigames don't even know sounds exist. The UI plays the cues for them.
+There's no audio backend yet, so every cue goes to the silent player, which does nothing. This is also what wasm will use if audio isn't available.
+A real backend (rodio on native, web audio on wasm) only has to implement SoundPlayer and be returned from default_player.
+rodio goes through cpal, which needs the ALSA development headers to build on Linux, so adding it means a new system dependency for everyone.
+Until that's worth it, the sound settings are left out of the settings window, so there's no switch that does nothing.
+ */
+
+use crate::idle::settings::SoundSettings;
+
+// Something that happened which has a sound.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Cue {
+    Solved,  // An ore minigame was solved.
+    Mistake, // A wrong button was clicked in an ore minigame, even if it was forgiven.
+    Denied, // Something the player asked for couldn't be done, like quick-buying with nothing affordable.
+}
+
+// Plays sounds. The volume goes from 0 (silent) to 1 (full volume).
+pub trait SoundPlayer {
+    fn play(&mut self, cue: Cue, volume: f32);
+}
+
+// A sound player which doesn't play anything.
+pub struct Silent;
+
+impl SoundPlayer for Silent {
+    fn play(&mut self, _cue: Cue, _volume: f32) {}
+}
+
+// The sound player the game uses on this platform.
+fn default_player() -> Box<dyn SoundPlayer> {
+    Box::new(Silent)
+}
+
+// Plays cues through the sound player, if the player has sound turned on.
+pub struct Sounds {
+    player: Box<dyn SoundPlayer>,
+}
+
+impl Default for Sounds {
+    fn default() -> Self {
+        Self {
+            player: default_player(),
+        }
+    }
+}
+
+impl Sounds {
+    // Plays a cue, at the volume from the settings. Does nothing if sound is turned off.
+    pub fn play(&mut self, cue: Cue, settings: &SoundSettings) {
+        if settings.enabled && settings.volume > 0.0 {
+            self.player.play(cue, settings.volume.clamp(0.0, 1.0));
+        }
+    }
+}
@@ -0,0 +1,88 @@
+/*
+This is the stats code. Stats are records of how the player has been doing, kept across the whole game.
+Right now they cover the ore minigames (how many times each ore has been mined by hand, and the fastest solve) and how many producers were built.
+They live in the game state, so they're saved with it, and resetting the game clears them. Achievements are worked out from them too.
+
+Solve times are measured from the first accepted click to the last one, so time spent reading the buttons doesn't count.
+The metallurgy tab uses them to estimate how much ore per minute each minigame is worth. Check ores_per_minute.
+ */
+
+use crate::idle::goods::Good;
+use egui::Ui;
+use std::collections::BTreeMap;
+
+// How long a player takes to find and click a button, in seconds, when there's no recorded time to go off.
+// A guess at a fairly quick player. Anyone faster will beat it and get a recorded time soon enough.
+const ESTIMATED_CLICK_SECONDS: f64 = 0.4;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Stats {
+    pub solves: BTreeMap<Good, u64>, // How many times each ore minigame has been solved.
+    pub best_solve: BTreeMap<Good, f64>, // The fastest solve of each ore minigame, in seconds.
+    pub producers_built: u64,        // How many producers have finished construction.
+    #[serde(skip)]
+    revision: u64, // Counts the changes to the stats. Used to cache things worked out from them. Check AchievementCache.
+}
+
+impl Stats {
+    // Goes up by one whenever the stats change. Fresh and loaded stats both start at 0, so anything cached on the revision
+    // has to start over when the stats are swapped for some other stats. Check IdleGame::replace_game_state.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    // Records a solve of an ore's minigame, and keeps the time if it's the fastest one yet.
+    pub fn record_solve(&mut self, ore: Good, seconds: f64) {
+        *self.solves.entry(ore).or_insert(0) += 1;
+        let best = self.best_solve.entry(ore).or_insert(seconds);
+        if seconds < *best {
+            *best = seconds;
+        }
+        self.revision += 1;
+    }
+
+    // Records a producer finishing construction.
+    pub fn record_built(&mut self) {
+        self.producers_built += 1;
+        self.revision += 1;
+    }
+
+    // Renders every stat, for the achievements tab.
+    pub fn ui(&self, ui: &mut Ui) {
+        egui::Grid::new("stats_grid").show(ui, |ui| {
+            ui.label("Producers built");
+            ui.label(self.producers_built.to_string());
+            ui.end_row();
+            for (ore, solves) in self.solves.iter() {
+                ui.label(format!("{ore} mined by hand"));
+                ui.label(solves.to_string());
+                ui.end_row();
+            }
+            for (ore, seconds) in self.best_solve.iter() {
+                ui.label(format!("Fastest {ore} solve"));
+                ui.label(format!("{seconds:.2}s"));
+                ui.end_row();
+            }
+        });
+    }
+}
+
+// How long a solve of a minigame is expected to take, in seconds.
+// The best recorded time is used if there is one. Otherwise every button takes ESTIMATED_CLICK_SECONDS,
+// or the click interval if that's longer, since clicks can't come faster than it.
+// The first click starts the clock, so a solve is one click shorter than the number of buttons.
+pub fn expected_solve_seconds(difficulty: u32, click_interval: f64, best: Option<f64>) -> f64 {
+    best.unwrap_or_else(|| {
+        f64::from(difficulty.saturating_sub(1)) * click_interval.max(ESTIMATED_CLICK_SECONDS)
+    })
+}
+
+// How much ore a minute of solving a minigame should get, if every solve takes the expected time. Check expected_solve_seconds.
+// A solve that takes no time at all (a single button) is capped by the click interval instead, so this never divides by zero.
+pub fn ores_per_minute(difficulty: u32, click_interval: f64, best: Option<f64>) -> f64 {
+    let seconds = expected_solve_seconds(difficulty, click_interval, best)
+        .max(click_interval)
+        .max(f64::EPSILON);
+    60.0 / seconds
+}
@@ -0,0 +1,53 @@
+/*
+This is the production target code. A good can have a target stockpile, and the game turns the good's producers off and on to hold it there.
+When there's at least the target, the least important producer of the good that's still running is paused.
+When there's less than the target, the most important producer the target paused is turned back on.
+Only one producer changes per check, and the checks are a second apart, so the producers ease on and off instead of all flipping at once.
+That also means fewer producers are eating inputs while the good is full, instead of them all running flat out until storage caps them.
+
+Importance is the producer's priority, set in its window. Ties go to the oldest producer, so the newest is paused first.
+A target only ever turns back on the producers it paused itself. Producers the player turned off stay off.
+Targets are opt in, per good. Goods without one are never managed.
+ */
+
+use num::{BigInt, BigRational};
+use std::collections::BTreeSet;
+
+type F = BigRational;
+type I = BigInt;
+
+// How often the targets are checked, in seconds.
+fn target_interval() -> F {
+    F::from(I::from(1))
+}
+
+// A target stockpile for a single good.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProductionTarget {
+    pub target: F,               // How much of the good to hold.
+    pub paused: BTreeSet<usize>, // The ids of the producers this target has paused, so only those are turned back on.
+    pub timer: F,                // How long it's been since the last check.
+}
+
+impl Default for ProductionTarget {
+    fn default() -> Self {
+        Self {
+            target: F::from(I::from(0)),
+            paused: BTreeSet::new(),
+            timer: F::from(I::from(0)),
+        }
+    }
+}
+
+impl ProductionTarget {
+    // Moves the timer along by a tick. Returns whether it's time to check the target.
+    pub fn tick(&mut self, tick_rate: &F) -> bool {
+        self.timer += tick_rate;
+        if self.timer < target_interval() {
+            return false;
+        }
+        self.timer -= target_interval();
+        true
+    }
+}
@@ -0,0 +1,89 @@
+/*
+This is the data validation code. All the goods and producers are databased in their properties functions, which the compiler can't check.
+This goes over every good and every producer, and makes sure their properties make sense.
+It's run from the command line with --validate, so it can be checked without opening the game. Check main.rs for that.
+ */
+
+use crate::idle::goods::Good;
+use crate::idle::producers::Producer;
+use num::{BigInt, BigRational};
+use strum::IntoEnumIterator;
+
+type F = BigRational;
+type I = BigInt;
+
+// Every producer in the game. Producer::None isn't buildable, but it still has properties, so it's checked too.
+fn all_producers() -> Vec<Producer> {
+    let mut producers = vec![Producer::None];
+    producers.extend(Producer::buildable());
+    producers
+}
+
+// Checks every good and producer, and returns a description of every problem found. No problems means the data is fine.
+pub fn validate_data() -> Vec<String> {
+    let zero = F::from(I::from(0));
+    let goods: Vec<Good> = Good::iter().collect();
+    let mut problems = Vec::new();
+
+    // Inventories are iterated in the order the goods are declared, and shown in that order without sorting. Check goods::Inventory.
+    if goods != Good::sorted() {
+        problems.push(String::from(
+            "Goods aren't declared grouped in their groups' order, so inventories won't show up sorted",
+        ));
+    }
+
+    for good in goods.iter() {
+        let properties = good.properties();
+        if properties.name.is_empty() {
+            problems.push(format!("{good:?} has no name"));
+        }
+        if properties.capacity == Some(0) {
+            problems.push(format!(
+                "{good} has a capacity of zero, so it can never be held"
+            ));
+        }
+    }
+
+    for producer in all_producers() {
+        let properties = producer.properties();
+        if properties.cost < zero {
+            problems.push(format!(
+                "{producer} has a negative cost ({})",
+                properties.cost
+            ));
+        }
+        if properties.warmup < zero {
+            problems.push(format!(
+                "{producer} has a negative warmup ({})",
+                properties.warmup
+            ));
+        }
+        for (kind, flows) in [
+            ("input", &properties.recipe.inputs),
+            ("output", &properties.recipe.outputs),
+        ] {
+            for (good, rate) in flows.iter() {
+                if !goods.contains(good) {
+                    problems.push(format!(
+                        "{producer} has an {kind} of {good:?}, which isn't a good"
+                    ));
+                }
+                if *rate < zero {
+                    problems.push(format!(
+                        "{producer} has a negative {kind} rate for {good} ({rate})"
+                    ));
+                }
+            }
+        }
+        // A producer that makes exactly as much of a good as it uses does nothing with it, which is almost certainly a mistake.
+        for (good, output) in properties.recipe.outputs.iter() {
+            if properties.recipe.inputs.get(good) == Some(output) {
+                problems.push(format!(
+                    "{producer} both consumes and produces {good} at {output}/s, so it has no effect on it"
+                ));
+            }
+        }
+    }
+
+    problems
+}
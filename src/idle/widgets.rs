@@ -0,0 +1,176 @@
+/*
+This is the custom widget code. egui's sliders and drag values only work with floats and primitive integers,
+but the game stores everything as BigInt or BigRational, so big values would get mangled going through an f64.
+NumericEdit works on the big number types directly. It has -/+ buttons, a text box for typing a value, and the mouse wheel works over it.
+
+The UI's helpers for showing goods are in here too, so goods.rs doesn't need egui.
+ */
+
+use crate::idle::goods::Good;
+use egui::{Response, RichText, Ui, Widget};
+use num::{BigInt, BigRational, Zero};
+use std::str::FromStr;
+
+type F = BigRational;
+type I = BigInt;
+
+// The good's name, tinted with its group's color. Used wherever goods are listed in the UI.
+pub(crate) fn good_text(good: Good) -> RichText {
+    let (r, g, b) = good.properties().group.properties().color;
+    RichText::new(good.properties().name).color(egui::Color32::from_rgb(r, g, b))
+}
+
+// A number type NumericEdit can edit. It needs to be parsed from and shown as text, and to be stepped up and down.
+pub trait NumericValue:
+    Clone + Ord + std::ops::Add<Output = Self> + std::ops::Sub<Output = Self> + 'static + Send + Sync
+{
+    // Parses a typed value. Returns None if the text isn't a valid number.
+    fn parse(text: &str) -> Option<Self>;
+    // Shows the value as text, in a form parse can read back.
+    fn display(&self) -> String;
+}
+
+impl NumericValue for I {
+    fn parse(text: &str) -> Option<Self> {
+        I::from_str(text.trim()).ok()
+    }
+
+    fn display(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl NumericValue for F {
+    // Rationals can be typed as integers ("3"), fractions ("3/4"), or decimals ("0.75"). All of them are exact.
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if let Some((numerator, denominator)) = text.split_once('/') {
+            let denominator = I::from_str(denominator.trim()).ok()?;
+            if denominator.is_zero() {
+                return None;
+            }
+            return Some(F::new(I::from_str(numerator.trim()).ok()?, denominator));
+        }
+        if let Some((whole, fraction)) = text.split_once('.') {
+            let negative = whole.starts_with('-');
+            let whole = if whole.is_empty() || whole == "-" {
+                I::zero()
+            } else {
+                I::from_str(whole).ok()?
+            };
+            if fraction.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let scale = I::from(10).pow(fraction.len() as u32);
+            let fraction = F::new(I::from_str(fraction).ok()?, scale);
+            let whole = F::from(whole);
+            return Some(if negative {
+                whole - fraction
+            } else {
+                whole + fraction
+            });
+        }
+        I::from_str(text).ok().map(F::from)
+    }
+
+    fn display(&self) -> String {
+        self.to_string()
+    }
+}
+
+// A text box for a big number, with -/+ buttons on either side. Scrolling the mouse wheel over it steps it too.
+// The typed text is only applied when the text box loses focus, so half-typed numbers don't get applied.
+pub struct NumericEdit<'a, T: NumericValue> {
+    id_source: &'a str,
+    value: &'a mut T,
+    step: T,
+    min: Option<T>,
+    prefix: &'a str,
+}
+
+impl<'a, T: NumericValue> NumericEdit<'a, T> {
+    // The id source has to be unique within the ui, since it's what the typed text is remembered by.
+    pub fn new(id_source: &'a str, value: &'a mut T, step: T) -> Self {
+        Self {
+            id_source,
+            value,
+            step,
+            min: None,
+            prefix: "",
+        }
+    }
+
+    // Keeps the value from going below the minimum.
+    pub fn min(mut self, min: T) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    // Text shown before the widget, like "Keep: ".
+    pub fn prefix(mut self, prefix: &'a str) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    // Clamps a value to the minimum, if there is one.
+    fn clamp(&self, value: T) -> T {
+        match &self.min {
+            Some(min) if value < *min => min.clone(),
+            _ => value,
+        }
+    }
+}
+
+impl<'a, T: NumericValue> Widget for NumericEdit<'a, T> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let id = ui.make_persistent_id(self.id_source);
+        let mut new_value = None;
+        let mut inner = ui.horizontal(|ui| {
+            if !self.prefix.is_empty() {
+                ui.label(self.prefix);
+            }
+            if ui.small_button("-").clicked() {
+                new_value = Some(self.value.clone() - self.step.clone());
+            }
+            // While the text box is being typed in, the text lives in egui's memory. Otherwise it just shows the value.
+            let mut text = ui
+                .data()
+                .get_temp::<String>(id)
+                .unwrap_or_else(|| self.value.display());
+            let text_response = ui.add(egui::TextEdit::singleline(&mut text).desired_width(80.0));
+            if text_response.has_focus() {
+                ui.data().insert_temp(id, text.clone());
+            } else {
+                ui.data().remove::<String>(id);
+            }
+            if text_response.lost_focus() {
+                // Text that isn't a number is thrown away, and the box goes back to showing the value.
+                if let Some(parsed) = T::parse(&text) {
+                    new_value = Some(parsed);
+                }
+            }
+            if ui.small_button("+").clicked() {
+                new_value = Some(self.value.clone() + self.step.clone());
+            }
+        });
+        // Scrolling up steps the value up, and scrolling down steps it down.
+        // The scroll is used up here, so a scroll area the widget is in doesn't scroll at the same time.
+        if inner.response.hovered() {
+            let scroll = ui.input().scroll_delta.y;
+            if scroll > 0.0 {
+                new_value = Some(self.value.clone() + self.step.clone());
+            } else if scroll < 0.0 {
+                new_value = Some(self.value.clone() - self.step.clone());
+            }
+            ui.input_mut().scroll_delta = egui::Vec2::ZERO;
+        }
+        if let Some(value) = new_value {
+            let value = self.clamp(value);
+            if value != *self.value {
+                *self.value = value;
+                inner.response.mark_changed();
+            }
+        }
+        inner.response
+    }
+}
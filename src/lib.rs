@@ -3,3 +3,18 @@
 mod idle;
 
 pub use idle::IdleGame;
+
+// The simulation, without any of the UI. Check idle/mod.rs for more info.
+pub use idle::{
+    coarse_backlog_threshold, construction_time, decode_compact, encode_compact,
+    expected_solve_seconds, format_exact, format_good_amount, format_si, inversions,
+    min_inversions, ores_per_minute, progress_all, recipes_for, round_money, shuffled_order,
+    validate_data, Achievement, AchievementProgress, AlarmLevel, AutoSellRule, BuildOrder,
+    ChainPlan, Clock, EconomySettings, ElemVariant, Element, FactoryPlan, FailBehavior, GameEvent,
+    GameState, Good, GoodAlarm, GoodDefinition, GoodGroup, GoodId, GoodProperties, GroupProperties,
+    InsufficientFunds, Inventory, LogisticsMode, MigrationReport, MinigameSettings, NumericValue,
+    OreMinigame, PasteError, PlanError, Producer, ProducerCategory, ProducerConfig,
+    ProducerDefinition, ProducerId, ProducerProperties, ProductionTarget, Recipe, RegistryError,
+    SaveChange, SaveError, SellPriority, SimSettings, Solution, Stats, StorageError, StorageLevels,
+    SystemClock, TaxKind, MAX_PLAN_PRODUCERS,
+};
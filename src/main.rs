@@ -7,9 +7,30 @@ Not to mention, it's not even my code, so I can't even provide my own commentary
 Entirely because I have no idea what it does or what I can say about it.
  */
 
+// Checks the goods and producers data, and prints a report. Returns the exit code, which is nonzero if anything is wrong.
+// Check idle/validate.rs for what's checked.
+#[cfg(not(target_arch = "wasm32"))]
+fn validate() -> i32 {
+    let problems = dull_idle_game::validate_data();
+    if problems.is_empty() {
+        println!("All goods and producers are valid.");
+        return 0;
+    }
+    println!("Found {} problems:", problems.len());
+    for problem in problems.iter() {
+        println!("  - {problem}");
+    }
+    1
+}
+
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
+    // Running with --validate checks the data and exits, without ever opening a window.
+    if std::env::args().any(|arg| arg == "--validate") {
+        std::process::exit(validate());
+    }
+
     // Log to stdout (if you run with `RUST_LOG=debug`).
     tracing_subscriber::fmt::init();
 
@@ -0,0 +1,55 @@
+// Tests for achievements, which are worked out from the stats.
+
+mod common;
+
+use common::{int, tick_rate};
+use dull_idle_game::{construction_time, progress_all, Achievement, GameState, Good, Producer};
+
+fn progress_of(state: &GameState, achievement: Achievement) -> (u64, u64) {
+    let progress = progress_all(state.stats());
+    let p = progress
+        .iter()
+        .find(|p| p.achievement == achievement)
+        .unwrap();
+    (p.progress, p.target)
+}
+
+#[test]
+fn mining_by_hand_counts_towards_the_mining_achievements() {
+    let mut state = GameState::new(Some(0));
+    for _ in 0..3 {
+        state.record_solve(Good::IronOre, 4.0, &int(1));
+    }
+    state.record_solve(Good::GoldOre, 4.0, &int(1));
+    assert_eq!(progress_of(&state, Achievement::FirstOre), (1, 1));
+    assert_eq!(progress_of(&state, Achievement::Miner), (4, 100));
+    assert_eq!(progress_of(&state, Achievement::SpeedyFingers), (0, 1));
+    state.record_solve(Good::IronOre, 1.0, &int(1));
+    assert_eq!(progress_of(&state, Achievement::SpeedyFingers), (1, 1));
+}
+
+#[test]
+fn finished_builds_count_towards_the_building_achievements() {
+    let mut state = GameState::new(Some(0));
+    let producer = Producer::GravityDrill(Good::IronOre);
+    state.add_good(Good::Money, &producer.properties().cost);
+    state.try_buy_producer(producer).unwrap();
+    // Bought, but not built yet.
+    assert_eq!(progress_of(&state, Achievement::Builder), (0, 1));
+    state.advance(&construction_time(&producer), &tick_rate());
+    assert_eq!(progress_of(&state, Achievement::Builder), (1, 1));
+    assert_eq!(progress_of(&state, Achievement::Industrialist), (1, 50));
+}
+
+#[test]
+fn changing_the_stats_changes_the_revision() {
+    let mut state = GameState::new(Some(0));
+    let before = state.stats().revision();
+    state.record_solve(Good::IronOre, 4.0, &int(1));
+    assert_eq!(state.stats().revision(), before + 1);
+    // Every game state counts its own changes, so a fresh one starts over, and isn't touched by changes to this one.
+    let other = GameState::new(Some(0));
+    assert_eq!(other.stats().revision(), 0);
+    state.record_solve(Good::IronOre, 4.0, &int(1));
+    assert_eq!(other.stats().revision(), 0);
+}
@@ -0,0 +1,58 @@
+// Tests for the good alarms. Check alarms.rs for how they work.
+
+mod common;
+
+use common::{coal_drill_game, int, tick_rate, F, I};
+use dull_idle_game::{AlarmLevel, GameEvent, GameState, Good, GoodAlarm};
+
+// A game with a single iron coal drill, 10 coal, and a low alarm on coal at 5.
+// The drill burns 1/4 coal per second, so coal drops below 5 after 20 seconds.
+fn low_coal_game(pause_producers: bool) -> GameState {
+    let mut state = coal_drill_game(10);
+    state.set_alarm(
+        Good::Coal,
+        GoodAlarm {
+            alarm_low: Some(int(5)),
+            pause_producers,
+            ..GoodAlarm::default()
+        },
+    );
+    state
+}
+
+#[test]
+fn low_alarm_goes_off_once_and_pauses_consumers() {
+    let mut state = low_coal_game(true);
+    state.advance(&int(30), &tick_rate());
+    let raised: Vec<GameEvent> = state
+        .drain_events()
+        .into_iter()
+        .filter(|event| matches!(event, GameEvent::AlarmRaised(..)))
+        .collect();
+    assert_eq!(
+        raised,
+        vec![GameEvent::AlarmRaised(
+            Good::Coal.to_string(),
+            AlarmLevel::Low,
+            1
+        )]
+    );
+    assert_eq!(state.alarm(Good::Coal).active, Some(AlarmLevel::Low));
+    // The drill was paused, so the coal stops going down.
+    let coal = state.inventory()[&Good::Coal].clone();
+    assert!(coal > int(4));
+    state.advance(&int(30), &tick_rate());
+    assert_eq!(state.inventory()[&Good::Coal], coal);
+}
+
+#[test]
+fn alarm_without_pausing_lets_producers_run() {
+    let mut state = low_coal_game(false);
+    state.advance(&int(30), &tick_rate());
+    assert_eq!(state.alarm(Good::Coal).active, Some(AlarmLevel::Low));
+    // 30 seconds at 1/4 coal per second.
+    assert_eq!(
+        state.inventory()[&Good::Coal],
+        F::new(I::from(5), I::from(2))
+    );
+}
@@ -0,0 +1,44 @@
+// Tests for auto-build, which keeps buying producers up to a target count. Check build.rs for more info.
+
+mod common;
+
+use common::{int, tick_rate};
+use dull_idle_game::{GameState, Good, Producer};
+
+fn owned(state: &GameState, producer: Producer) -> usize {
+    let built = state
+        .producer_breakdown()
+        .get(&producer)
+        .copied()
+        .unwrap_or(0);
+    built
+        + state
+            .build_queue()
+            .iter()
+            .filter(|order| order.producer == producer)
+            .count()
+}
+
+#[test]
+fn auto_build_stops_at_the_target() {
+    let mut state = GameState::new(Some(0));
+    let producer = Producer::CoalDrill(Good::IronOre);
+    state.add_good(Good::Money, &int(100));
+    state.set_auto_build_target(producer, 3);
+    state.advance(&int(60), &tick_rate());
+    assert_eq!(owned(&state, producer), 3);
+    // Coal drills cost $10 each.
+    assert_eq!(state.money(), int(70));
+}
+
+#[test]
+fn paused_auto_build_buys_nothing() {
+    let mut state = GameState::new(Some(0));
+    let producer = Producer::CoalDrill(Good::IronOre);
+    state.add_good(Good::Money, &int(100));
+    state.set_auto_build_target(producer, 3);
+    state.set_auto_build_paused(true);
+    state.advance(&int(60), &tick_rate());
+    assert_eq!(owned(&state, producer), 0);
+    assert_eq!(state.money(), int(100));
+}
@@ -0,0 +1,73 @@
+// Tests for auto-selling, and who gets a good first when it's both auto-sold and consumed by producers.
+
+mod common;
+
+use common::{add_producer, coal_drill_game, int, tick_rate, F, I};
+use dull_idle_game::{AutoSellRule, GameState, Good, Producer, SellPriority};
+
+// A game with 10 coal, a coal drill burning it, and coal set to auto-sell with the given priority.
+fn coal_market_game(priority: SellPriority) -> GameState {
+    let mut state = coal_drill_game(10);
+    state.set_auto_sell_rule(
+        Good::Coal,
+        AutoSellRule {
+            enabled: true,
+            keep: int(0),
+            priority,
+        },
+    );
+    state
+}
+
+#[test]
+fn produce_first_feeds_the_drill_before_selling_coal() {
+    let mut state = coal_market_game(SellPriority::ProduceFirst);
+    state.advance(&int(1), &tick_rate());
+    // The drill ran the whole second, and burned 1/4 coal doing it.
+    assert_eq!(state.inventory()[&Good::IronOre], int(1));
+    // Only whole coal is sold, and a tick's worth is always held back for the drill.
+    assert_eq!(
+        state.inventory()[&Good::Coal],
+        F::new(I::from(3), I::from(4))
+    );
+    assert_eq!(state.money(), int(9) * state.sell_price(Good::Coal));
+}
+
+#[test]
+fn sell_first_sells_the_coal_out_from_under_the_drill() {
+    let mut state = coal_market_game(SellPriority::SellFirst);
+    state.advance(&int(1), &tick_rate());
+    assert_eq!(state.inventory()[&Good::IronOre], int(0));
+    assert_eq!(state.inventory()[&Good::Coal], int(0));
+    assert_eq!(state.money(), int(10) * state.sell_price(Good::Coal));
+}
+
+#[test]
+fn auto_selling_shows_up_as_money_per_second() {
+    let mut state = GameState::new(Some(0));
+    add_producer(&mut state, Producer::GravityDrill(Good::IronOre));
+    assert!(!state
+        .production_table_theoretical()
+        .contains_key(&Good::Money));
+    state.set_auto_sell_rule(
+        Good::IronOre,
+        AutoSellRule {
+            enabled: true,
+            keep: int(0),
+            priority: SellPriority::ProduceFirst,
+        },
+    );
+    let (made, used) = state.production_table_theoretical()[&Good::Money].clone();
+    assert!(made > int(0));
+    assert_eq!(made, state.sell_price(Good::IronOre));
+    assert_eq!(used, int(0));
+}
+
+#[test]
+fn only_the_surplus_counts_as_money() {
+    // The drill burns 1/4 coal a second, and nothing makes coal, so there's no surplus to sell.
+    let state = coal_market_game(SellPriority::ProduceFirst);
+    assert!(!state
+        .production_table_theoretical()
+        .contains_key(&Good::Money));
+}
@@ -0,0 +1,94 @@
+// Tests for producers' input buffers, with buffered logistics. Check Element::fill_input_buffer for more info.
+
+mod common;
+
+use common::{build, build_configured, int, tick_rate, F, I};
+use dull_idle_game::{GameState, Good, LogisticsMode, Producer, ProducerConfig};
+
+// A coal drill with an input buffer of the given size, on buffered logistics, with plenty of coal.
+// It uses 1/4 coal a second.
+fn buffered_drill(buffer_seconds: F) -> GameState {
+    let mut state = GameState::new(Some(0));
+    build_configured(
+        &mut state,
+        ProducerConfig {
+            producer: Producer::CoalDrill(Good::IronOre),
+            buffer_seconds,
+            ..ProducerConfig::default()
+        },
+    );
+    state.set_logistics_mode(LogisticsMode::Buffered);
+    state.add_good(Good::Coal, &int(100));
+    state
+}
+
+// The buffer levels of the only producer in the game.
+fn levels(state: &GameState) -> Vec<(Good, F, F)> {
+    state.elements().values().next().unwrap().buffer_levels()
+}
+
+#[test]
+fn buffers_fill_up_to_their_size_right_away() {
+    let mut state = buffered_drill(int(8));
+    state.advance(&tick_rate(), &tick_rate());
+    // 8 seconds of 1/4 coal is 2 coal. The tick then used a twentieth of a second of it.
+    let used = F::new(I::from(1), I::from(80));
+    assert_eq!(levels(&state), vec![(Good::Coal, int(2) - &used, int(2))]);
+    assert_eq!(state.inventory()[&Good::Coal], int(98));
+}
+
+#[test]
+fn limited_fill_rate_fills_buffers_gradually() {
+    let mut state = buffered_drill(int(5));
+    // Two seconds of input per second, while the drill only uses one, so the buffer gains 1/4 coal a second.
+    state.set_buffer_fill_rate(Some(int(2)));
+    state.advance(&int(2), &tick_rate());
+    let quarter = F::new(I::from(1), I::from(4));
+    assert_eq!(
+        levels(&state),
+        vec![(
+            Good::Coal,
+            quarter.clone() * int(2),
+            quarter.clone() * int(5)
+        )]
+    );
+    // Once it's full, it only takes what's used.
+    state.advance(&int(10), &tick_rate());
+    assert_eq!(
+        levels(&state)[0].1,
+        quarter * int(5) - F::new(I::from(1), I::from(80))
+    );
+}
+
+// A gravity drill on iron ore, which needs no inputs, on buffered logistics.
+fn buffered_iron_drill() -> GameState {
+    let mut state = GameState::new(Some(0));
+    build(&mut state, Producer::GravityDrill(Good::IronOre));
+    state.set_logistics_mode(LogisticsMode::Buffered);
+    state
+}
+
+#[test]
+fn outputs_back_up_when_the_inventory_is_full() {
+    let mut state = buffered_iron_drill();
+    let capacity = Good::IronOre.capacity(&Default::default()).unwrap();
+    let missing = capacity.clone() - &state.inventory()[&Good::IronOre];
+    state.add_good(Good::IronOre, &missing);
+    // The drill makes 1 iron ore a second, and the buffer holds 5 seconds of it, so it fills up and then the drill stalls.
+    state.advance(&int(10), &tick_rate());
+    let element = state.elements().values().next().unwrap();
+    assert_eq!(
+        element.output_levels(),
+        vec![(Good::IronOre, int(5), int(5))]
+    );
+    assert_eq!(state.inventory()[&Good::IronOre], capacity);
+    // Once there's room again, the backed up ore goes into the inventory first.
+    assert!(state.remove_good(Good::IronOre, &int(100)));
+    state.advance(&tick_rate(), &tick_rate());
+    let element = state.elements().values().next().unwrap();
+    assert!(element.output_levels().is_empty());
+    assert_eq!(
+        state.inventory()[&Good::IronOre],
+        capacity - int(95) + F::new(I::from(1), I::from(20))
+    );
+}
@@ -0,0 +1,41 @@
+// Tests for building and deleting producers in bulk, like the shift and ctrl clicks do.
+
+mod common;
+
+use common::{add_producer, int};
+use dull_idle_game::{GameState, Good, Producer};
+
+#[test]
+fn buying_many_stops_when_the_money_runs_out() {
+    let mut state = GameState::new(Some(0));
+    let producer = Producer::GravityDrill(Good::Coal);
+    let cost = producer.properties().cost;
+    // Enough for three and a bit.
+    state.add_good(Good::Money, &(&cost * int(3) + int(1)));
+    assert_eq!(state.try_buy_many(producer, 10), Ok(3));
+    assert_eq!(state.build_queue().len(), 3);
+    assert!(state.try_buy_many(producer, 10).is_err());
+    assert_eq!(state.money(), int(1));
+}
+
+#[test]
+fn removing_similar_only_takes_the_same_producer_in_the_same_region() {
+    let mut state = GameState::new(Some(0));
+    let coal = Producer::GravityDrill(Good::Coal);
+    let iron = Producer::GravityDrill(Good::IronOre);
+    let mut ids = Vec::new();
+    for producer in [coal, coal, iron, coal, coal] {
+        ids.push(add_producer(&mut state, producer));
+    }
+    // The last coal drill is somewhere else, so it isn't like the others.
+    state.move_to_region(ids[4], Some(String::from("East")));
+    assert_eq!(state.remove_similar(ids[0], 10), 3);
+    let left: Vec<usize> = state.elements().keys().copied().collect();
+    assert_eq!(left, vec![ids[2], ids[4]]);
+    // Asking for fewer than there are removes the clicked one first, then the newest.
+    let mut state = GameState::new(Some(0));
+    let ids: Vec<usize> = (0..4).map(|_| add_producer(&mut state, coal)).collect();
+    assert_eq!(state.remove_similar(ids[0], 2), 2);
+    let left: Vec<usize> = state.elements().keys().copied().collect();
+    assert_eq!(left, vec![ids[1], ids[2]]);
+}
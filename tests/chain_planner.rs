@@ -0,0 +1,82 @@
+// Tests for the "build the whole chain" planner. Check chain.rs for more info.
+
+mod common;
+
+use common::{int, tick_rate, F, I};
+use dull_idle_game::{construction_time, ChainPlan, GameState, Good, Producer};
+use std::collections::BTreeMap;
+
+#[test]
+fn chains_include_what_feeds_them() {
+    let plan = ChainPlan::new(Good::IronOre, &int(5));
+    // 5 iron drills use 5/4 coal a second. Coal drills on coal make 3/4 of a coal a second after feeding themselves, so it takes 2.
+    assert_eq!(
+        plan.producers,
+        BTreeMap::from([
+            (Producer::CoalDrill(Good::IronOre), 5),
+            (Producer::CoalDrill(Good::Coal), 2),
+        ])
+    );
+    assert!(plan.unmet.is_empty());
+    assert_eq!(plan.count(), 7);
+    assert_eq!(plan.cost(), int(70));
+}
+
+#[test]
+fn rates_are_rounded_up_to_whole_producers() {
+    let plan = ChainPlan::new(Good::Coal, &F::new(I::from(1), I::from(2)));
+    assert_eq!(
+        plan.producers,
+        BTreeMap::from([(Producer::CoalDrill(Good::Coal), 1)])
+    );
+}
+
+#[test]
+fn huge_rates_stop_at_the_most_producers_there_can_be() {
+    let rate = F::from(I::from(10).pow(30));
+    let plan = ChainPlan::new(Good::IronOre, &rate);
+    assert_eq!(
+        plan.producers[&Producer::CoalDrill(Good::IronOre)],
+        usize::MAX
+    );
+    assert_eq!(plan.count(), usize::MAX);
+}
+
+#[test]
+fn goods_nothing_makes_are_unmet() {
+    let plan = ChainPlan::new(Good::Money, &int(3));
+    assert!(plan.producers.is_empty());
+    assert_eq!(plan.unmet, vec![Good::Money]);
+}
+
+#[test]
+fn built_chains_sustain_their_rate() {
+    let plan = ChainPlan::new(Good::GoldOre, &int(3));
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Money, &plan.cost());
+    assert_eq!(state.build_chain(&plan), plan.count());
+    state.advance(&int(60), &tick_rate());
+    let solution = state.production_table_sustainable();
+    let (made, used) = &solution.table[&Good::GoldOre];
+    assert!(made - used >= int(3));
+    let (made, used) = &solution.table[&Good::Coal];
+    assert!(made >= used);
+}
+
+#[test]
+fn unaffordable_chains_keep_their_ratios() {
+    let plan = ChainPlan::new(Good::IronOre, &int(10));
+    // 10 iron drills and 4 coal drills, at $10 each. $70 is half of it.
+    assert_eq!(plan.cost(), int(140));
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Money, &int(70));
+    assert_eq!(state.build_chain(&plan), 7);
+    // The build queue works on one order at a time.
+    state.advance(
+        &(construction_time(&Producer::CoalDrill(Good::Coal)) * int(7)),
+        &tick_rate(),
+    );
+    let breakdown = state.producer_breakdown();
+    assert_eq!(breakdown[&Producer::CoalDrill(Good::IronOre)], 5);
+    assert_eq!(breakdown[&Producer::CoalDrill(Good::Coal)], 2);
+}
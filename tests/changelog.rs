@@ -0,0 +1,29 @@
+// Tests for the "What's new" window shown after loading an older save.
+
+mod common;
+
+use common::exported_json;
+use dull_idle_game::IdleGame;
+use serde_json::json;
+
+#[test]
+fn older_saves_show_whats_new_since_their_version() {
+    let mut save = exported_json(&IdleGame::default());
+    save["save_version"] = json!(1);
+    let mut game = IdleGame::default();
+    game.import_save(&save.to_string()).unwrap();
+    let versions: Vec<u32> = game.whats_new().iter().map(|(v, _)| *v).collect();
+    assert_eq!(versions, vec![2]);
+    // The save is marked as seen right away, so saving and loading again doesn't show it twice.
+    let mut again = IdleGame::default();
+    again.import_save(&game.export_save()).unwrap();
+    assert!(again.whats_new().is_empty());
+}
+
+#[test]
+fn current_saves_have_nothing_new() {
+    let mut game = IdleGame::default();
+    game.import_save(&IdleGame::default().export_save())
+        .unwrap();
+    assert!(game.whats_new().is_empty());
+}
@@ -0,0 +1,55 @@
+// Tests for copying and pasting producers. Check clipboard.rs for how it works.
+
+mod common;
+
+use common::{F, I};
+use dull_idle_game::{ElemVariant, GameState, Good, PasteError, Producer, ProducerConfig};
+
+#[test]
+fn pasted_producer_is_a_configured_copy() {
+    let mut state = GameState::new(Some(0));
+    let producer = Producer::GravityDrill(Good::IronOre);
+    let id = state.add_element(
+        ElemVariant::Producer(producer),
+        &producer.to_string(),
+        false,
+    );
+    let mut config = ProducerConfig::of(&state.elements()[&id]).unwrap();
+    config.discard_excess = true;
+    config.region = Some(String::from("Mine"));
+    let pasted = ProducerConfig::from_json(&config.to_json()).unwrap();
+    assert_eq!(pasted, config);
+    // The copy is bought and built like any other producer. Gravity drills cost $10, and take 5 seconds to build.
+    state
+        .inventory_mut()
+        .insert(Good::Money, F::from(I::from(10)));
+    state.try_buy_configured(pasted).unwrap();
+    state.advance(&F::from(I::from(5)), &F::new(I::from(1), I::from(20)));
+    let (copy_id, copy) = state.elements().iter().last().unwrap();
+    assert_ne!(*copy_id, id);
+    assert_eq!(ProducerConfig::of(copy), Some(config));
+}
+
+#[test]
+fn malformed_pastes_are_rejected() {
+    assert!(matches!(
+        ProducerConfig::from_json("not json"),
+        Err(PasteError::Malformed(_))
+    ));
+    assert!(matches!(
+        ProducerConfig::from_json("{\"enabled\": false}"),
+        Err(PasteError::NotBuildable(Producer::None))
+    ));
+}
+
+#[test]
+fn pasting_costs_money() {
+    let mut state = GameState::new(Some(0));
+    let config = ProducerConfig {
+        producer: Producer::CoalDrill(Good::IronOre),
+        ..ProducerConfig::default()
+    };
+    assert!(state.try_buy_configured(config).is_err());
+    assert_eq!(state.money(), F::from(I::from(0)));
+    assert!(state.build_queue().is_empty());
+}
@@ -0,0 +1,40 @@
+// Tests for cloning every producer in a region into another region.
+
+mod common;
+
+use common::{add_producer, int, tick_rate};
+use dull_idle_game::{construction_time, GameState, Good, Producer};
+
+// A game with three coal drills in the "Mine" region.
+fn mine_game() -> GameState {
+    let mut state = GameState::new(Some(0));
+    let producer = Producer::CoalDrill(Good::IronOre);
+    for _ in 0..3 {
+        let id = add_producer(&mut state, producer);
+        state.move_to_region(id, Some(String::from("Mine")));
+    }
+    state
+}
+
+#[test]
+fn cloning_builds_configured_copies_in_the_new_region() {
+    let mut state = mine_game();
+    state.add_good(Good::Money, &int(30));
+    assert_eq!(state.clone_region_preview("Mine"), (3, 3, int(30)));
+    assert_eq!(state.clone_region("Mine", "Mine 2"), 3);
+    assert_eq!(state.money(), int(0));
+    let producer = Producer::CoalDrill(Good::IronOre);
+    state.advance(&(construction_time(&producer) * int(3)), &tick_rate());
+    assert_eq!(state.region_configs("Mine 2").len(), 3);
+    assert_eq!(state.region_configs("Mine").len(), 3);
+}
+
+#[test]
+fn cloning_stops_when_the_money_runs_out() {
+    let mut state = mine_game();
+    state.add_good(Good::Money, &int(25));
+    assert_eq!(state.clone_region_preview("Mine"), (2, 3, int(20)));
+    assert_eq!(state.clone_region("Mine", "Mine 2"), 2);
+    assert_eq!(state.money(), int(5));
+    assert_eq!(state.build_queue().len(), 2);
+}
@@ -0,0 +1,68 @@
+// Helpers shared by the integration tests. Every file in tests/ is its own crate, so each one pulls this in with `mod common;`.
+// Not every test uses every helper, and the ones that go unused in a file would warn there, hence the allow.
+#![allow(dead_code)]
+
+use dull_idle_game::{
+    construction_time, ElemVariant, GameState, Good, IdleGame, Producer, ProducerConfig,
+};
+use num::{BigInt, BigRational};
+use serde_json::Value;
+
+pub type F = BigRational;
+pub type I = BigInt;
+
+pub fn int(n: i64) -> F {
+    F::from(I::from(n))
+}
+
+pub fn ratio(numer: i64, denom: i64) -> F {
+    F::new(I::from(numer), I::from(denom))
+}
+
+// The game's tick rate, 20 ticks a second. Check the top of IdleGame::update.
+pub fn tick_rate() -> F {
+    ratio(1, 20)
+}
+
+// How much of a good the game has.
+pub fn amount(state: &GameState, good: Good) -> F {
+    state.inventory()[&good].clone()
+}
+
+// Adds a producer to the game straight away, for free, skipping the build queue. Returns its id.
+pub fn add_producer(state: &mut GameState, producer: Producer) -> usize {
+    state.add_element(
+        ElemVariant::Producer(producer),
+        &producer.to_string(),
+        false,
+    )
+}
+
+// Pays for a producer, buys it, and waits for it to finish building.
+pub fn build(state: &mut GameState, producer: Producer) {
+    state.add_good(Good::Money, &producer.properties().cost);
+    state.try_buy_producer(producer).unwrap();
+    state.advance(&construction_time(&producer), &tick_rate());
+}
+
+// Pays for a configured producer, buys it, and waits for it to finish building.
+pub fn build_configured(state: &mut GameState, config: ProducerConfig) {
+    let producer = config.producer;
+    state.add_good(Good::Money, &producer.properties().cost);
+    state.try_buy_configured(config).unwrap();
+    state.advance(&construction_time(&producer), &tick_rate());
+}
+
+// A game with a single coal drill on iron ore, and the given amount of coal.
+pub fn coal_drill_game(coal: i64) -> GameState {
+    let mut state = GameState::new(Some(0));
+    state.inventory_mut().insert(Good::Coal, int(coal));
+    add_producer(&mut state, Producer::CoalDrill(Good::IronOre));
+    state
+}
+
+// Reads an exported save as JSON. Exports end with a checksum line, which isn't part of the JSON.
+pub fn exported_json(game: &IdleGame) -> Value {
+    let save = game.export_save();
+    serde_json::from_str(save.lines().next().unwrap()).unwrap()
+}
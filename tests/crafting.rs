@@ -0,0 +1,55 @@
+// Tests for crafting, which makes a producer's recipe by hand. Check crafting.rs for more info.
+
+mod common;
+
+use common::{amount, int, ratio};
+use dull_idle_game::{recipes_for, GameState, Good, Producer};
+
+#[test]
+fn recipes_are_the_producers_that_make_a_good_from_something() {
+    assert_eq!(
+        recipes_for(Good::IronBar),
+        vec![Producer::Smelter(Good::IronOre)]
+    );
+    // Gravity drills make iron ore out of nothing, so they aren't a recipe. Coal drills burn coal, so they are.
+    assert_eq!(
+        recipes_for(Good::IronOre),
+        vec![Producer::CoalDrill(Good::IronOre)]
+    );
+    assert!(recipes_for(Good::Money).is_empty());
+}
+
+#[test]
+fn crafting_takes_the_inputs_and_makes_the_outputs() {
+    let mut state = GameState::new(Some(0));
+    state.clear_inventory();
+    state.add_good(Good::Coal, &int(1));
+    let drill = Producer::CoalDrill(Good::IronOre);
+    // A coal drill burns 1/4 coal a second, so there's enough coal for four.
+    assert_eq!(state.max_craftable(drill), 4);
+    assert_eq!(state.craft(drill, 1), 1);
+    assert_eq!(amount(&state, Good::Coal), ratio(3, 4));
+    assert_eq!(amount(&state, Good::IronOre), int(1));
+    // Asking for more than there's coal for only makes what it can.
+    assert_eq!(state.craft(drill, 10), 3);
+    assert_eq!(amount(&state, Good::Coal), int(0));
+    assert_eq!(amount(&state, Good::IronOre), int(4));
+    assert_eq!(state.max_craftable(drill), 0);
+    assert_eq!(state.craft(drill, 1), 0);
+}
+
+#[test]
+fn crafting_stops_when_the_outputs_are_full() {
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Coal, &int(100));
+    let capacity = state.capacity(Good::IronOre).unwrap();
+    state
+        .inventory_mut()
+        .insert(Good::IronOre, &capacity - int(2));
+    let drill = Producer::CoalDrill(Good::IronOre);
+    assert_eq!(state.max_craftable(drill), 2);
+    assert_eq!(state.craft(drill, 5), 2);
+    assert_eq!(amount(&state, Good::IronOre), capacity);
+    // Nothing was burned for output that wouldn't have fit.
+    assert_eq!(amount(&state, Good::Coal), int(100) - ratio(1, 2));
+}
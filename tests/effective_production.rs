@@ -0,0 +1,30 @@
+// Tests for the effective production table. Check GameState::production_table_effective.
+
+mod common;
+
+use common::{add_producer, int, tick_rate};
+use dull_idle_game::{GameState, Good, Producer};
+
+#[test]
+fn starved_producers_only_count_in_theory() {
+    let mut state = GameState::new(Some(0));
+    state.clear_inventory();
+    add_producer(&mut state, Producer::CoalDrill(Good::IronOre));
+    let theoretical = state.production_table_theoretical();
+    assert_eq!(theoretical[&Good::IronOre].0, int(1));
+    // There's no coal, so the drill is starved, and makes nothing right now.
+    let effective = state.production_table_effective(&tick_rate());
+    assert_eq!(effective[&Good::IronOre].0, int(0));
+    assert_eq!(effective[&Good::Coal].1, int(0));
+}
+
+#[test]
+fn fed_producers_count_the_same_as_in_theory() {
+    let mut state = GameState::new(Some(0));
+    add_producer(&mut state, Producer::CoalDrill(Good::IronOre));
+    state.add_good(Good::Coal, &int(10));
+    assert_eq!(
+        state.production_table_effective(&tick_rate()),
+        state.production_table_theoretical()
+    );
+}
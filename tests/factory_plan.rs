@@ -0,0 +1,86 @@
+// Tests for sharing factory plans, which are a factory's layout without any of its progress.
+
+mod common;
+
+use common::{build_configured, int};
+use dull_idle_game::{GameState, Good, PlanError, Producer, ProducerConfig, MAX_PLAN_PRODUCERS};
+use serde_json::{json, Value};
+
+// A factory with two configured drills, and some money and ore lying around.
+fn factory() -> GameState {
+    let mut state = GameState::new(Some(0));
+    for priority in [3, 7] {
+        build_configured(
+            &mut state,
+            ProducerConfig {
+                producer: Producer::CoalDrill(Good::IronOre),
+                priority,
+                region: Some(String::from("Mine")),
+                ..ProducerConfig::default()
+            },
+        );
+    }
+    state.add_good(Good::Money, &int(500));
+    state.add_good(Good::GoldOre, &int(40));
+    state
+}
+
+#[test]
+fn plans_leave_out_progress() {
+    let plan: Value = serde_json::from_str(&factory().export_plan()).unwrap();
+    assert_eq!(
+        plan.as_object().unwrap().keys().collect::<Vec<_>>(),
+        vec!["producers"]
+    );
+    assert_eq!(plan["producers"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn free_imports_rebuild_the_layout_with_fresh_ids() {
+    let plan = factory().export_plan();
+    let mut state = GameState::new(Some(0));
+    assert_eq!(state.import_plan(&plan, true), Ok(2));
+    assert_eq!(state.money(), int(0));
+    assert_eq!(state.inventory()[&Good::GoldOre], int(0));
+    let priorities: Vec<i32> = state.elements().values().map(|e| e.priority).collect();
+    assert_eq!(priorities, vec![3, 7]);
+    assert!(state
+        .elements()
+        .values()
+        .all(|e| e.region.as_deref() == Some("Mine")));
+    // Importing again adds more alongside, instead of replacing them.
+    assert_eq!(state.import_plan(&plan, true), Ok(2));
+    assert_eq!(state.elements().len(), 4);
+}
+
+#[test]
+fn paid_imports_stop_when_the_money_runs_out() {
+    let plan = factory().export_plan();
+    let mut state = GameState::new(Some(0));
+    state.add_good(
+        Good::Money,
+        &Producer::CoalDrill(Good::IronOre).properties().cost,
+    );
+    assert_eq!(state.import_plan(&plan, false), Ok(1));
+    assert_eq!(state.build_queue().len(), 1);
+}
+
+#[test]
+fn bad_plans_change_nothing() {
+    let mut state = GameState::new(Some(0));
+    let unbuildable = json!({ "producers": [{ "producer": "None" }] }).to_string();
+    assert_eq!(
+        state.import_plan(&unbuildable, true),
+        Err(PlanError::NotBuildable(Producer::None))
+    );
+    let huge = json!({ "producers": vec![json!({}); MAX_PLAN_PRODUCERS + 1] }).to_string();
+    assert_eq!(
+        state.import_plan(&huge, true),
+        Err(PlanError::TooBig(MAX_PLAN_PRODUCERS + 1))
+    );
+    assert!(matches!(
+        state.import_plan("nope", true),
+        Err(PlanError::Malformed(_))
+    ));
+    assert!(state.elements().is_empty());
+}
@@ -0,0 +1,26 @@
+// Tests for the "time to reach" estimate. Check GameState::time_to_reach for more info.
+
+mod common;
+
+use common::{add_producer, coal_drill_game, int};
+use dull_idle_game::{GameState, Good, Producer};
+
+#[test]
+fn two_gravity_drills_reach_the_target_at_their_combined_rate() {
+    let mut state = GameState::new(Some(0));
+    state.inventory_mut().insert(Good::IronOre, int(10));
+    for _ in 0..2 {
+        add_producer(&mut state, Producer::GravityDrill(Good::IronOre));
+    }
+    // 90 more iron ore at 2 per second.
+    assert_eq!(state.time_to_reach(Good::IronOre, &int(100)), Some(int(45)));
+    // Already past the target.
+    assert_eq!(state.time_to_reach(Good::IronOre, &int(5)), Some(int(0)));
+}
+
+#[test]
+fn goods_that_arent_going_up_never_get_there() {
+    let state = coal_drill_game(10);
+    assert_eq!(state.time_to_reach(Good::Coal, &int(100)), None);
+    assert_eq!(state.time_to_reach(Good::GoldOre, &int(100)), None);
+}
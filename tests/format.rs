@@ -0,0 +1,77 @@
+// Numbers are shortened with SI suffixes for window titles and the production graph. Check idle/lib.rs for more info.
+
+use dull_idle_game::{format_exact, format_good_amount, format_si};
+use num::{BigInt, BigRational};
+
+fn si(numer: i64, denom: i64) -> String {
+    format_si(&BigRational::new(BigInt::from(numer), BigInt::from(denom)))
+}
+
+#[test]
+fn numbers_get_suffixes_and_three_digits() {
+    assert_eq!(si(0, 1), "0");
+    assert_eq!(si(950, 1), "950");
+    assert_eq!(si(1, 4), "0.25");
+    assert_eq!(si(1000, 1), "1K");
+    assert_eq!(si(1234, 1), "1.23K");
+    assert_eq!(si(34_500_000, 1), "34.5M");
+    assert_eq!(si(-2_000_000_000, 1), "-2B");
+    assert_eq!(si(5_000_000_000_000_000, 1), "5.00e15");
+}
+
+fn exact(numer: i64, denom: i64, places: usize) -> String {
+    format_exact(
+        &BigRational::new(BigInt::from(numer), BigInt::from(denom)),
+        places,
+    )
+}
+
+#[test]
+fn exact_decimals_are_cut_off_not_rounded() {
+    assert_eq!(exact(1, 3, 5), "0.33333");
+    assert_eq!(exact(2, 3, 5), "0.66666");
+    assert_eq!(exact(5, 1, 2), "5.00");
+    assert_eq!(exact(-1, 8, 4), "-0.1250");
+    assert_eq!(exact(7, 4, 0), "1");
+}
+
+#[test]
+fn exact_decimals_handle_numbers_too_big_for_f64() {
+    // 10^400 + 1/2 doesn't fit in an f64 at all, let alone with the half.
+    let huge = BigInt::from(10).pow(400);
+    let value =
+        BigRational::from(huge.clone()) + BigRational::new(BigInt::from(1), BigInt::from(2));
+    assert_eq!(format_exact(&value, 1), format!("{huge}.5"));
+}
+
+fn amount(numer: i64, denom: i64) -> String {
+    format_good_amount(&BigRational::new(BigInt::from(numer), BigInt::from(denom)))
+}
+
+#[test]
+fn amounts_are_shortened_without_rounding_up() {
+    assert_eq!(amount(0, 1), "0");
+    assert_eq!(amount(999, 1), "999");
+    assert_eq!(amount(1000, 1), "1K");
+    assert_eq!(amount(1234, 1), "1.23K");
+    assert_eq!(amount(999_999, 1), "999K");
+    assert_eq!(amount(34_500_000, 1), "34.5M");
+    assert_eq!(amount(2_000_000_000_001, 1), "2T");
+    assert_eq!(amount(5_000_000_000_000_000, 1), "5.00e15");
+}
+
+#[test]
+fn small_and_negative_amounts_still_make_sense() {
+    assert_eq!(amount(1, 4), "0.25");
+    assert_eq!(amount(1, 2), "0.5");
+    assert_eq!(amount(1, 1000), "0");
+    assert_eq!(amount(-1, 1000), "0");
+    assert_eq!(amount(-1500, 1), "-1.5K");
+    assert_eq!(amount(-3, 4), "-0.75");
+}
+
+#[test]
+fn amounts_too_big_for_f64_are_still_exact() {
+    let huge = BigRational::from(BigInt::from(10).pow(400) * BigInt::from(123));
+    assert_eq!(format_good_amount(&huge), "1.23e402");
+}
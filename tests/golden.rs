@@ -0,0 +1,115 @@
+// Golden file tests. Each one runs a fixed scenario, and compares the whole game state afterwards against a checked-in save in tests/golden.
+// The simulation is exact (everything is BigRational, and seeded games shuffle the same way every time), so any difference at all is a change
+// in behavior. If the change was on purpose, regenerate the goldens with UPDATE_GOLDENS=1 cargo test --test golden, and check the diff.
+
+mod common;
+
+use common::{build, int, tick_rate};
+use dull_idle_game::{GameState, Good, GoodGroup, Producer};
+use serde_json::Value;
+use std::path::PathBuf;
+
+// Where a golden file lives.
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.json"))
+}
+
+// The paths of every value that differs between the two, like "inventory.Coal". Used to say where a golden went wrong.
+fn differences(path: &str, expected: &Value, actual: &Value, found: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for key in expected
+                .keys()
+                .chain(actual.keys().filter(|k| !expected.contains_key(*k)))
+            {
+                let child = format!("{path}.{key}");
+                match (expected.get(key), actual.get(key)) {
+                    (Some(e), Some(a)) => differences(&child, e, a, found),
+                    _ => found.push(child),
+                }
+            }
+        }
+        (Value::Array(e), Value::Array(a)) if e.len() == a.len() => {
+            for (index, (e, a)) in e.iter().zip(a).enumerate() {
+                differences(&format!("{path}[{index}]"), e, a, found);
+            }
+        }
+        _ if expected != actual => found.push(path.to_string()),
+        _ => {}
+    }
+}
+
+// Compares the game state against its golden file, or writes the golden file if UPDATE_GOLDENS is set.
+// It's compared as a JSON value rather than as text, since some of the game state is in hash maps, which don't serialize in a fixed order.
+// Golden files are written with their keys sorted, so they diff nicely when they're regenerated.
+fn check_golden(name: &str, state: &GameState) {
+    let actual = serde_json::to_value(state).unwrap();
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDENS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "{} is missing. Run UPDATE_GOLDENS=1 cargo test --test golden to make it",
+            path.display()
+        )
+    });
+    let expected: Value = serde_json::from_str(&expected).unwrap();
+    let mut found = Vec::new();
+    differences("", &expected, &actual, &mut found);
+    assert!(
+        found.is_empty(),
+        "{name} doesn't match its golden file. These changed:\n{}\nIf that's on purpose, rerun with UPDATE_GOLDENS=1",
+        found.join("\n")
+    );
+}
+
+// A gravity drill digging coal, feeding coal drills for every ore. There's more coal wanted than made, so the drills are starved
+// part of the time, which is where most of the interesting logic is.
+#[test]
+fn drill_chain() {
+    let mut state = GameState::new(Some(0));
+    build(&mut state, Producer::GravityDrill(Good::Coal));
+    for ore in Good::group_iter(GoodGroup::Ore).filter(|ore| *ore != Good::Coal) {
+        build(&mut state, Producer::CoalDrill(ore));
+        build(&mut state, Producer::CoalDrill(ore));
+    }
+    state.add_good(Good::Coal, &int(3));
+    state.advance(&int(90), &tick_rate());
+    check_golden("drill_chain", &state);
+}
+
+// A seeded game that solves minigames between stretches of drilling. The minigames are shuffled again after each solve,
+// so this catches changes to the button orders as well as to the simulation.
+#[test]
+fn seeded_minigames() {
+    let mut state = GameState::new(Some(1234));
+    build(&mut state, Producer::GravityDrill(Good::Coal));
+    for round in 0..5 {
+        for ore in Good::group_iter(GoodGroup::Ore) {
+            state.record_solve(ore, 2.5 + round as f64, &int(1));
+        }
+        state.reset_minigames(&Default::default());
+        state.advance(&int(7), &tick_rate());
+    }
+    check_golden("seeded_minigames", &state);
+}
+
+// The same scenario has to come out the same every time, or the goldens would be useless.
+#[test]
+fn scenarios_are_deterministic() {
+    let run = || {
+        let mut state = GameState::new(Some(99));
+        build(&mut state, Producer::CoalDrill(Good::IronOre));
+        state.add_good(Good::Coal, &int(1));
+        state.reset_minigames(&Default::default());
+        state.advance(&int(10), &tick_rate());
+        serde_json::to_value(&state).unwrap()
+    };
+    assert_eq!(run(), run());
+}
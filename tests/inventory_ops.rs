@@ -0,0 +1,53 @@
+// Tests for the inventory operations on GameState: add_good, remove_good and transfer.
+
+mod common;
+
+use common::{amount, int, F, I};
+use dull_idle_game::{GameState, Good};
+
+#[test]
+fn remove_good_fails_without_taking_anything() {
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Coal, &int(5));
+    assert!(!state.remove_good(Good::Coal, &int(6)));
+    assert_eq!(amount(&state, Good::Coal), int(5));
+    // Taking exactly everything is fine, and leaves zero rather than going negative.
+    assert!(state.remove_good(Good::Coal, &int(5)));
+    assert_eq!(amount(&state, Good::Coal), int(0));
+    assert!(!state.remove_good(Good::Coal, &F::new(I::from(1), I::from(100))));
+}
+
+#[test]
+fn negative_amounts_cant_be_removed() {
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Coal, &int(5));
+    assert!(!state.remove_good(Good::Coal, &int(-3)));
+    assert_eq!(amount(&state, Good::Coal), int(5));
+    // The same goes for the money side of a trade.
+    assert!(!state.transfer((Good::Money, &int(-10)), (Good::Coal, &int(1))));
+    assert_eq!(amount(&state, Good::Coal), int(5));
+}
+
+#[test]
+fn add_good_stops_at_capacity() {
+    let mut state = GameState::new(Some(0));
+    let capacity = state.capacity(Good::IronOre).unwrap();
+    state.add_good(Good::IronOre, &(&capacity - int(1)));
+    state.add_good(Good::IronOre, &int(5));
+    assert_eq!(amount(&state, Good::IronOre), capacity);
+    // Money has no capacity.
+    state.add_good(Good::Money, &(&capacity * int(2)));
+    assert_eq!(state.money(), capacity * int(2));
+}
+
+#[test]
+fn transfer_is_all_or_nothing() {
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Money, &int(10));
+    assert!(!state.transfer((Good::Money, &int(11)), (Good::Coal, &int(1))));
+    assert_eq!(state.money(), int(10));
+    assert_eq!(amount(&state, Good::Coal), int(0));
+    assert!(state.transfer((Good::Money, &int(4)), (Good::Coal, &int(2))));
+    assert_eq!(state.money(), int(6));
+    assert_eq!(amount(&state, Good::Coal), int(2));
+}
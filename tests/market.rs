@@ -0,0 +1,86 @@
+// Tests for buying and selling goods by hand. Check GameState::sell and GameState::buy.
+
+mod common;
+
+use common::{add_producer, int, tick_rate};
+use dull_idle_game::{GameState, Good, InsufficientFunds, Producer};
+
+#[test]
+fn selling_turns_goods_into_money() {
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::IronOre, &int(5));
+    let earned = state.sell(Good::IronOre, &int(2));
+    assert_eq!(earned, state.sell_price(Good::IronOre) * int(2));
+    assert_eq!(state.inventory()[&Good::IronOre], int(3));
+    assert_eq!(state.money(), earned);
+}
+
+#[test]
+fn only_what_is_held_can_be_sold() {
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::IronOre, &int(3));
+    let earned = state.sell(Good::IronOre, &int(10));
+    assert_eq!(earned, state.sell_price(Good::IronOre) * int(3));
+    assert_eq!(state.inventory()[&Good::IronOre], int(0));
+    assert_eq!(state.sell(Good::IronOre, &int(1)), int(0));
+}
+
+#[test]
+fn money_cannot_be_sold() {
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Money, &int(50));
+    assert_eq!(state.sell(Good::Money, &int(10)), int(0));
+    assert_eq!(state.money(), int(50));
+}
+
+#[test]
+fn buying_costs_more_than_selling_makes() {
+    let state = GameState::new(Some(0));
+    for good in [Good::Coal, Good::IronOre, Good::IronBar] {
+        assert!(state.buy_price(good) > state.sell_price(good));
+    }
+    assert_eq!(state.buy_price(Good::Money), int(0));
+}
+
+#[test]
+fn bought_coal_feeds_a_starved_drill() {
+    let mut state = GameState::new(Some(0));
+    add_producer(&mut state, Producer::CoalDrill(Good::IronOre));
+    state.advance(&int(1), &tick_rate());
+    assert_eq!(state.inventory()[&Good::IronOre], int(0));
+
+    let price = state.buy_price(Good::Coal);
+    state.add_good(Good::Money, &(&price * int(2)));
+    assert_eq!(state.buy(Good::Coal, &int(2)), Ok(&price * int(2)));
+    assert_eq!(state.money(), int(0));
+    assert_eq!(state.inventory()[&Good::Coal], int(2));
+    state.advance(&int(4), &tick_rate());
+    assert_eq!(state.inventory()[&Good::IronOre], int(4));
+}
+
+#[test]
+fn unaffordable_buys_buy_nothing() {
+    let mut state = GameState::new(Some(0));
+    let price = state.buy_price(Good::Coal);
+    state.add_good(Good::Money, &(&price * int(3)));
+    assert_eq!(
+        state.buy(Good::Coal, &int(4)),
+        Err(InsufficientFunds {
+            cost: &price * int(4),
+            available: &price * int(3),
+        })
+    );
+    assert_eq!(state.inventory()[&Good::Coal], int(0));
+    assert_eq!(state.money(), &price * int(3));
+}
+
+#[test]
+fn buying_stops_at_capacity() {
+    let mut state = GameState::new(Some(0));
+    let capacity = state.capacity(Good::Coal).unwrap();
+    state.add_good(Good::Coal, &(&capacity - int(1)));
+    state.add_good(Good::Money, &int(1000));
+    let cost = state.buy(Good::Coal, &int(10)).unwrap();
+    assert_eq!(cost, state.buy_price(Good::Coal));
+    assert_eq!(state.inventory()[&Good::Coal], capacity);
+}
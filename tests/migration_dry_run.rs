@@ -0,0 +1,61 @@
+// Tests for dry-running the save migrations. Check save::dry_run for more info.
+
+mod common;
+
+use common::exported_json;
+use dull_idle_game::{IdleGame, SaveChange};
+
+// A freshly exported save, as JSON, without its checksum line so it can be edited.
+fn current_save() -> serde_json::Value {
+    exported_json(&IdleGame::default())
+}
+
+#[test]
+fn current_saves_dry_run_cleanly() {
+    let save = IdleGame::default().export_save();
+    let report = IdleGame::dry_run_migration(&save).unwrap();
+    // Nothing to migrate, and loading writes back exactly what was saved.
+    assert!(report.changes.is_empty(), "{report}");
+    assert!(!report.is_lossy());
+}
+
+#[test]
+fn old_saves_report_their_migrations() {
+    // A version 1 save had the selected tab at the top level.
+    let mut value = current_save();
+    let selection = value["ui_state"]["selection"].clone();
+    let object = value.as_object_mut().unwrap();
+    object.remove("ui_state");
+    object.insert(String::from("selection"), selection);
+    object.insert(String::from("save_version"), serde_json::Value::from(1));
+    let report = IdleGame::dry_run_migration(&value.to_string()).unwrap();
+    assert_eq!(report.from_version, 1);
+    assert!(report.changes.contains(&SaveChange::Renamed(
+        String::from("selection"),
+        String::from("ui_state.selection")
+    )));
+    assert!(report
+        .changes
+        .iter()
+        .any(|change| matches!(change, SaveChange::Changed(path, ..) if path == "save_version")));
+    // The rest of the UI state wasn't in the old save, so it's filled in with defaults when loading.
+    assert!(report.changes.iter().any(
+        |change| matches!(change, SaveChange::Defaulted(path, _) if path.starts_with("ui_state."))
+    ));
+    assert!(!report.is_lossy(), "{report}");
+}
+
+#[test]
+fn fields_nothing_reads_are_reported_as_dropped() {
+    let mut value = current_save();
+    value["retired_field"] = serde_json::Value::from(7);
+    let report = IdleGame::dry_run_migration(&value.to_string()).unwrap();
+    assert_eq!(
+        report.changes,
+        vec![SaveChange::Dropped(
+            String::from("retired_field"),
+            serde_json::Value::from(7)
+        )]
+    );
+    assert!(report.is_lossy());
+}
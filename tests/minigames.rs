@@ -0,0 +1,262 @@
+// Tests for the ore minigame button orders. Check ores.rs for how they're shuffled.
+
+mod common;
+
+use common::{int, F};
+use dull_idle_game::{
+    inversions, min_inversions, shuffled_order, FailBehavior, GameState, MinigameSettings,
+    OreMinigame,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn inversions_are_counted() {
+    assert_eq!(inversions(&[1, 2, 3, 4, 5]), 0);
+    assert_eq!(inversions(&[5, 4, 3, 2, 1]), 10);
+    assert_eq!(inversions(&[2, 1, 3]), 1);
+}
+
+#[test]
+fn scrambled_orders_meet_the_minimum_inversions() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for difficulty in 0..=8 {
+        for _ in 0..200 {
+            let order = shuffled_order(difficulty, true, &mut rng);
+            // Every button shows up exactly once.
+            let mut sorted = order.clone();
+            sorted.sort();
+            assert_eq!(sorted, (1..=difficulty).collect::<Vec<_>>());
+            assert!(inversions(&order) >= min_inversions(difficulty));
+        }
+    }
+}
+
+// Makes a minigame with five buttons, clicks the first two right and the fifth one wrong, and deals with the failure.
+fn fail_after_two(settings: &MinigameSettings) -> OreMinigame {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut minigame = OreMinigame::new(5, settings, &mut rng);
+    minigame.press(1, settings, 0.0);
+    minigame.press(2, settings, 1.0);
+    minigame.press(5, settings, 2.0);
+    assert!(minigame.is_failed());
+    minigame.reset_if_failed(settings, &mut rng);
+    assert!(!minigame.is_failed());
+    minigame
+}
+
+#[test]
+fn reset_starts_over() {
+    let settings = MinigameSettings::default();
+    assert_eq!(settings.fail_behavior, FailBehavior::Reset);
+    let minigame = fail_after_two(&settings);
+    assert_eq!(minigame.next(), 1);
+    assert!(!minigame.is_locked(2.0));
+}
+
+#[test]
+fn setback_steps_back_without_reshuffling() {
+    let settings = MinigameSettings {
+        fail_behavior: FailBehavior::Setback,
+        setback_steps: 1,
+        ..MinigameSettings::default()
+    };
+    let mut rng = StdRng::seed_from_u64(0);
+    let order = OreMinigame::new(5, &settings, &mut rng).order().to_vec();
+    let minigame = fail_after_two(&settings);
+    // Two buttons in, so next was 3. One step back makes it 2.
+    assert_eq!(minigame.next(), 2);
+    assert_eq!(minigame.order(), &order[..]);
+    // Stepping back further than the start stops at the first button.
+    let settings = MinigameSettings {
+        setback_steps: 10,
+        ..settings
+    };
+    assert_eq!(fail_after_two(&settings).next(), 1);
+}
+
+#[test]
+fn lockout_keeps_progress_and_ignores_clicks_for_a_while() {
+    let settings = MinigameSettings {
+        fail_behavior: FailBehavior::Lockout,
+        lockout_ms: 3000,
+        ..MinigameSettings::default()
+    };
+    let mut minigame = fail_after_two(&settings);
+    assert_eq!(minigame.next(), 3);
+    // The wrong click was at 2 seconds, so the lock lasts until 5.
+    assert!(minigame.is_locked(4.9));
+    minigame.press(3, &settings, 4.0);
+    assert_eq!(minigame.next(), 3);
+    assert!(!minigame.is_failed());
+    assert!(!minigame.is_locked(5.0));
+    minigame.press(3, &settings, 5.0);
+    assert_eq!(minigame.next(), 4);
+}
+
+#[test]
+fn number_keys_press_the_buttons() {
+    let settings = MinigameSettings::default();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut minigame = OreMinigame::new(3, &settings, &mut rng);
+    assert!(minigame.key_press(egui::Key::Num1, &settings, 0.0));
+    assert!(minigame.key_press(egui::Key::Num2, &settings, 1.0));
+    assert!(minigame.key_press(egui::Key::Num3, &settings, 2.0));
+    assert!(minigame.is_solved());
+}
+
+#[test]
+fn wrong_number_keys_fail_like_wrong_clicks() {
+    let settings = MinigameSettings {
+        forgiving: false,
+        ..Default::default()
+    };
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut minigame = OreMinigame::new(5, &settings, &mut rng);
+    assert!(minigame.key_press(egui::Key::Num1, &settings, 0.0));
+    // Keys for buttons that are done, or that aren't there, don't count for anything.
+    assert!(!minigame.key_press(egui::Key::Num1, &settings, 1.0));
+    assert!(!minigame.key_press(egui::Key::Num9, &settings, 2.0));
+    assert!(!minigame.key_press(egui::Key::A, &settings, 3.0));
+    assert!(!minigame.is_failed());
+    assert!(minigame.key_press(egui::Key::Num4, &settings, 4.0));
+    assert!(minigame.is_failed());
+    assert!(minigame.take_mistake());
+}
+
+#[test]
+fn timed_minigames_fail_when_time_runs_out() {
+    let settings = MinigameSettings {
+        timed: true,
+        ..Default::default()
+    };
+    let time_limit = settings.time_limit(Some(3000));
+    assert_eq!(time_limit, Some(3.0));
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut minigame = OreMinigame::new(5, &settings, &mut rng);
+    // The time doesn't start until the first click.
+    minigame.check_time(time_limit, 100.0);
+    assert!(!minigame.is_failed());
+    minigame.press(1, &settings, 100.0);
+    assert_eq!(minigame.time_left(time_limit, 101.0), Some(2.0));
+    minigame.check_time(time_limit, 103.0);
+    assert!(!minigame.is_failed());
+    minigame.check_time(time_limit, 103.5);
+    assert!(minigame.is_failed());
+    assert!(minigame.take_mistake());
+    minigame.reset_if_failed(&settings, &mut rng);
+    assert_eq!(minigame.next(), 1);
+    assert_eq!(minigame.time_left(time_limit, 104.0), None);
+}
+
+#[test]
+fn untimed_minigames_never_run_out() {
+    let settings = MinigameSettings::default();
+    assert_eq!(settings.time_limit(Some(3000)), None);
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut minigame = OreMinigame::new(5, &settings, &mut rng);
+    minigame.press(1, &settings, 0.0);
+    minigame.check_time(settings.time_limit(Some(3000)), 1000.0);
+    assert!(!minigame.is_failed());
+}
+
+// Solves a minigame with the given number of buttons, the way the ui does, and returns the reward it gave.
+fn solve(
+    minigame: &mut OreMinigame,
+    settings: &MinigameSettings,
+    rng: &mut StdRng,
+    now: f64,
+) -> Option<F> {
+    let mut reward = None;
+    for (i, value) in (1..=minigame.order().len() as u32).enumerate() {
+        minigame.press(value, settings, now + i as f64);
+    }
+    minigame
+        .reset_if_failed(settings, rng)
+        .do_if_solved(|_, r| reward = Some(r))
+        .reset_if_solved(settings, rng);
+    reward
+}
+
+#[test]
+fn streaks_multiply_the_reward_until_a_failure() {
+    let settings = MinigameSettings {
+        forgiving: false,
+        ..Default::default()
+    };
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut minigame = OreMinigame::new(3, &settings, &mut rng);
+    assert_eq!(solve(&mut minigame, &settings, &mut rng, 0.0), Some(int(1)));
+    assert_eq!(
+        solve(&mut minigame, &settings, &mut rng, 10.0),
+        Some(int(2))
+    );
+    assert_eq!(
+        solve(&mut minigame, &settings, &mut rng, 20.0),
+        Some(int(3))
+    );
+    minigame.press(3, &settings, 30.0);
+    minigame.reset_if_failed(&settings, &mut rng);
+    assert_eq!(minigame.streak(), 1);
+    assert_eq!(
+        solve(&mut minigame, &settings, &mut rng, 40.0),
+        Some(int(1))
+    );
+}
+
+#[test]
+fn streaks_are_capped() {
+    let settings = MinigameSettings::default();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut minigame = OreMinigame::new(2, &settings, &mut rng);
+    for round in 0..20 {
+        solve(&mut minigame, &settings, &mut rng, round as f64 * 10.0);
+    }
+    assert_eq!(minigame.streak(), 21);
+    assert_eq!(minigame.reward(), int(10));
+}
+
+#[test]
+fn streaks_survive_saving() {
+    let settings = MinigameSettings::default();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut minigame = OreMinigame::new(3, &settings, &mut rng);
+    solve(&mut minigame, &settings, &mut rng, 0.0);
+    solve(&mut minigame, &settings, &mut rng, 10.0);
+    let json = serde_json::to_string(&minigame).unwrap();
+    let loaded: OreMinigame = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.streak(), 3);
+    // Saves from before streaks start without one.
+    let old: OreMinigame =
+        serde_json::from_str(r#"{"order":[2,1,3],"next":1,"difficulty":3}"#).unwrap();
+    assert_eq!(old.streak(), 1);
+}
+
+// The button orders a game deals out, for every ore, over a few rounds of resets.
+fn order_sequence(seed: u64) -> Vec<serde_json::Value> {
+    let mut state = GameState::new(Some(seed));
+    let mut sequence = Vec::new();
+    for _ in 0..5 {
+        let json = serde_json::to_value(&state).unwrap();
+        sequence.push(json["ore_minigames"].clone());
+        state.reset_minigames(&MinigameSettings::default());
+    }
+    sequence
+}
+
+#[test]
+fn the_same_seed_deals_the_same_orders_across_resets() {
+    assert_eq!(order_sequence(7), order_sequence(7));
+    assert_ne!(order_sequence(7), order_sequence(8));
+
+    let settings = MinigameSettings::default();
+    let mut a_rng = StdRng::seed_from_u64(42);
+    let mut b_rng = StdRng::seed_from_u64(42);
+    let mut a = OreMinigame::new(8, &settings, &mut a_rng);
+    let mut b = OreMinigame::new(8, &settings, &mut b_rng);
+    for _ in 0..10 {
+        assert_eq!(a.order(), b.order());
+        a.reset(&settings, &mut a_rng);
+        b.reset(&settings, &mut b_rng);
+    }
+}
@@ -0,0 +1,25 @@
+// Checks that saves from before a good existed load with that good filled in. Check GameState::ensure_goods_present.
+
+mod common;
+
+use common::exported_json;
+use dull_idle_game::IdleGame;
+
+#[test]
+fn missing_goods_are_filled_in_with_zero() {
+    let fresh = exported_json(&IdleGame::default());
+    let zero = fresh["game_state"]["inventory"]["GoldBar"].clone();
+    assert!(!zero.is_null());
+    let mut save = fresh.clone();
+    let inventory = save["game_state"]["inventory"].as_object_mut().unwrap();
+    assert!(inventory.remove("GoldBar").is_some());
+
+    let mut game = IdleGame::default();
+    game.import_save(&save.to_string()).unwrap();
+    let loaded = exported_json(&game);
+    assert_eq!(loaded["game_state"]["inventory"]["GoldBar"], zero);
+    assert_eq!(
+        loaded["game_state"]["inventory"],
+        fresh["game_state"]["inventory"]
+    );
+}
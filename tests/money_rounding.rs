@@ -0,0 +1,82 @@
+// Tests for the money rounding rule, and that every way of selling follows it. Check round_money for the rule.
+
+mod common;
+
+use common::{build_configured, int, ratio, tick_rate};
+use dull_idle_game::{
+    round_money, AutoSellRule, EconomySettings, GameState, Good, Producer, ProducerConfig, TaxKind,
+};
+
+// A 1.37x multiplier with a 33% tax, so iron ore (base price 2) sells for 1.8358, which isn't a whole number of cents.
+fn taxed() -> EconomySettings {
+    EconomySettings {
+        price_multiplier: ratio(137, 100),
+        tax_kind: TaxKind::Percentage,
+        tax: ratio(33, 100),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn money_rounds_down_to_whole_cents() {
+    assert_eq!(round_money(&ratio(999, 1000)), ratio(99, 100));
+    assert_eq!(round_money(&ratio(125, 1000)), ratio(12, 100));
+    assert_eq!(round_money(&ratio(1, 2)), ratio(1, 2));
+    assert_eq!(round_money(&ratio(1, 3)), ratio(33, 100));
+    assert_eq!(round_money(&ratio(1, 1000)), int(0));
+    assert_eq!(round_money(&int(7)), int(7));
+}
+
+#[test]
+fn selling_by_hand_pays_whole_cents() {
+    let mut state = GameState::new(Some(0));
+    state.set_economy(taxed());
+    state.add_good(Good::IronOre, &int(3));
+    let exact = state.sell_price(Good::IronOre) * int(3);
+    let earned = state.sell(Good::IronOre, &int(3));
+    assert_eq!(earned, round_money(&exact));
+    assert!(earned < exact);
+    assert_eq!(state.money(), earned);
+}
+
+#[test]
+fn auto_selling_pays_whole_cents() {
+    let mut state = GameState::new(Some(0));
+    state.set_economy(taxed());
+    state.add_good(Good::IronOre, &int(3));
+    state.set_auto_sell_rule(
+        Good::IronOre,
+        AutoSellRule {
+            enabled: true,
+            ..AutoSellRule::default()
+        },
+    );
+    state.advance(&tick_rate(), &tick_rate());
+    assert_eq!(
+        state.money(),
+        round_money(&(state.sell_price(Good::IronOre) * int(3)))
+    );
+}
+
+#[test]
+fn selling_output_directly_pays_whole_cents_without_losing_any() {
+    let mut state = GameState::new(Some(0));
+    state.set_economy(taxed());
+    let producer = Producer::GravityDrill(Good::IronOre);
+    build_configured(
+        &mut state,
+        ProducerConfig {
+            producer,
+            sell_output: true,
+            ..ProducerConfig::default()
+        },
+    );
+    let before = state.money();
+    state.advance(&int(10), &tick_rate());
+    let earned = state.money() - before;
+    let rate = producer.properties().recipe.outputs[&Good::IronOre].clone();
+    let exact = rate * state.sell_price(Good::IronOre) * int(10);
+    // Every payment is whole cents, and what isn't paid yet is less than a cent.
+    assert_eq!(round_money(&earned), earned);
+    assert!(earned <= exact && exact - &earned < ratio(1, 100));
+}
@@ -0,0 +1,99 @@
+// Tests for producers with more than one output. None of the built-in producers have one yet, so these use a registered producer.
+
+mod common;
+
+use common::{add_producer, int, tick_rate, F, I};
+use dull_idle_game::{
+    GameState, Good, GoodDefinition, GoodGroup, Inventory, Producer, ProducerCategory,
+    ProducerDefinition, Recipe,
+};
+use std::sync::Mutex;
+
+// A drill that digs 1 iron ore and 3 stone a second, for 1/4 coal a second. It's registered once, since the registry is shared.
+fn stone_drill() -> (Producer, Good) {
+    static REGISTERED: Mutex<Option<(Producer, Good)>> = Mutex::new(None);
+    *REGISTERED.lock().unwrap().get_or_insert_with(|| {
+        let stone = GameState::register_good(GoodDefinition {
+            key: String::from("MultiStone"),
+            name: String::from("Stone"),
+            group: GoodGroup::Ore,
+            difficulty: 2,
+            capacity: Some(100),
+            base_price: 0,
+            time_limit_ms: None,
+            old_keys: Vec::new(),
+        })
+        .unwrap();
+        let drill = GameState::register_producer(ProducerDefinition {
+            key: String::from("MultiStoneDrill"),
+            name: String::from("Stone Drill"),
+            cost: int(10),
+            recipe: Recipe::new(
+                Inventory::from([(Good::Coal, F::new(I::from(1), I::from(4)))]),
+                Inventory::from([(Good::IronOre, int(1)), (stone, int(3))]),
+            ),
+            warmup: int(0),
+            category: ProducerCategory::Drill,
+        })
+        .unwrap();
+        (drill, stone)
+    })
+}
+
+#[test]
+fn every_output_is_made_each_tick() {
+    let (drill, stone) = stone_drill();
+    let mut state = GameState::new(Some(0));
+    add_producer(&mut state, drill);
+    state.add_good(Good::Coal, &int(10));
+    let iron = state.inventory()[&Good::IronOre].clone();
+    state.advance(&tick_rate(), &tick_rate());
+    assert_eq!(state.inventory()[&Good::IronOre], &iron + tick_rate());
+    assert_eq!(state.inventory()[&stone], int(3) * tick_rate());
+}
+
+#[test]
+fn the_production_table_counts_every_output() {
+    let (drill, stone) = stone_drill();
+    let mut state = GameState::new(Some(0));
+    add_producer(&mut state, drill);
+    add_producer(&mut state, drill);
+    let table = state.production_table_theoretical();
+    assert_eq!(table[&Good::IronOre], (int(2), int(0)));
+    assert_eq!(table[&stone], (int(6), int(0)));
+    assert_eq!(table[&Good::Coal], (int(0), F::new(I::from(1), I::from(2))));
+}
+
+#[test]
+fn a_full_output_stalls_the_whole_recipe() {
+    let (drill, stone) = stone_drill();
+    let mut state = GameState::new(Some(0));
+    add_producer(&mut state, drill);
+    state.add_good(Good::Coal, &int(10));
+    state.add_good(stone, &int(100));
+    let iron = state.inventory()[&Good::IronOre].clone();
+    state.advance(&int(1), &tick_rate());
+    // There's no room for stone, so no iron ore is made either, and no coal is used.
+    assert_eq!(state.inventory()[&Good::IronOre], iron);
+    assert_eq!(state.inventory()[&Good::Coal], int(10));
+}
+
+#[test]
+fn crafting_makes_every_output() {
+    let (drill, stone) = stone_drill();
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Coal, &int(1));
+    let iron = state.inventory()[&Good::IronOre].clone();
+    // The drill's recipe is the same one crafting makes, so a craft is a second of the drill.
+    assert_eq!(state.craft(drill, 2), 2);
+    let recipe = drill.properties().recipe;
+    assert_eq!(
+        state.inventory()[&Good::IronOre],
+        &iron + int(2) * &recipe.outputs[&Good::IronOre]
+    );
+    assert_eq!(state.inventory()[&stone], int(6));
+    assert_eq!(
+        state.inventory()[&Good::Coal],
+        F::new(I::from(1), I::from(2))
+    );
+}
@@ -0,0 +1,37 @@
+// Tests for normalizing the game state, which rounds big amounts so their denominators stay small.
+
+mod common;
+
+use common::{int, F, I};
+use dull_idle_game::{GameState, Good};
+use num::Signed;
+
+// A fraction with a denominator way past anything the normalizer allows. 3^40 is about 1.2e19.
+fn tiny_third_power() -> F {
+    F::new(I::from(1), num::pow(I::from(3), 40))
+}
+
+#[test]
+fn big_amounts_are_rounded_within_tolerance() {
+    let mut state = GameState::new(Some(0));
+    let amount = int(5000) + tiny_third_power() * int(7);
+    state.inventory_mut().insert(Good::IronOre, amount.clone());
+    assert_eq!(state.normalize(), 1);
+    let rounded = state.inventory()[&Good::IronOre].clone();
+    let places = state.sim_settings().normalize_places as usize;
+    let limit = num::pow(I::from(10), places);
+    assert!(rounded.denom() <= &limit);
+    assert!((rounded - amount).abs() <= F::new(I::from(1), limit * I::from(2)));
+}
+
+#[test]
+fn small_and_clean_amounts_are_left_alone() {
+    let mut state = GameState::new(Some(0));
+    let small = int(10) + tiny_third_power();
+    let clean = F::new(I::from(10001), I::from(4));
+    state.inventory_mut().insert(Good::IronOre, small.clone());
+    state.inventory_mut().insert(Good::Coal, clean.clone());
+    assert_eq!(state.normalize(), 0);
+    assert_eq!(state.inventory()[&Good::IronOre], small);
+    assert_eq!(state.inventory()[&Good::Coal], clean);
+}
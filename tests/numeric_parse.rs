@@ -0,0 +1,42 @@
+// Tests for reading typed numbers, for the UI's number boxes. Check NumericValue in widgets.rs.
+
+mod common;
+
+use common::{int, ratio, F, I};
+use dull_idle_game::NumericValue;
+
+#[test]
+fn rationals_read_integers_fractions_and_decimals_exactly() {
+    assert_eq!(F::parse("3"), Some(int(3)));
+    assert_eq!(F::parse(" 3/4 "), Some(ratio(3, 4)));
+    assert_eq!(F::parse("-3 / 4"), Some(ratio(-3, 4)));
+    assert_eq!(F::parse("0.75"), Some(ratio(3, 4)));
+    assert_eq!(F::parse(".5"), Some(ratio(1, 2)));
+    assert_eq!(F::parse("-1.25"), Some(ratio(-5, 4)));
+    assert_eq!(F::parse("-0.1"), Some(ratio(-1, 10)));
+    // Decimals are exact, so 0.1 is a tenth, not the closest f64 to it.
+    assert_eq!(F::parse("0.1"), Some(ratio(1, 10)));
+}
+
+#[test]
+fn rationals_reject_what_isnt_a_number() {
+    for text in [
+        "", "abc", "1/0", "1/", "/2", "1.", "1.2.3", "1.-2", "1e5", "--1",
+    ] {
+        assert_eq!(F::parse(text), None, "{text:?}");
+    }
+}
+
+#[test]
+fn displayed_rationals_parse_back() {
+    for value in [int(0), int(-7), ratio(22, 7), ratio(-1, 3)] {
+        assert_eq!(F::parse(&value.display()), Some(value));
+    }
+}
+
+#[test]
+fn integers_read_whole_numbers_only() {
+    assert_eq!(I::parse(" -42 "), Some(I::from(-42)));
+    assert_eq!(I::parse("1.5"), None);
+    assert_eq!(I::parse("1/2"), None);
+}
@@ -0,0 +1,34 @@
+// Tests for the partial resets, which reset one part of the game and leave the rest alone.
+
+mod common;
+
+use common::{add_producer, int};
+use dull_idle_game::{ElemVariant, GameState, Good, Producer};
+
+#[test]
+fn clearing_producers_refunds_the_queue_and_keeps_goods() {
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Money, &int(30));
+    state.add_good(Good::Coal, &int(7));
+    add_producer(&mut state, Producer::CoalDrill(Good::IronOre));
+    state.add_element(ElemVariant::Good(Good::Coal), "Coal", true);
+    state
+        .try_buy_producer(Producer::GravityDrill(Good::IronOre))
+        .unwrap();
+    assert_eq!(state.clear_producers(), 1);
+    assert!(state.build_queue().is_empty());
+    assert_eq!(state.money(), int(30));
+    assert_eq!(state.inventory()[&Good::Coal], int(7));
+    // Goods' windows aren't producers, so they stay.
+    assert_eq!(state.elements().len(), 1);
+}
+
+#[test]
+fn clearing_the_inventory_keeps_producers() {
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Money, &int(30));
+    add_producer(&mut state, Producer::GravityDrill(Good::IronOre));
+    state.clear_inventory();
+    assert!(state.inventory().values().all(|amount| *amount == int(0)));
+    assert_eq!(state.elements().len(), 1);
+}
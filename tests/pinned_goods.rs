@@ -0,0 +1,36 @@
+// Tests for pinning goods to the top of the inventory.
+
+use dull_idle_game::{GameState, Good};
+
+#[test]
+fn pinned_goods_come_first_in_declaration_order() {
+    let mut state = GameState::new(Some(0));
+    state.toggle_pin(Good::Coal);
+    state.toggle_pin(Good::IronOre);
+    let order = state.inventory_order();
+    // Iron ore is declared before coal, so it stays first even though it was pinned second.
+    assert_eq!(&order[..2], &[Good::IronOre, Good::Coal]);
+    // Every good is still shown exactly once.
+    assert_eq!(order.len(), state.inventory().len());
+    assert_eq!(order.iter().filter(|good| **good == Good::Coal).count(), 1);
+}
+
+#[test]
+fn pinning_twice_unpins() {
+    let mut state = GameState::new(Some(0));
+    let before = state.inventory_order();
+    state.toggle_pin(Good::GoldOre);
+    assert!(state.is_pinned(Good::GoldOre));
+    state.toggle_pin(Good::GoldOre);
+    assert!(!state.is_pinned(Good::GoldOre));
+    assert_eq!(state.inventory_order(), before);
+}
+
+#[test]
+fn pins_survive_a_save() {
+    let mut state = GameState::new(Some(0));
+    state.toggle_pin(Good::SilverOre);
+    let loaded: GameState = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+    assert!(loaded.is_pinned(Good::SilverOre));
+    assert_eq!(loaded.inventory_order()[0], Good::SilverOre);
+}
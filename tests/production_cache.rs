@@ -0,0 +1,49 @@
+// Tests for the cached production table. Check GameState::production_table_theoretical.
+
+mod common;
+
+use common::{add_producer, int};
+use dull_idle_game::{GameState, Good, Producer};
+
+fn drill(state: &mut GameState) -> usize {
+    add_producer(state, Producer::CoalDrill(Good::IronOre))
+}
+
+#[test]
+fn the_cache_follows_the_producers() {
+    let mut state = GameState::new(Some(0));
+    assert!(!state
+        .production_table_theoretical()
+        .contains_key(&Good::IronOre));
+    let id = drill(&mut state);
+    assert_eq!(
+        state.production_table_theoretical()[&Good::IronOre].0,
+        int(1)
+    );
+    drill(&mut state);
+    assert_eq!(
+        state.production_table_theoretical()[&Good::IronOre].0,
+        int(2)
+    );
+    state.set_producers_enabled(false, |_| true);
+    assert!(!state
+        .production_table_theoretical()
+        .contains_key(&Good::IronOre));
+    state.set_producers_enabled(true, |_| true);
+    state.remove_element(id);
+    assert_eq!(
+        state.production_table_theoretical()[&Good::IronOre].0,
+        int(1)
+    );
+}
+
+#[test]
+fn the_cache_is_rebuilt_after_loading() {
+    let mut state = GameState::new(Some(0));
+    drill(&mut state);
+    let table = state.production_table_theoretical();
+    let json = serde_json::to_string(&state).unwrap();
+    assert!(!json.contains("production_cache"));
+    let loaded: GameState = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.production_table_theoretical(), table);
+}
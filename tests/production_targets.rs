@@ -0,0 +1,57 @@
+// Tests for production targets, which pause and unpause a good's producers to hold a stockpile. Check targets.rs for more info.
+
+mod common;
+
+use common::{build_configured, int, tick_rate};
+use dull_idle_game::{GameState, Good, Producer, ProducerConfig};
+
+// Three iron ore drills with priorities 0, 5 and 1, which get ids 0, 1 and 2.
+fn drills() -> GameState {
+    let mut state = GameState::new(Some(0));
+    for priority in [0, 5, 1] {
+        build_configured(
+            &mut state,
+            ProducerConfig {
+                producer: Producer::GravityDrill(Good::IronOre),
+                priority,
+                ..ProducerConfig::default()
+            },
+        );
+    }
+    state
+}
+
+fn enabled(state: &GameState) -> Vec<bool> {
+    state.elements().values().map(|e| e.enabled).collect()
+}
+
+#[test]
+fn least_important_producers_are_paused_first_and_unpaused_last() {
+    let mut state = drills();
+    state.set_production_target(Good::IronOre, Some(int(1)));
+    state.advance(&int(1), &tick_rate());
+    assert_eq!(enabled(&state), vec![false, true, true]);
+    state.advance(&int(1), &tick_rate());
+    assert_eq!(enabled(&state), vec![false, true, false]);
+    state.advance(&int(1), &tick_rate());
+    assert_eq!(enabled(&state), vec![false, false, false]);
+    assert_eq!(
+        state.production_target(Good::IronOre).unwrap().paused.len(),
+        3
+    );
+    // Once it drops below the target, the most important one comes back first.
+    state.inventory_mut().insert(Good::IronOre, int(0));
+    state.advance(&int(1), &tick_rate());
+    assert_eq!(enabled(&state), vec![false, true, false]);
+}
+
+#[test]
+fn removing_a_target_unpauses_what_it_paused() {
+    let mut state = drills();
+    state.set_production_target(Good::IronOre, Some(int(1)));
+    state.advance(&int(2), &tick_rate());
+    assert_eq!(enabled(&state), vec![false, true, false]);
+    state.set_production_target(Good::IronOre, None);
+    assert_eq!(enabled(&state), vec![true, true, true]);
+    assert!(state.production_target(Good::IronOre).is_none());
+}
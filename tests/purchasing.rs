@@ -0,0 +1,41 @@
+// Tests for buying producers with money. Check GameState::try_buy_producer.
+
+mod common;
+
+use common::{int, ratio};
+use dull_idle_game::{GameState, Good, Producer};
+
+#[test]
+fn buying_takes_the_cost() {
+    let mut state = GameState::new(Some(0));
+    let producer = Producer::CoalDrill(Good::IronOre);
+    state.add_good(Good::Money, &int(25));
+    assert!(state.can_afford(producer));
+    state.try_buy_producer(producer).unwrap();
+    assert_eq!(state.money(), int(25) - producer.properties().cost);
+}
+
+#[test]
+fn free_producers_need_no_money() {
+    let mut state = GameState::new(Some(0));
+    assert_eq!(state.money(), int(0));
+    assert!(state.can_afford(Producer::None));
+    state.try_buy_producer(Producer::None).unwrap();
+    assert_eq!(state.money(), int(0));
+}
+
+#[test]
+fn a_fraction_short_is_still_short() {
+    let mut state = GameState::new(Some(0));
+    let producer = Producer::CoalDrill(Good::IronOre);
+    // 9.99, a cent short of the $10 it costs.
+    state.add_good(Good::Money, &ratio(999, 100));
+    assert!(!state.can_afford(producer));
+    let error = state.try_buy_producer(producer).unwrap_err();
+    assert_eq!(state.money(), ratio(999, 100));
+    // Money is rounded down for display, so it doesn't look like there's enough.
+    assert_eq!(
+        error.to_string(),
+        "Not enough money: costs 10, but you only have 9"
+    );
+}
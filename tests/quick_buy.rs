@@ -0,0 +1,24 @@
+// Tests for quick-buying the cheapest producer the player can afford.
+
+mod common;
+
+use common::int;
+use dull_idle_game::{GameState, Good, Producer};
+
+#[test]
+fn nothing_is_affordable_without_money() {
+    let state = GameState::new(Some(0));
+    assert_eq!(state.cheapest_affordable(), None);
+}
+
+#[test]
+fn the_cheapest_real_producer_is_picked() {
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Money, &int(10));
+    // The gravity drill costs the same, but it's a debug producer, so it's skipped.
+    let cheapest = state.cheapest_affordable().unwrap();
+    assert_eq!(cheapest, Producer::CoalDrill(Good::IronOre));
+    assert!(cheapest.properties().cost <= state.money());
+    state.try_buy_producer(cheapest).unwrap();
+    assert_eq!(state.cheapest_affordable(), None);
+}
@@ -0,0 +1,148 @@
+// Tests for goods and producers registered at runtime. Check registry.rs for more info.
+// The registry is shared by every test in here, so each test registers its own keys.
+
+mod common;
+
+use common::{build, int, tick_rate};
+use dull_idle_game::{
+    ElemVariant, GameState, Good, GoodDefinition, GoodGroup, Inventory, Producer, ProducerCategory,
+    ProducerDefinition, Recipe, RegistryError,
+};
+
+fn ore(key: &str, name: &str) -> GoodDefinition {
+    GoodDefinition {
+        key: key.to_string(),
+        name: name.to_string(),
+        group: GoodGroup::Ore,
+        difficulty: 4,
+        capacity: None,
+        base_price: 3,
+        time_limit_ms: None,
+        old_keys: Vec::new(),
+    }
+}
+
+// A producer that turns one of the input into two of the output every second.
+fn converter(key: &str, input: Good, output: Good) -> ProducerDefinition {
+    ProducerDefinition {
+        key: key.to_string(),
+        name: "Converter".to_string(),
+        cost: int(10),
+        recipe: Recipe::new(
+            Inventory::from([(input, int(1))]),
+            Inventory::from([(output, int(2))]),
+        ),
+        warmup: int(0),
+        category: ProducerCategory::Drill,
+    }
+}
+
+#[test]
+fn registered_producers_tick_with_registered_goods() {
+    let copper = GameState::register_good(ore("TickCopper", "Copper")).unwrap();
+    let smelter =
+        GameState::register_producer(converter("TickSmelter", copper, Good::IronOre)).unwrap();
+    assert_eq!(copper.properties().name, "Copper");
+    assert!(Producer::buildable().contains(&smelter));
+
+    let mut state = GameState::new(Some(0));
+    assert_eq!(state.inventory()[&copper], int(0));
+    build(&mut state, smelter);
+    state.add_good(copper, &int(5));
+    let iron = state.inventory()[&Good::IronOre].clone();
+    state.advance(&int(2), &tick_rate());
+    assert_eq!(state.inventory()[&copper], int(3));
+    assert_eq!(state.inventory()[&Good::IronOre], iron + int(4));
+}
+
+#[test]
+fn registered_content_saves_under_its_key() {
+    let tin = GameState::register_good(ore("SaveTin", "Tin")).unwrap();
+    let press = GameState::register_producer(converter("SavePress", Good::Coal, tin)).unwrap();
+    let mut state = GameState::new(Some(0));
+    build(&mut state, press);
+    state.add_good(tin, &int(7));
+
+    let json = serde_json::to_string(&state).unwrap();
+    assert!(json.contains("\"SaveTin\""));
+    assert!(json.contains("\"SavePress\""));
+    let loaded: GameState = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.inventory()[&tin], int(7));
+    let element = loaded.elements().values().next().unwrap();
+    assert!(matches!(element.variant, ElemVariant::Producer(p) if p == press));
+}
+
+#[test]
+fn built_in_goods_save_the_same_as_before() {
+    assert_eq!(
+        serde_json::to_string(&Good::IronOre).unwrap(),
+        "\"IronOre\""
+    );
+    let good: Good = serde_json::from_str("\"Coal\"").unwrap();
+    assert_eq!(good, Good::Coal);
+    assert!(serde_json::from_str::<Good>("\"NeverRegistered\"").is_err());
+}
+
+#[test]
+fn bad_keys_are_refused() {
+    assert_eq!(
+        GameState::register_good(ore("1Copper", "Copper")),
+        Err(RegistryError::InvalidKey("1Copper".to_string()))
+    );
+    assert_eq!(
+        GameState::register_good(ore("Coal", "More Coal")),
+        Err(RegistryError::DuplicateKey("Coal".to_string()))
+    );
+    GameState::register_good(ore("TwiceZinc", "Zinc")).unwrap();
+    assert_eq!(
+        GameState::register_good(ore("TwiceZinc", "Zinc")),
+        Err(RegistryError::DuplicateKey("TwiceZinc".to_string()))
+    );
+}
+
+#[test]
+fn saves_under_an_old_key_still_load() {
+    // Zinc used to be saved as "AliasSpelter", before it was renamed.
+    let zinc = GameState::register_good(GoodDefinition {
+        old_keys: vec!["AliasSpelter".to_string()],
+        ..ore("AliasZinc", "Zinc")
+    })
+    .unwrap();
+    let mut state = GameState::new(Some(0));
+    state.add_good(zinc, &int(4));
+    let json = serde_json::to_string(&state).unwrap();
+    assert!(json.contains("\"AliasZinc\""));
+    let old_save = json.replace("\"AliasZinc\"", "\"AliasSpelter\"");
+    assert_ne!(old_save, json);
+    let loaded: GameState = serde_json::from_str(&old_save).unwrap();
+    assert_eq!(loaded.inventory()[&zinc], int(4));
+    // It's written back out under the new key.
+    assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+    let good: Good = serde_json::from_str("\"AliasSpelter\"").unwrap();
+    assert_eq!(good, zinc);
+}
+
+#[test]
+fn old_keys_cant_clash() {
+    assert_eq!(
+        GameState::register_good(GoodDefinition {
+            old_keys: vec!["Coal".to_string()],
+            ..ore("ClashNickel", "Nickel")
+        }),
+        Err(RegistryError::DuplicateKey("Coal".to_string()))
+    );
+    assert_eq!(
+        GameState::register_good(GoodDefinition {
+            old_keys: vec!["ClashCobalt".to_string()],
+            ..ore("ClashCobalt", "Cobalt")
+        }),
+        Err(RegistryError::DuplicateKey("ClashCobalt".to_string()))
+    );
+    assert_eq!(
+        GameState::register_good(GoodDefinition {
+            old_keys: vec!["Not a key".to_string()],
+            ..ore("ClashLead", "Lead")
+        }),
+        Err(RegistryError::InvalidKey("Not a key".to_string()))
+    );
+}
@@ -0,0 +1,62 @@
+// Tests for the checksum on exported saves. Check save.rs for more info.
+
+use dull_idle_game::{IdleGame, SaveError};
+
+#[test]
+fn exported_saves_import_cleanly() {
+    let save = IdleGame::default().export_save();
+    assert!(save.lines().last().unwrap().starts_with("crc32:"));
+    assert!(IdleGame::default().import_save(&save).is_ok());
+}
+
+#[test]
+fn tampered_saves_are_rejected() {
+    let save = IdleGame::default().export_save();
+    let tampered = save.replacen("\"debug_seed\":0", "\"debug_seed\":1", 1);
+    assert_ne!(tampered, save);
+    assert!(matches!(
+        IdleGame::default().import_save(&tampered),
+        Err(SaveError::ChecksumMismatch { .. })
+    ));
+}
+
+#[test]
+fn saves_without_a_checksum_still_import() {
+    // Saves exported before checksums were added are just the JSON.
+    let save = IdleGame::default().export_save();
+    let json = save.lines().next().unwrap();
+    assert!(IdleGame::default().import_save(json).is_ok());
+}
+
+#[test]
+fn backups_import_like_saves() {
+    let game = IdleGame::default();
+    let backup = game.export_backup();
+    assert!(backup.starts_with("base64:"));
+    assert!(!backup.contains('\n'));
+    assert!(IdleGame::default().import_save(&backup).is_ok());
+    // Pasting tends to pick up whitespace around it.
+    assert!(IdleGame::default()
+        .import_save(&format!("  {backup}\n"))
+        .is_ok());
+}
+
+#[test]
+fn mangled_backups_are_rejected() {
+    let backup = IdleGame::default().export_backup();
+    // Cut off partway through, it isn't valid base64 anymore, or the JSON inside doesn't add up.
+    let cut = &backup[..backup.len() / 2 + 1];
+    assert!(matches!(
+        IdleGame::default().import_save(cut),
+        Err(SaveError::Malformed(_) | SaveError::ChecksumMismatch { .. })
+    ));
+    assert!(matches!(
+        IdleGame::default().import_save("base64:not a backup!"),
+        Err(SaveError::Malformed(_))
+    ));
+    // Valid base64 that isn't a save at all.
+    assert!(matches!(
+        IdleGame::default().import_save("base64:aGVsbG8="),
+        Err(SaveError::Malformed(_))
+    ));
+}
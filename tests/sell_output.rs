@@ -0,0 +1,44 @@
+// Tests for producers that sell their output directly, instead of storing it.
+
+mod common;
+
+use common::{build_configured, int, tick_rate};
+use dull_idle_game::{GameState, Good, Producer, ProducerConfig};
+
+// A game with a built gravity drill selling its iron ore.
+fn selling_game() -> (GameState, Producer) {
+    let mut state = GameState::new(Some(0));
+    let producer = Producer::GravityDrill(Good::IronOre);
+    build_configured(
+        &mut state,
+        ProducerConfig {
+            producer,
+            sell_output: true,
+            ..ProducerConfig::default()
+        },
+    );
+    (state, producer)
+}
+
+#[test]
+fn sold_output_turns_into_money_at_the_sell_price() {
+    let (mut state, producer) = selling_game();
+    let money = state.money();
+    state.advance(&int(10), &tick_rate());
+    let ore_rate = producer.properties().recipe.outputs[&Good::IronOre].clone();
+    let income = ore_rate * state.sell_price(Good::IronOre) * int(10);
+    assert_eq!(state.money() - money, income);
+    assert_eq!(state.inventory()[&Good::IronOre], int(0));
+}
+
+#[test]
+fn sold_output_shows_up_as_money_in_the_production_table() {
+    let (state, producer) = selling_game();
+    let table = state.production_table_theoretical();
+    let ore_rate = producer.properties().recipe.outputs[&Good::IronOre].clone();
+    assert_eq!(
+        table[&Good::Money].0,
+        ore_rate * state.sell_price(Good::IronOre)
+    );
+    assert!(!table.contains_key(&Good::IronOre));
+}
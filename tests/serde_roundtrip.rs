@@ -0,0 +1,86 @@
+// Checks that the game state survives being saved and loaded, now that its maps are ordered, along with where its windows were.
+
+mod common;
+
+use common::{F, I};
+use dull_idle_game::{decode_compact, encode_compact, ElemVariant, GameState, Good, Producer};
+
+#[test]
+fn game_state_round_trips_through_json() {
+    let mut state = GameState::new(Some(0));
+    state
+        .inventory_mut()
+        .insert(Good::Coal, F::new(I::from(7), I::from(3)));
+    for producer in [
+        Producer::GravityDrill(Good::IronOre),
+        Producer::CoalDrill(Good::GoldOre),
+    ] {
+        state.add_element(
+            ElemVariant::Producer(producer),
+            &producer.to_string(),
+            false,
+        );
+    }
+    let json = serde_json::to_string(&state).unwrap();
+    let loaded: GameState = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.inventory(), state.inventory());
+    // The maps are iterated in order, so the goods come out the way the UI shows them and the elements in the order they were added.
+    let goods: Vec<Good> = loaded.inventory().keys().copied().collect();
+    assert_eq!(goods, Good::sorted());
+    let ids: Vec<usize> = loaded.elements().keys().copied().collect();
+    assert_eq!(ids, vec![0, 1]);
+}
+
+#[test]
+fn window_positions_survive_saving() {
+    let mut state = GameState::new(Some(0));
+    state.add_element(ElemVariant::Good(Good::Coal), "Coal", true);
+    let mut save = serde_json::to_value(&state).unwrap();
+    save["elements"]["0"]["window_pos"] = serde_json::json!([120.0, 45.5]);
+    save["elements"]["0"]["window_size"] = serde_json::json!([300.0, 200.0]);
+    let loaded: GameState = serde_json::from_value(save.clone()).unwrap();
+    assert_eq!(loaded.elements()[&0].window_pos, Some([120.0, 45.5]));
+    assert_eq!(loaded.elements()[&0].window_size, Some([300.0, 200.0]));
+    // Saves from before windows remembered where they were load with no position, so egui places them.
+    let element = save["elements"]["0"].as_object_mut().unwrap();
+    element.remove("window_pos");
+    element.remove("window_size");
+    let loaded: GameState = serde_json::from_value(save).unwrap();
+    assert_eq!(loaded.elements()[&0].window_pos, None);
+    assert_eq!(loaded.elements()[&0].window_size, None);
+}
+
+#[test]
+fn big_rationals_round_trip_through_the_compact_format() {
+    let mut state = GameState::new(Some(0));
+    // Numbers far too big for any machine integer, with awkward denominators, and a negative one for good measure.
+    let huge = F::new(I::from(10).pow(60u32) + I::from(1), I::from(7));
+    state.inventory_mut().insert(Good::Money, huge.clone());
+    state
+        .inventory_mut()
+        .insert(Good::Coal, F::new(I::from(-3), I::from(10).pow(30u32)));
+    state.inventory_mut().insert(Good::IronOre, &huge * &huge);
+    state.add_element(
+        ElemVariant::Producer(Producer::CoalDrill(Good::IronOre)),
+        "Drill",
+        false,
+    );
+    let compact = encode_compact(&state).unwrap();
+    let loaded: GameState = decode_compact(&compact).unwrap().unwrap();
+    assert_eq!(loaded.inventory(), state.inventory());
+    assert_eq!(
+        serde_json::to_value(&loaded).unwrap(),
+        serde_json::to_value(&state).unwrap()
+    );
+    // It's only worth it if it's actually smaller.
+    assert!(compact.len() < serde_json::to_string(&state).unwrap().len());
+}
+
+#[test]
+fn only_compact_saves_decode_as_compact() {
+    let json = serde_json::to_string(&GameState::new(Some(0))).unwrap();
+    assert!(decode_compact::<GameState>(&json).is_none());
+    assert!(decode_compact::<GameState>("msgpack:not base64!")
+        .unwrap()
+        .is_err());
+}
@@ -0,0 +1,63 @@
+// Tests for smelters, which turn ore and coal into bars.
+
+mod common;
+
+use common::{add_producer, int, tick_rate};
+use dull_idle_game::{ChainPlan, GameState, Good, Producer};
+
+// A game with a finished iron smelter, already warmed up, and nothing else.
+fn iron_smelter() -> GameState {
+    let mut state = GameState::new(Some(0));
+    let producer = Producer::Smelter(Good::IronOre);
+    add_producer(&mut state, producer);
+    state.add_good(Good::IronOre, &int(100));
+    state.add_good(Good::Coal, &int(100));
+    state.advance(&producer.properties().warmup, &tick_rate());
+    state
+}
+
+#[test]
+fn smelters_turn_ore_and_coal_into_bars() {
+    let mut state = iron_smelter();
+    let bars = state.inventory()[&Good::IronBar].clone();
+    let ore = state.inventory()[&Good::IronOre].clone();
+    let coal = state.inventory()[&Good::Coal].clone();
+    state.advance(&int(10), &tick_rate());
+    assert_eq!(state.inventory()[&Good::IronBar], bars + int(10));
+    assert_eq!(state.inventory()[&Good::IronOre], ore - int(20));
+    assert_eq!(state.inventory()[&Good::Coal], coal - int(5));
+}
+
+#[test]
+fn smelters_stall_without_coal() {
+    let mut state = iron_smelter();
+    let coal = state.inventory()[&Good::Coal].clone();
+    assert!(state.remove_good(Good::Coal, &coal));
+    let bars = state.inventory()[&Good::IronBar].clone();
+    let ore = state.inventory()[&Good::IronOre].clone();
+    state.advance(&int(5), &tick_rate());
+    assert_eq!(state.inventory()[&Good::IronBar], bars);
+    assert_eq!(state.inventory()[&Good::IronOre], ore);
+    let smelter = state.elements().values().next().unwrap();
+    assert_eq!(smelter.missing_inputs, vec![Good::Coal]);
+}
+
+#[test]
+fn only_ores_with_bars_get_smelters() {
+    let buildable = Producer::buildable();
+    assert!(buildable.contains(&Producer::Smelter(Good::GoldOre)));
+    assert!(!buildable.contains(&Producer::Smelter(Good::Coal)));
+    assert_eq!(Good::SilverOre.bar(), Some(Good::SilverBar));
+}
+
+#[test]
+fn bar_chains_include_their_drills() {
+    let plan = ChainPlan::new(Good::IronBar, &int(2));
+    assert_eq!(plan.producers[&Producer::Smelter(Good::IronOre)], 2);
+    assert_eq!(plan.producers[&Producer::CoalDrill(Good::IronOre)], 4);
+    // 2 smelters burn 1 coal a second, and 4 iron drills burn another 1. That takes 3 coal drills on coal.
+    assert_eq!(plan.producers[&Producer::CoalDrill(Good::Coal)], 3);
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Money, &plan.cost());
+    assert_eq!(state.build_chain(&plan), plan.count());
+}
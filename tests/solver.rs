@@ -0,0 +1,140 @@
+// Tests for the production solver. Check solver.rs for more info.
+
+mod common;
+
+use common::{add_producer, build_configured, int, F, I};
+use dull_idle_game::{
+    GameState, Good, GoodDefinition, GoodGroup, Inventory, Producer, ProducerCategory,
+    ProducerConfig, ProducerDefinition, Recipe,
+};
+use std::sync::Mutex;
+
+// Buys and finishes a producer, capped to the given throughput.
+fn build(state: &mut GameState, producer: Producer, max_throughput: Option<F>) {
+    build_configured(
+        state,
+        ProducerConfig {
+            producer,
+            max_throughput,
+            ..ProducerConfig::default()
+        },
+    );
+}
+
+#[test]
+fn two_stage_chain_runs_at_the_speed_of_its_inputs() {
+    let mut state = GameState::new(Some(0));
+    // A gravity drill capped to 1/8 coal a second, feeding a coal drill that wants 1/4 coal a second.
+    let eighth = F::new(I::from(1), I::from(8));
+    build(
+        &mut state,
+        Producer::GravityDrill(Good::Coal),
+        Some(eighth.clone()),
+    );
+    build(&mut state, Producer::CoalDrill(Good::IronOre), None);
+    let theoretical = state.production_table_theoretical();
+    let solution = state.production_table_sustainable();
+    assert!(solution.settled);
+    // In theory the coal drill runs flat out, but it can only ever get half the coal it needs.
+    assert_eq!(theoretical[&Good::IronOre].0, int(1));
+    assert_eq!(
+        solution.table[&Good::IronOre].0,
+        F::new(I::from(1), I::from(2))
+    );
+    // All the coal that's made gets used, and no more.
+    assert_eq!(solution.table[&Good::Coal], (eighth.clone(), eighth));
+}
+
+#[test]
+fn fully_supplied_chain_matches_the_theoretical_table() {
+    let mut state = GameState::new(Some(0));
+    build(&mut state, Producer::GravityDrill(Good::Coal), None);
+    build(&mut state, Producer::CoalDrill(Good::IronOre), None);
+    let solution = state.production_table_sustainable();
+    assert!(solution.settled);
+    assert_eq!(solution.table, state.production_table_theoretical());
+}
+
+#[test]
+fn producers_with_nothing_feeding_them_are_not_sustainable() {
+    let mut state = GameState::new(Some(0));
+    build(&mut state, Producer::CoalDrill(Good::IronOre), None);
+    // Even with a stockpile of coal, nothing makes it, so the drill stops once it's gone.
+    state.add_good(Good::Coal, &int(100));
+    let solution = state.production_table_sustainable();
+    assert_eq!(solution.table[&Good::IronOre].0, int(0));
+    assert_eq!(
+        state.production_table_theoretical()[&Good::IronOre].0,
+        int(1)
+    );
+}
+
+#[test]
+fn a_starved_consumer_leaves_its_share_to_the_others() {
+    let mut state = GameState::new(Some(0));
+    // A quarter of a coal a second, shared by a coal drill that wants 1/4 and a smelter that wants 1/2.
+    let quarter = F::new(I::from(1), I::from(4));
+    build(
+        &mut state,
+        Producer::GravityDrill(Good::Coal),
+        Some(quarter.clone()),
+    );
+    build(&mut state, Producer::CoalDrill(Good::IronOre), None);
+    build(&mut state, Producer::Smelter(Good::GoldOre), None);
+    let solution = state.production_table_sustainable();
+    assert!(solution.settled);
+    // Nothing mines gold ore, so the smelter never runs, and all the coal goes to the drill.
+    assert_eq!(solution.table[&Good::GoldBar].0, int(0));
+    assert_eq!(solution.table[&Good::IronOre].0, int(1));
+    assert_eq!(solution.table[&Good::Coal], (quarter.clone(), quarter));
+}
+
+// Two producers that feed each other, losing half on the way round. It's registered once, since the registry is shared.
+fn leaky_cycle() -> (Producer, Producer) {
+    static REGISTERED: Mutex<Option<(Producer, Producer)>> = Mutex::new(None);
+    *REGISTERED.lock().unwrap().get_or_insert_with(|| {
+        let good = |key: &str| {
+            GameState::register_good(GoodDefinition {
+                key: String::from(key),
+                name: String::from(key),
+                group: GoodGroup::Ore,
+                difficulty: 1,
+                capacity: Some(100),
+                base_price: 0,
+                time_limit_ms: None,
+                old_keys: Vec::new(),
+            })
+            .unwrap()
+        };
+        let (x, y) = (good("SolverCycleX"), good("SolverCycleY"));
+        let producer = |key: &str, input: Good, output: Good, made: F| {
+            GameState::register_producer(ProducerDefinition {
+                key: String::from(key),
+                name: String::from(key),
+                cost: int(10),
+                recipe: Recipe::new(
+                    Inventory::from([(input, int(1))]),
+                    Inventory::from([(output, made)]),
+                ),
+                warmup: int(0),
+                category: ProducerCategory::Smelter,
+            })
+            .unwrap()
+        };
+        (
+            producer("SolverCycleForward", x, y, int(1)),
+            producer("SolverCycleBack", y, x, F::new(I::from(1), I::from(2))),
+        )
+    })
+}
+
+#[test]
+fn a_leaky_cycle_never_settles() {
+    let (forward, back) = leaky_cycle();
+    let mut state = GameState::new(Some(0));
+    add_producer(&mut state, forward);
+    add_producer(&mut state, back);
+    // Each time round the cycle halves, so it shrinks towards nothing without ever getting there.
+    let solution = state.production_table_sustainable();
+    assert!(!solution.settled);
+}
@@ -0,0 +1,29 @@
+// Tests for the player's stats, and the ore per minute estimate built on them.
+
+mod common;
+
+use common::int;
+use dull_idle_game::{expected_solve_seconds, ores_per_minute, GameState, Good};
+
+#[test]
+fn solves_reward_ore_and_keep_the_best_time() {
+    let mut state = GameState::new(Some(0));
+    state.record_solve(Good::IronOre, 3.0, &int(1));
+    state.record_solve(Good::IronOre, 1.5, &int(1));
+    state.record_solve(Good::IronOre, 2.0, &int(1));
+    assert_eq!(state.inventory()[&Good::IronOre], int(3));
+    assert_eq!(state.stats().solves[&Good::IronOre], 3);
+    assert_eq!(state.stats().best_solve[&Good::IronOre], 1.5);
+    assert!(!state.stats().best_solve.contains_key(&Good::GoldOre));
+}
+
+#[test]
+fn estimates_use_the_best_time_when_there_is_one() {
+    assert_eq!(expected_solve_seconds(5, 0.05, Some(2.0)), 2.0);
+    assert_eq!(ores_per_minute(5, 0.05, Some(2.0)), 30.0);
+    // Without a record, each of the four clicks after the first takes the estimated 0.4 seconds.
+    assert!((expected_solve_seconds(5, 0.05, None) - 1.6).abs() < 1e-9);
+    // A slow click interval is the floor for every click.
+    assert_eq!(expected_solve_seconds(5, 1.0, None), 4.0);
+    assert!(ores_per_minute(1, 0.0, None).is_finite());
+}
@@ -0,0 +1,41 @@
+// Tests for storage upgrades. Check GameState::upgrade_storage for more info.
+
+mod common;
+
+use common::int;
+use dull_idle_game::{GameState, Good, GoodGroup, StorageError};
+
+#[test]
+fn upgrades_double_the_capacity_and_cost_more_each_time() {
+    let mut state = GameState::new(Some(0));
+    let capacity = state.capacity(Good::IronOre).unwrap();
+    state.add_good(Good::Money, &int(400));
+    state.upgrade_storage(GoodGroup::Ore).unwrap();
+    assert_eq!(state.capacity(Good::IronOre).unwrap(), &capacity * int(2));
+    assert_eq!(state.money(), int(300));
+    assert_eq!(state.storage_upgrade_cost(GoodGroup::Ore), Some(int(300)));
+}
+
+#[test]
+fn groups_without_capacity_cant_be_upgraded() {
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Money, &int(1000));
+    assert_eq!(
+        state.upgrade_storage(GoodGroup::Money),
+        Err(StorageError::NoCapacity(GoodGroup::Money))
+    );
+    assert_eq!(state.storage_level(GoodGroup::Money), 0);
+    assert_eq!(state.money(), int(1000));
+}
+
+#[test]
+fn unaffordable_upgrades_take_nothing() {
+    let mut state = GameState::new(Some(0));
+    state.add_good(Good::Money, &int(99));
+    assert!(matches!(
+        state.upgrade_storage(GoodGroup::Ore),
+        Err(StorageError::InsufficientFunds(_))
+    ));
+    assert_eq!(state.storage_level(GoodGroup::Ore), 0);
+    assert_eq!(state.money(), int(99));
+}
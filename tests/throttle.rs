@@ -0,0 +1,54 @@
+// Tests for auto-throttling producers on scarce inputs. Check GameState::throttles for more info.
+
+mod common;
+
+use common::{build, build_configured, int, tick_rate, F, I};
+use dull_idle_game::{GameState, Good, Producer, ProducerConfig};
+
+// A coal drill needing 1/4 coal per second, fed by a gravity drill capped at 1/8 coal per second. So there's only half the coal it needs.
+fn starved_chain(auto_throttle: bool) -> GameState {
+    let mut state = GameState::new(Some(0));
+    state.set_auto_throttle(auto_throttle);
+    build_configured(
+        &mut state,
+        ProducerConfig {
+            producer: Producer::GravityDrill(Good::Coal),
+            max_throughput: Some(F::new(I::from(1), I::from(8))),
+            ..ProducerConfig::default()
+        },
+    );
+    build(&mut state, Producer::CoalDrill(Good::IronOre));
+    // The coal made while the drill was being built would last a while, so it's thrown out to get straight to the shortage.
+    state.inventory_mut().insert(Good::Coal, int(0));
+    state
+}
+
+// How much iron ore each of the next few ticks made.
+fn ore_per_tick(state: &mut GameState, ticks: usize) -> Vec<F> {
+    let mut made = Vec::new();
+    for _ in 0..ticks {
+        let before = state.inventory()[&Good::IronOre].clone();
+        state.advance(&tick_rate(), &tick_rate());
+        made.push(state.inventory()[&Good::IronOre].clone() - before);
+    }
+    made
+}
+
+#[test]
+fn without_throttling_the_chain_runs_in_fits_and_starts() {
+    let mut state = starved_chain(false);
+    let made = ore_per_tick(&mut state, 20);
+    assert!(made.contains(&int(0)));
+    assert!(made.iter().any(|amount| amount > &int(0)));
+}
+
+#[test]
+fn throttled_chain_settles_into_a_steady_state() {
+    let mut state = starved_chain(true);
+    // The first couple of ticks are left out, while the coal made during them settles.
+    ore_per_tick(&mut state, 2);
+    let made = ore_per_tick(&mut state, 20);
+    // Half the coal it needs, so half the ore, every single tick.
+    let expected = F::new(I::from(1), I::from(2)) * tick_rate();
+    assert!(made.iter().all(|amount| amount == &expected), "{made:?}");
+}
@@ -0,0 +1,36 @@
+// Tests for capping a producer's throughput. Check Element::throughput_scale for more info.
+
+mod common;
+
+use common::{build_configured, int, tick_rate, F, I};
+use dull_idle_game::{GameState, Good, Producer, ProducerConfig};
+
+#[test]
+fn capped_producer_never_exceeds_its_throughput() {
+    let mut state = GameState::new(Some(0));
+    // Way more coal than the drill could ever burn, so only the cap holds it back.
+    state.add_good(Good::Coal, &int(1000));
+    let half = F::new(I::from(1), I::from(2));
+    build_configured(
+        &mut state,
+        ProducerConfig {
+            producer: Producer::CoalDrill(Good::IronOre),
+            max_throughput: Some(half.clone()),
+            ..ProducerConfig::default()
+        },
+    );
+    let (ore, coal) = (
+        state.inventory()[&Good::IronOre].clone(),
+        state.inventory()[&Good::Coal].clone(),
+    );
+    // The production table knows about the cap too.
+    let (made, used) = state.production_table_theoretical()[&Good::IronOre].clone();
+    assert_eq!((made, used), (half.clone(), int(0)));
+    state.advance(&int(10), &tick_rate());
+    assert_eq!(&state.inventory()[&Good::IronOre] - ore, int(5));
+    // Inputs slow down along with the outputs. 10 seconds at half of 1/4 coal a second.
+    assert_eq!(
+        coal - &state.inventory()[&Good::Coal],
+        F::new(I::from(5), I::from(4))
+    );
+}
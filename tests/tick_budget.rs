@@ -0,0 +1,62 @@
+// Tests for the budgeted tick loop, using a fake clock so the results don't depend on how fast the machine is.
+
+mod common;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use common::{coal_drill_game, int, tick_rate, F, I};
+use dull_idle_game::{Clock, Good};
+use std::cell::Cell;
+
+// A clock that moves forward by a fixed step every time it's asked the time, as if every tick took that long.
+struct FakeClock {
+    time: Cell<DateTime<Utc>>,
+    step: Duration,
+}
+
+impl FakeClock {
+    fn new(step_ms: i64) -> Self {
+        Self {
+            time: Cell::new(Utc.timestamp_opt(0, 0).unwrap()),
+            step: Duration::milliseconds(step_ms),
+        }
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> DateTime<Utc> {
+        let now = self.time.get();
+        self.time.set(now + self.step);
+        now
+    }
+}
+
+#[test]
+fn budget_cuts_off_the_tick_loop() {
+    // Coal drills have inputs, so they're ticked one tick at a time.
+    let mut state = coal_drill_game(1000);
+    // Every read of the clock "takes" 1 ms. The start is read at 0 ms, and the budget is checked before every tick,
+    // so ticks happen at 1 ms through 7 ms, and the check at 8 ms stops the loop.
+    let clock = FakeClock::new(1);
+    let leftover =
+        state.advance_budgeted(&int(10), &tick_rate(), Duration::milliseconds(8), &clock);
+    assert_eq!(
+        state.inventory()[&Good::IronOre],
+        F::new(I::from(7), I::from(20))
+    );
+    // Whatever wasn't simulated is handed back, to be carried over to the next frame.
+    assert_eq!(leftover, int(10) - F::new(I::from(7), I::from(20)));
+}
+
+#[test]
+fn budget_does_everything_when_there_is_time() {
+    let mut state = coal_drill_game(1000);
+    let clock = FakeClock::new(0);
+    let leftover = state.advance_budgeted(
+        &F::new(I::from(41), I::from(40)),
+        &tick_rate(),
+        Duration::milliseconds(8),
+        &clock,
+    );
+    assert_eq!(state.inventory()[&Good::IronOre], int(1));
+    assert_eq!(leftover, F::new(I::from(1), I::from(40)));
+}
@@ -0,0 +1,196 @@
+// Deterministic tests for the tick loop. The simulation is exact rational math, so every amount can be checked exactly.
+
+mod common;
+
+use common::{add_producer, amount, build_configured, coal_drill_game, int, tick_rate, F, I};
+use dull_idle_game::{GameEvent, GameState, Good, GoodAlarm, Producer, ProducerConfig};
+
+#[test]
+fn coal_drill_runs_while_it_has_coal() {
+    let mut state = coal_drill_game(10);
+    let leftover = state.advance(&int(20), &tick_rate());
+    assert_eq!(leftover, int(0));
+    // 1 iron ore per second, at a cost of 1/4 coal per second.
+    assert_eq!(amount(&state, Good::IronOre), int(20));
+    assert_eq!(amount(&state, Good::Coal), int(5));
+}
+
+#[test]
+fn coal_drill_stalls_when_coal_runs_out() {
+    let mut state = coal_drill_game(10);
+    state.advance(&int(60), &tick_rate());
+    // 10 coal lasts exactly 40 seconds. After that, the drill stalls and nothing else changes.
+    assert_eq!(amount(&state, Good::IronOre), int(40));
+    assert_eq!(amount(&state, Good::Coal), int(0));
+}
+
+#[test]
+fn starved_coal_drill_says_it_needs_coal() {
+    let mut state = coal_drill_game(0);
+    state.advance(&int(5), &tick_rate());
+    let starved: Vec<GameEvent> = state
+        .drain_events()
+        .into_iter()
+        .filter(|event| matches!(event, GameEvent::ProducerStarved(..)))
+        .collect();
+    assert_eq!(starved.len(), 1);
+    assert_eq!(
+        starved[0].message(),
+        "0: Coal Drill (Iron Ore) needs more Coal"
+    );
+    let element = state.elements().values().next().unwrap();
+    assert_eq!(element.missing_inputs, vec![Good::Coal]);
+}
+
+#[test]
+fn coal_drill_stalls_on_a_partial_tick() {
+    // 1/100 coal isn't enough for a whole tick (1/80), and producers are all or nothing, so nothing happens.
+    let mut state = coal_drill_game(0);
+    state
+        .inventory_mut()
+        .insert(Good::Coal, F::new(I::from(1), I::from(100)));
+    state.advance(&int(5), &tick_rate());
+    assert_eq!(amount(&state, Good::IronOre), int(0));
+    assert_eq!(amount(&state, Good::Coal), F::new(I::from(1), I::from(100)));
+}
+
+#[test]
+fn advance_returns_the_leftover_time() {
+    let mut state = coal_drill_game(10);
+    let leftover = state.advance(&F::new(I::from(41), I::from(40)), &tick_rate());
+    // 41/40 seconds is 20 whole ticks plus half a tick.
+    assert_eq!(leftover, F::new(I::from(1), I::from(40)));
+    assert_eq!(amount(&state, Good::IronOre), int(1));
+}
+
+#[test]
+fn advancing_in_pieces_matches_advancing_at_once() {
+    let mut at_once = coal_drill_game(10);
+    at_once.advance(&int(30), &tick_rate());
+    let mut in_pieces = coal_drill_game(10);
+    for _ in 0..30 {
+        in_pieces.advance(&int(1), &tick_rate());
+    }
+    assert_eq!(at_once.inventory(), in_pieces.inventory());
+}
+
+#[test]
+fn gravity_drill_stops_at_capacity() {
+    // Gravity drills have no inputs, so they're advanced in one step. The capacity still has to hold.
+    let mut state = GameState::new(Some(0));
+    let capacity = state.capacity(Good::IronOre).unwrap();
+    state
+        .inventory_mut()
+        .insert(Good::IronOre, &capacity - int(1));
+    add_producer(&mut state, Producer::GravityDrill(Good::IronOre));
+    state.advance(&int(100), &tick_rate());
+    assert_eq!(amount(&state, Good::IronOre), capacity);
+}
+
+#[test]
+fn producers_only_count_as_producing_once_they_make_something() {
+    let mut state = coal_drill_game(0);
+    state.advance(&int(1), &tick_rate());
+    let drill = state.elements().values().next().unwrap();
+    assert!(!drill.has_produced);
+    state.add_good(Good::Coal, &int(1));
+    state.advance(&int(1), &tick_rate());
+    let drill = state.elements().values().next().unwrap();
+    assert!(drill.has_produced);
+}
+
+#[test]
+fn coarse_advance_matches_advance_when_nothing_runs_out() {
+    let mut fine = coal_drill_game(1000);
+    let mut coarse = fine.clone();
+    fine.advance(&int(300), &tick_rate());
+    coarse.advance_coarse(&int(300), &tick_rate());
+    assert_eq!(amount(&fine, Good::IronOre), amount(&coarse, Good::IronOre));
+    assert_eq!(amount(&fine, Good::Coal), amount(&coarse, Good::Coal));
+}
+
+#[test]
+fn coarse_advance_is_at_most_a_step_short_when_inputs_run_out() {
+    // 7 coal lasts the drill 28 seconds. 20 minutes in 1200 steps is 1 second a step.
+    let mut fine = coal_drill_game(7);
+    let mut coarse = fine.clone();
+    fine.advance(&int(1200), &tick_rate());
+    coarse.advance_coarse(&int(1200), &tick_rate());
+    let (fine_ore, coarse_ore) = (amount(&fine, Good::IronOre), amount(&coarse, Good::IronOre));
+    assert_eq!(fine_ore, int(28));
+    assert!(coarse_ore <= fine_ore);
+    assert!(fine_ore - coarse_ore <= int(1));
+}
+
+// Runs the game the way the frame loop does: each frame's time is scaled by the speed and added to a timer, and whatever isn't a whole tick is kept.
+fn run_frames(state: &mut GameState, frames: usize, frame: &F, speed: &F) {
+    let mut timer = int(0);
+    for _ in 0..frames {
+        timer += frame * speed;
+        timer = state.advance(&timer, &tick_rate());
+    }
+}
+
+#[test]
+fn faster_speeds_are_exactly_the_same_as_more_time() {
+    // Frames of 37 ms, which isn't a whole number of ticks, so the leftovers have to add up exactly too.
+    let frame = F::new(I::from(37), I::from(1000));
+    let mut fast = coal_drill_game(30);
+    run_frames(&mut fast, 600, &frame, &int(5));
+    let mut slow = coal_drill_game(30);
+    run_frames(&mut slow, 3000, &frame, &int(1));
+    assert_eq!(fast.inventory(), slow.inventory());
+    assert_eq!(amount(&fast, Good::IronOre), int(111));
+}
+
+// A game with a built gravity drill on iron ore, throwing away whatever doesn't fit, so nothing can ever stop it.
+fn gravity_drill_game() -> GameState {
+    let mut state = GameState::new(Some(0));
+    build_configured(
+        &mut state,
+        ProducerConfig {
+            producer: Producer::GravityDrill(Good::IronOre),
+            discard_excess: true,
+            ..ProducerConfig::default()
+        },
+    );
+    state
+}
+
+#[test]
+fn unconstrained_drills_come_out_the_same_in_one_step_or_tick_by_tick() {
+    let mut analytical = gravity_drill_game();
+    let mut iterative = gravity_drill_game();
+    // An alarm on the drill's output has to be checked every tick, so it keeps the drill off the one step path.
+    // It never goes off, so it doesn't change anything else.
+    iterative.set_alarm(
+        Good::IronOre,
+        GoodAlarm {
+            alarm_high: Some(int(1_000_000)),
+            ..GoodAlarm::default()
+        },
+    );
+    let seconds = int(100) + F::new(I::from(1), I::from(30));
+    let analytical_leftover = analytical.advance(&seconds, &tick_rate());
+    let iterative_leftover = iterative.advance(&seconds, &tick_rate());
+    assert_eq!(analytical_leftover, iterative_leftover);
+    assert_eq!(
+        amount(&analytical, Good::IronOre),
+        amount(&iterative, Good::IronOre)
+    );
+    assert_eq!(analytical.inventory(), iterative.inventory());
+}
+
+#[test]
+fn nearly_full_outputs_fill_up_exactly() {
+    // Less room than a tick's worth of ore. The drill only runs as much of the tick as fits, and uses only that much coal.
+    let mut state = coal_drill_game(10);
+    let capacity = state.capacity(Good::IronOre).unwrap();
+    let room = F::new(I::from(1), I::from(100));
+    state
+        .inventory_mut()
+        .insert(Good::IronOre, &capacity - &room);
+    state.advance(&int(1), &tick_rate());
+    assert_eq!(amount(&state, Good::IronOre), capacity);
+    assert_eq!(amount(&state, Good::Coal), int(10) - room / int(4));
+}
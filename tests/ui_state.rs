@@ -0,0 +1,34 @@
+// Checks that the UI state that's meant to stick, like the selected tab, survives saving and loading.
+
+mod common;
+
+use common::exported_json;
+use dull_idle_game::IdleGame;
+use serde_json::{json, Value};
+
+// Saves the game, and loads the save into a fresh game.
+fn round_trip(save: &Value) -> Value {
+    let mut game = IdleGame::default();
+    game.import_save(&save.to_string()).unwrap();
+    exported_json(&game)
+}
+
+#[test]
+fn selected_tab_round_trips() {
+    let mut save = exported_json(&IdleGame::default());
+    save["ui_state"]["selection"] = json!("Graph");
+    let loaded = round_trip(&save);
+    assert_eq!(loaded["ui_state"]["selection"], json!("Graph"));
+}
+
+#[test]
+fn selected_tab_is_migrated_from_version_1() {
+    let mut save = exported_json(&IdleGame::default());
+    let object = save.as_object_mut().unwrap();
+    object.remove("ui_state");
+    object.insert(String::from("selection"), json!("Metallurgy"));
+    object.insert(String::from("save_version"), json!(1));
+    let loaded = round_trip(&save);
+    assert_eq!(loaded["ui_state"]["selection"], json!("Metallurgy"));
+    assert!(loaded.get("selection").is_none());
+}
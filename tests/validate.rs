@@ -0,0 +1,7 @@
+// The game data has to pass its own validation. Check idle/validate.rs for what's checked.
+
+#[test]
+fn game_data_is_valid() {
+    let problems = dull_idle_game::validate_data();
+    assert!(problems.is_empty(), "{problems:#?}");
+}
@@ -0,0 +1,79 @@
+// Tests for repairing the duplicate window ids in saves from before window ids were built from element ids.
+
+use dull_idle_game::{ElemVariant, GameState, Good, Producer};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+// A save with three drills, where the last two have the same window id as the first, like the old id scheme could leave behind.
+fn damaged_save() -> GameState {
+    let mut state = GameState::new(Some(0));
+    let producer = Producer::CoalDrill(Good::IronOre);
+    for _ in 0..3 {
+        state.add_element(
+            ElemVariant::Producer(producer),
+            &producer.to_string(),
+            false,
+        );
+    }
+    let mut save: Value = serde_json::to_value(&state).unwrap();
+    for id in ["1", "2"] {
+        save["elements"][id]["window_id"] = json!("0: Coal Drill (Iron Ore)");
+    }
+    serde_json::from_value(save).unwrap()
+}
+
+#[test]
+fn duplicate_window_ids_are_made_unique() {
+    let mut state = damaged_save();
+    assert_eq!(state.repair_window_ids(), 2);
+    let ids: HashSet<String> = state
+        .elements()
+        .values()
+        .map(|e| e.window_id.clone())
+        .collect();
+    assert_eq!(ids.len(), 3);
+    // The first one keeps its window id, and the others get fresh ones built from their new ids.
+    assert_eq!(state.elements()[&0].window_id, "0: Coal Drill (Iron Ore)");
+    for (id, element) in state.elements().iter() {
+        assert_eq!(element.window_id, format!("{id}: Coal Drill (Iron Ore)"));
+    }
+    // Anything added afterwards doesn't collide either.
+    let id = state.add_element(ElemVariant::Producer(Producer::None), "None", false);
+    assert!(!ids.contains(&state.elements()[&id].window_id));
+}
+
+#[test]
+fn healthy_saves_are_left_alone() {
+    let mut state = damaged_save();
+    state.repair_window_ids();
+    let before: Vec<String> = state
+        .elements()
+        .values()
+        .map(|e| e.window_id.clone())
+        .collect();
+    assert_eq!(state.repair_window_ids(), 0);
+    let after: Vec<String> = state
+        .elements()
+        .values()
+        .map(|e| e.window_id.clone())
+        .collect();
+    assert_eq!(before, after);
+}
+
+#[test]
+fn ids_are_never_reused() {
+    let mut state = GameState::new(Some(0));
+    let first = state.add_element(ElemVariant::Good(Good::Coal), "Coal", false);
+    let second = state.add_element(ElemVariant::Good(Good::Coal), "Coal", false);
+    assert_ne!(first, second);
+    // The last element going away doesn't free its id up again.
+    state.remove_element(second);
+    let third = state.add_element(ElemVariant::Good(Good::Coal), "Coal", false);
+    assert!(third > second);
+    // The counter is saved, so ids stay unique after loading too.
+    state.remove_element(third);
+    let json = serde_json::to_string(&state).unwrap();
+    let mut loaded: GameState = serde_json::from_str(&json).unwrap();
+    let fourth = loaded.add_element(ElemVariant::Good(Good::Coal), "Coal", false);
+    assert!(fourth > third);
+}